@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use crate::Result;
-use crate::application::transfers::{CategoryDto, ProductDto, UserDto};
+use crate::application::transfers::{CategoryDto, PageDto, ProductDto, UserDto};
 ///
 /// 商品検索アプリケーションサービス
 ///
@@ -9,7 +9,7 @@ pub trait ProductSearchAppService : Send + Sync + 'static {
     type Pool;
     type Form;
     // 検索処理
-    async fn search(&self , pool: &Self::Pool , form: &Self::Form) -> Result<Vec<ProductDto>>;
+    async fn search(&self , pool: &Self::Pool , form: &Self::Form) -> Result<PageDto<ProductDto>>;
 }
 ///
 /// 商品登録アプリケーションサービス
@@ -24,14 +24,38 @@ pub trait ProductRegisterAppService: Send + Sync + 'static {
     async fn execute(&self , pool:&Self::Pool , form: &Self::Form) -> Result<ProductDto>;
 }
 ///
+/// 商品変更アプリケーションサービス
+///
+#[async_trait]
+pub trait ProductUpdateAppService: Send + Sync + 'static {
+    type Pool;
+    type Form;
+    // カテゴリリストの取得
+    async fn categories(&self , pool:&Self::Pool) -> Result<Vec<CategoryDto>>;
+    // 変更対象商品の取得
+    async fn product(&self , pool:&Self::Pool , id: i32) -> Result<ProductDto>;
+    // 商品の変更
+    async fn execute(&self , pool:&Self::Pool , form: &Self::Form) -> Result<ProductDto>;
+}
+///
+/// 商品削除アプリケーションサービス
+///
+#[async_trait]
+pub trait ProductDeleteAppService: Send + Sync + 'static {
+    type Pool;
+    type Form;
+    // 商品の削除
+    async fn execute(&self , pool:&Self::Pool , form: &Self::Form) -> Result<ProductDto>;
+}
+///
 /// 認証アプリケーションサービス
 ///
 #[async_trait]
 pub trait AuthenticateAppService: Send + Sync + 'static {
     type Pool;
     type Form;
-    // ユーザーの認証
-    async fn execute(&self , pool:&Self::Pool , form: &Self::Form) -> Result<UserDto>;
+    // ユーザーの認証、clientは失敗回数を数えるクライアントの識別子(IPアドレスなど)
+    async fn execute(&self , pool:&Self::Pool , client: &str , form: &Self::Form) -> Result<UserDto>;
 }
 ///
 /// ユーザー登録アプリケーションサービス
@@ -43,3 +67,28 @@ pub trait UserRegisterAppService: Send + Sync + 'static {
     // ユーザーの登録
     async fn execute(&self , pool:&Self::Pool , form: &Self::Form) -> Result<()>;
 }
+///
+/// パスワード変更アプリケーションサービス
+///
+#[async_trait]
+pub trait PasswordChangeAppService: Send + Sync + 'static {
+    type Pool;
+    type Form;
+    // 認証済ユーザーのパスワードの変更
+    async fn execute(&self , pool:&Self::Pool , user_id: &str , form: &Self::Form) -> Result<()>;
+}
+///
+/// トークンアプリケーションサービス
+///
+#[async_trait]
+pub trait TokenAppService: Send + Sync + 'static {
+    type Pool;
+    // 認証したユーザーにリフレッシュトークンを発行する
+    async fn issue(&self , pool:&Self::Pool , user: &UserDto) -> Result<String>;
+    // リフレッシュトークンをローテーションし、新しいトークンとユーザーを返す
+    async fn refresh(&self , pool:&Self::Pool , refresh_token: &str) -> Result<(String , UserDto)>;
+    // アクセストークン(jti)とリフレッシュトークンを失効させる
+    async fn revoke(&self , pool:&Self::Pool , jti: &str , expires_at: u64 , refresh_token: Option<&str>) -> Result<()>;
+    // 有効期限内の失効したアクセストークン
+    async fn denied(&self , pool:&Self::Pool) -> Result<Vec<(String , u64)>>;
+}
@@ -1,10 +1,15 @@
 use std::sync::Arc;
 use async_trait::async_trait;
 use sea_orm::DatabaseConnection;
+use crate::infrastructure::transaction::{TransactionManager, UnitOfWork};
 use crate::application::app_service::AuthenticateAppService;
 use crate::application::transfers::{EntityToDto, UserDto};
-use crate::Result;
-use crate::domain::services::UserService;
+use crate::{AppError, Result};
+use crate::domain::entities::User;
+use crate::domain::lockout_policy::LockoutPolicy;
+use crate::domain::services::{LoginAttemptService, UserService};
+use crate::domain::values::users::Credentials;
+use crate::service::sea_orm::login_attempt::LoginAttemptServiceImpl;
 use crate::service::sea_orm::user::UserServiceImpl;
 use crate::presentation::forms::{FormToDomain, LoginForm};
 
@@ -12,22 +17,132 @@ use crate::presentation::forms::{FormToDomain, LoginForm};
 ///
 /// ユーザー認証アプリケーションサービスの実装
 ///
-pub struct AuthenticateAppServiceImpl{
-    service: Arc<dyn UserService<Database=DatabaseConnection>>
+pub struct AuthenticateAppServiceImpl<D: TransactionManager>{
+    service: Arc<dyn UserService<Transaction=D::Transaction>> ,
+    // 認証の失敗回数とロックアウト
+    attempts: Arc<dyn LoginAttemptService<Transaction=D::Transaction>>
 }
-impl AuthenticateAppServiceImpl{
-    pub fn new() -> Arc<dyn AuthenticateAppService<Pool=DatabaseConnection , Form=LoginForm>>{
-        Arc::new(Self{service:UserServiceImpl::new()})
+impl AuthenticateAppServiceImpl<DatabaseConnection>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(policy: LockoutPolicy) -> Arc<dyn AuthenticateAppService<Pool=DatabaseConnection , Form=LoginForm>>{
+        Arc::new(Self{ service: UserServiceImpl::new() , attempts: LoginAttemptServiceImpl::new(policy) })
+    }
+}
+impl<D: TransactionManager> AuthenticateAppServiceImpl<D>{
+    // 指定されたサービスを利用するインスタンスを返す
+    pub fn with_services(service: Arc<dyn UserService<Transaction=D::Transaction>> ,
+                         attempts: Arc<dyn LoginAttemptService<Transaction=D::Transaction>>)
+        -> Arc<dyn AuthenticateAppService<Pool=D , Form=LoginForm>> {
+        Arc::new(Self{ service , attempts })
+    }
+    // ロックされていないことを確認して認証し、結果を記録する
+    async fn login(&self , tran: &D::Transaction , client: &str , credentials: &Credentials) -> Result<User> {
+        self.attempts.check(tran , &credentials.user_name , client).await?;
+        match self.service.authenticate(tran , credentials).await {
+            Ok(user) => {
+                self.attempts.succeeded(tran , &user , client).await?;
+                Ok(user)
+            },
+            Err(AppError::Unauthorized(message)) => {
+                self.attempts.failed(tran , &credentials.user_name , client).await?;
+                Err(AppError::Unauthorized(message))
+            },
+            Err(error) => Err(error)
+        }
     }
 }
 #[async_trait]
-impl AuthenticateAppService for AuthenticateAppServiceImpl{
-    type Pool = DatabaseConnection;
+impl<D: TransactionManager> AuthenticateAppService for AuthenticateAppServiceImpl<D>{
+    type Pool = D;
     type Form = LoginForm;
 
-    async fn execute(&self, pool: &Self::Pool, form: &Self::Form) -> Result<UserDto> {
-        let user = form.convert()?;
-        let result = self.service.authenticate(pool , &user).await?;
-        Ok(UserDto::convert(&result))
+    async fn execute(&self, pool: &Self::Pool, client: &str, form: &Self::Form) -> Result<UserDto> {
+        let credentials: Credentials = form.convert()?;
+        // 旧方式のパスワードの変換も同じトランザクションで確定する
+        // 認証に失敗した場合も失敗回数と認証イベントは確定させる
+        let uow = UnitOfWork::begin(pool).await?;
+        match self.login(uow.transaction() , client , &credentials).await {
+            Ok(user) => {
+                let user = uow.complete(Ok(user)).await?;
+                Ok(UserDto::convert(&user))
+            },
+            Err(AppError::Unauthorized(message)) => {
+                uow.complete(Ok(())).await?;
+                Err(AppError::Unauthorized(message))
+            },
+            Err(error) => uow.complete(Err(error)).await
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::domain::entities::AuthOutcome;
+    use crate::domain::values::ValueInto;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::memory::repositories::login_attempt::{AuthEventRepositoryImpl, LoginAttemptRepositoryImpl};
+    use crate::infrastructure::memory::repositories::user::UserRepositoryImpl;
+
+    fn service(policy: LockoutPolicy) -> Arc<dyn AuthenticateAppService<Pool=MemoryDatabase , Form=LoginForm>> {
+        AuthenticateAppServiceImpl::with_services(
+            UserServiceImpl::with_repository(UserRepositoryImpl::new()) ,
+            LoginAttemptServiceImpl::with_repositories(LoginAttemptRepositoryImpl::new() , AuthEventRepositoryImpl::new() , policy))
+    }
+    fn form(name: &str , password: &str) -> LoginForm {
+        LoginForm{ name: Some(name.to_string()) , password: Some(password.to_string()) }
+    }
+
+    #[actix::test]
+    async fn execute() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = service(LockoutPolicy::default());
+        let form = form("user001" , "pass001");
+        // SHA3-512のパスワードで認証し、Argon2idに変換される
+        let user = service.execute(&database , "192.0.2.1" , &form).await?;
+        assert!(user.password.starts_with("$argon2id$"));
+        let store = database.snapshot()?;
+        assert_eq!(store.users.get(&user.user_id).unwrap().password.value() , user.password);
+        // 変換後のパスワードでも認証できる
+        service.execute(&database , "192.0.2.1" , &form).await?;
+        assert_eq!(database.snapshot()?.auth_events.iter().filter(|event| event.outcome == AuthOutcome::Success).count() , 2);
+        Ok(())
+    }
+
+    // 存在しないユーザー名とパスワードの誤りは区別できない
+    #[actix::test]
+    async fn invalid_credentials() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = service(LockoutPolicy::default());
+        let wrong_password = service.execute(&database , "192.0.2.1" , &form("user001" , "pass002")).await.err().unwrap();
+        let unknown_name = service.execute(&database , "192.0.2.1" , &form("nobody01" , "pass001")).await.err().unwrap();
+        assert!(matches!(wrong_password , AppError::Unauthorized(_)));
+        assert_eq!(wrong_password.to_string() , unknown_name.to_string());
+        assert_eq!(wrong_password.to_string() , "ユーザー名またはパスワードが異なります。");
+        // 失敗回数と認証イベントはエラーでもコミットされる
+        let store = database.snapshot()?;
+        assert_eq!(store.login_attempts["user:nobody01"].failures , 1);
+        assert_eq!(store.login_attempts["client:192.0.2.1"].failures , 2);
+        assert_eq!(store.auth_events.len() , 2);
+        Ok(())
     }
-}
\ No newline at end of file
+
+    // 失敗回数がしきい値に達すると正しいパスワードでも認証しない
+    #[actix::test]
+    async fn lockout() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = service(LockoutPolicy{ user_threshold: 2 , ..Default::default() });
+        for _ in 0..2 {
+            assert!(service.execute(&database , "192.0.2.1" , &form("user001" , "wrong")).await.is_err());
+        }
+        let error = service.execute(&database , "192.0.2.2" , &form("user001" , "pass001")).await.err().unwrap();
+        assert_eq!(error.to_string() , "認証の失敗が続いたためロックされています。60秒後に再度お試し下さい。");
+        let store = database.snapshot()?;
+        assert_eq!(store.auth_events.last().unwrap().outcome , AuthOutcome::Locked);
+        // ロック中は照合しないためパスワードは変換されない
+        assert!(!store.users.values().any(|user| user.password.value().starts_with("$argon2id$")));
+        // 他のユーザーは認証できる
+        service.execute(&database , "192.0.2.1" , &form("user002" , "pass002")).await?;
+        Ok(())
+    }
+}
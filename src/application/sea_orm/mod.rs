@@ -1,4 +1,9 @@
 pub mod product_search;
 pub mod product_register;
+pub mod product_update;
+pub mod product_delete;
 pub mod authenticate;
+pub mod user_register;
+pub mod password_change;
+pub mod token;
 pub mod provider_impl;
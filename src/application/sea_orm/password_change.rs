@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use crate::infrastructure::transaction::{TransactionManager, UnitOfWork};
+use crate::application::app_service::PasswordChangeAppService;
+use crate::{AppError, Result};
+use crate::domain::password_policy::PasswordPolicy;
+use crate::domain::services::UserService;
+use crate::domain::values::users::{PasswordChange, UserId};
+use crate::service::sea_orm::user::UserServiceImpl;
+use crate::presentation::forms::{FormToDomain, PasswordChangeForm};
+use crate::presentation::validate::{FieldError, ValidationError};
+
+
+///
+/// パスワード変更アプリケーションサービスの実装
+///
+pub struct PasswordChangeAppServiceImpl<D: TransactionManager>{
+    service: Arc<dyn UserService<Transaction=D::Transaction>> ,
+    // 新しいパスワードに適用するポリシー
+    policy: Arc<PasswordPolicy>
+}
+impl PasswordChangeAppServiceImpl<DatabaseConnection>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(policy: Arc<PasswordPolicy>) -> Arc<dyn PasswordChangeAppService<Pool=DatabaseConnection , Form=PasswordChangeForm>>{
+        Arc::new(Self{ service: UserServiceImpl::new() , policy })
+    }
+}
+impl<D: TransactionManager> PasswordChangeAppServiceImpl<D>{
+    // 指定されたサービスを利用するインスタンスを返す
+    pub fn with_services(service: Arc<dyn UserService<Transaction=D::Transaction>> , policy: Arc<PasswordPolicy>)
+        -> Arc<dyn PasswordChangeAppService<Pool=D , Form=PasswordChangeForm>> {
+        Arc::new(Self{ service , policy })
+    }
+    // パスワードポリシーと現在のパスワードを確認してパスワードを変更する
+    async fn change(&self , tran: &D::Transaction , user_id: &UserId , change: &PasswordChange) -> Result<()> {
+        let user = self.service.by_id(tran , user_id).await?;
+        let mut errors = ValidationError::new();
+        for violation in self.policy.check(&change.password , &user) {
+            errors.add("password" , FieldError::new(violation.code() , violation.message()));
+        }
+        errors.into_result()?;
+        match self.service.change_password(tran , &user , change).await {
+            Err(AppError::Unauthorized(message)) => {
+                let mut errors = ValidationError::new();
+                errors.add("current" , FieldError::new("mismatch" , message));
+                Err(AppError::from(errors))
+            },
+            Err(error) => Err(error) ,
+            Ok(_) => Ok(())
+        }
+    }
+}
+#[async_trait]
+impl<D: TransactionManager> PasswordChangeAppService for PasswordChangeAppServiceImpl<D>{
+    type Pool = D;
+    type Form = PasswordChangeForm;
+    // 認証済ユーザーのパスワードを変更する
+    async fn execute(&self, pool: &Self::Pool, user_id: &str, form: &Self::Form) -> Result<()> {
+        let user_id = UserId::try_from(user_id.to_string())?;
+        let change: PasswordChange = form.convert()?;
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.change(uow.transaction() , &user_id , &change).await;
+        uow.complete(result).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::config::PasswordConfig;
+    use crate::domain::values::users::Password;
+    use crate::infrastructure::fixtures::USERS;
+    use crate::infrastructure::hasher_impl::Argon2PasswordHasher;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::memory::repositories::user::UserRepositoryImpl;
+
+    #[actix::test]
+    async fn execute() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = PasswordChangeAppServiceImpl::with_services(
+            UserServiceImpl::with_repository(UserRepositoryImpl::new()) , Arc::new(PasswordConfig::default().policy()?));
+        // user001(パスワード:pass001)
+        let user_id = USERS[0].0;
+        let form = |current: &str , password: &str| PasswordChangeForm{
+            current: Some(current.to_string()) ,
+            password: Some(password.to_string()) ,
+            confirmation: Some(password.to_string()) };
+        // 新しいパスワードがポリシーに違反する場合は現在のパスワードを照合しない
+        match service.execute(&database , user_id , &form("wrong" , "short")).await.err().unwrap() {
+            AppError::Validation(error) => {
+                assert_eq!(error.errors["password"].len() , 2);
+                assert!(!error.errors.contains_key("current"));
+            },
+            error => panic!("{:?}" , error)
+        }
+        // 現在のパスワードが異なる
+        match service.execute(&database , user_id , &form("wrong" , "sakura2022")).await.err().unwrap() {
+            AppError::Validation(error) => assert_eq!(error.errors["current"][0].code , "mismatch") ,
+            error => panic!("{:?}" , error)
+        }
+        service.execute(&database , user_id , &form("pass001" , "sakura2022")).await?;
+        let user = database.snapshot()?.users.get(user_id).unwrap().clone();
+        assert!(Argon2PasswordHasher::new().verify(&Password::try_from(String::from("sakura2022"))? , &user.password)?);
+        Ok(())
+    }
+}
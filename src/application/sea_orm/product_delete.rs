@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use crate::infrastructure::transaction::{TransactionManager, UnitOfWork};
+use crate::application::app_service::ProductDeleteAppService;
+use crate::application::transfers::{EntityToDto, ProductDto};
+use crate::Result;
+use crate::domain::entities::Product;
+use crate::domain::services::ProductService;
+use crate::domain::values::products::ProductId;
+use crate::service::sea_orm::product::ProductServiceImpl;
+use crate::presentation::forms::{FormToDomain, ProductDeleteForm};
+
+
+///
+/// 商品削除アプリケーションサービスの実装
+///
+pub struct ProductDeleteAppServiceImpl<D: TransactionManager>{
+    service: Arc<dyn ProductService<Transaction=D::Transaction>>
+}
+impl ProductDeleteAppServiceImpl<DatabaseConnection>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn ProductDeleteAppService<Pool=DatabaseConnection ,
+                                                    Form=ProductDeleteForm>>{
+        Arc::new(Self{ service:ProductServiceImpl::new() })
+    }
+}
+impl<D: TransactionManager> ProductDeleteAppServiceImpl<D>{
+    // 指定されたサービスを利用するインスタンスを返す
+    pub fn with_services(service: Arc<dyn ProductService<Transaction=D::Transaction>>)
+        -> Arc<dyn ProductDeleteAppService<Pool=D , Form=ProductDeleteForm>> {
+        Arc::new(Self{ service })
+    }
+}
+#[async_trait]
+impl<D: TransactionManager> ProductDeleteAppService for ProductDeleteAppServiceImpl<D>{
+    type Pool = D;
+    type Form = ProductDeleteForm;
+    // 商品を削除する
+    async fn execute(&self, pool: &Self::Pool, form: &Self::Form) -> Result<ProductDto> {
+        let id:ProductId = form.convert()?;
+        // 取得と削除を一つのトランザクションで実行する
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.delete(uow.transaction() , &id).await;
+        let product = uow.complete(result).await?;
+        Ok(ProductDto::convert(&product))
+    }
+}
+impl<D: TransactionManager> ProductDeleteAppServiceImpl<D>{
+    // 削除結果として返す商品を取得して削除する
+    async fn delete(&self, tran: &D::Transaction, id: &ProductId) -> Result<Product> {
+        let product = self.service.by_id(tran , id).await?;
+        self.service.delete(tran , id).await?;
+        Ok(product)
+    }
+}
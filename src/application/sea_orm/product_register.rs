@@ -1,28 +1,30 @@
-use std::borrow::Borrow;
 use std::sync::Arc;
 use async_trait::async_trait;
 use sea_orm::DatabaseConnection;
+use crate::infrastructure::transaction::{TransactionManager, UnitOfWork};
 use crate::application::app_service::ProductRegisterAppService;
 use crate::application::transfers::{CategoryDto, EntityToDto, ProductDto};
-use crate::Result;
-use crate::domain::entities::Characteristic;
+use crate::{AppError, Result};
+use crate::domain::entities::{Characteristic, Product};
 use crate::domain::services::{CategoryService, ProductService};
-use crate::domain::values::products::ProductName;
 use crate::service::sea_orm::category::CategoryServiceImpl;
 use crate::service::sea_orm::product::ProductServiceImpl;
 use crate::presentation::forms::{FormToDomain, ProductRegisterForm};
+use crate::i18n::Message;
+use crate::presentation::validate::{FieldError, ValidationError};
 
 
 ///
 /// 商品登録アプリケーションサービスの実装
 ///
-pub struct ProductRegisterAppServiceImpl{
+pub struct ProductRegisterAppServiceImpl<D: TransactionManager>{
     // カテゴリサービス
-    category_service: Arc<dyn CategoryService<Database=DatabaseConnection>> ,
+    category_service: Arc<dyn CategoryService<Transaction=D::Transaction>> ,
     // 商品サービス
-    product_service: Arc<dyn ProductService<Database=DatabaseConnection>>
+    product_service: Arc<dyn ProductService<Transaction=D::Transaction>>
 }
-impl ProductRegisterAppServiceImpl {
+impl ProductRegisterAppServiceImpl<DatabaseConnection>{
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> Arc<dyn ProductRegisterAppService<
                                 Pool=DatabaseConnection,Form=ProductRegisterForm>>{
         Arc::new(Self{
@@ -31,26 +33,127 @@ impl ProductRegisterAppServiceImpl {
         })
     }
 }
+impl<D: TransactionManager> ProductRegisterAppServiceImpl<D>{
+    // 指定されたサービスを利用するインスタンスを返す
+    pub fn with_services(category_service: Arc<dyn CategoryService<Transaction=D::Transaction>> ,
+                         product_service: Arc<dyn ProductService<Transaction=D::Transaction>>)
+        -> Arc<dyn ProductRegisterAppService<Pool=D , Form=ProductRegisterForm>> {
+        Arc::new(Self{ category_service , product_service })
+    }
+}
 #[async_trait]
-impl ProductRegisterAppService for ProductRegisterAppServiceImpl {
-    type Pool = DatabaseConnection;
+impl<D: TransactionManager> ProductRegisterAppService for ProductRegisterAppServiceImpl<D>{
+    type Pool = D;
     type Form = ProductRegisterForm;
 
     // 商品カテゴリを取得する
     async fn categories(&self, pool: &Self::Pool) -> Result<Vec<CategoryDto>> {
-        let categories = self.category_service.all(pool).await?;
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.category_service.all(uow.transaction()).await;
+        let categories = uow.complete(result).await?;
         // 取得結果をVec<CategoryDto>に変換して返す
         Ok(CategoryDto::converts(&categories))
     }
     // 新商品を登録する
     async fn execute(&self, pool: &Self::Pool, form: &Self::Form) -> Result<ProductDto> {
+        // 存在チェックと登録を一つのトランザクションで実行する
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.register(uow.transaction() , form).await;
+        let product = uow.complete(result).await?;
+        Ok(ProductDto::convert(&product))
+    }
+}
+impl<D: TransactionManager> ProductRegisterAppServiceImpl<D>{
+    // カテゴリと商品名を確認して商品を登録する
+    async fn register(&self, tran: &D::Transaction, form: &ProductRegisterForm) -> Result<Product> {
+        // 入力値を変換する、未入力や不正な値は検証エラーを返す
+        let new_product = form.convert()?;
+        // カテゴリの存在チェック、存在しない場合はcategory_idの検証エラーを返す
+        let category_id = match new_product.category.as_ref() {
+            Some(category) => category.get() ,
+            None => return Err(AppError::from("商品のカテゴリがありません。"))
+        };
+        let category = match self.category_service.by_id(tran , &category_id).await {
+            Ok(category) => category ,
+            Err(AppError::NotFound(_)) => {
+                let mut errors = ValidationError::new();
+                errors.add("category_id" , FieldError::new("invalid" ,
+                    Message::new("validation.invalid_choice").with_label("label" , "label.product.category")));
+                return Err(AppError::from(errors));
+            },
+            Err(error) => return Err(error)
+        };
         // 商品の存在チェック
-        let product_name = ProductName::try_from(form.name.as_ref().unwrap().clone())?;
-        self.product_service.exists(pool , &product_name).await?;
+        self.product_service.exists(tran , &new_product.name).await?;
         // 商品を登録する
-        let mut product = self.product_service.register(pool , &form.convert()?).await?;
-        // カテゴリを取得して 商品Entityのcategoryに格納する
-        product.category = self.category_service.by_id(pool , product.category.unwrap().get().borrow()).await.ok();
-        Ok(ProductDto::convert(&product))
+        let mut product = self.product_service.register(tran , &new_product).await?;
+        // 取得したカテゴリを商品Entityのcategoryに格納する
+        product.category = Some(category);
+        Ok(product)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::memory::repositories::{category, product};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+    use crate::config::AppConfig;
+    use crate::infrastructure::sea_orm::models;
+    use crate::infrastructure::sea_orm::models::prelude::SeaOrmProduct;
+    use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
+
+    #[actix::test]
+    async fn execute() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = ProductRegisterAppServiceImpl::with_services(
+            CategoryServiceImpl::with_repository(category::CategoryRepositoryImpl::new()) ,
+            ProductServiceImpl::with_repository(product::ProductRepositoryImpl::new()));
+        let form = ProductRegisterForm{
+            name: Some(String::from("油性ボールペン(緑)")) ,
+            price: Some(100) ,
+            category_id: Some(1) };
+        let product = service.execute(&database , &form).await?;
+        assert_eq!(product.id , "29");
+        assert_eq!(product.category.name , "文房具");
+        assert_eq!(database.snapshot()?.products.len() , 29);
+        // 登録済の商品名
+        let error = service.execute(&database , &form).await.err().unwrap();
+        assert!(matches!(error , AppError::Conflict(_)));
+        // 存在しないカテゴリ
+        let form = ProductRegisterForm{
+            name: Some(String::from("油性ボールペン(紫)")) ,
+            price: Some(100) ,
+            category_id: Some(4) };
+        match service.execute(&database , &form).await.err().unwrap() {
+            AppError::Validation(error) => assert!(error.errors.contains_key("category_id")) ,
+            error => panic!("{:?}" , error)
+        }
+        assert_eq!(database.snapshot()?.products.len() , 29);
+        Ok(())
+    }
+
+    // 同時に同じ商品名を登録しても一件だけ登録され、他は重複エラーになる
+    #[actix::test]
+    async fn concurrent_execute() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let name = format!("並行登録{}" , &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let form = ProductRegisterForm{ name: Some(name.clone()) , price: Some(100) , category_id: Some(1) };
+        let handles: Vec<_> = (0..2).map(|_| {
+            let (conn , form) = (conn.clone() , form.clone());
+            actix::spawn(async move { ProductRegisterAppServiceImpl::new().execute(&conn , &form).await })
+        }).collect();
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await?);
+        }
+        SeaOrmProduct::delete_many().filter(models::product::Column::Name.eq(name.as_str())).exec(&conn).await?;
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count() , 1);
+        match results.into_iter().find_map(|result| result.err()).unwrap() {
+            AppError::Conflict(message) => assert_eq!(message.to_string() , format!("{}は登録済です。" , name)) ,
+            error => panic!("{:?}" , error)
+        }
+        Ok(())
+    }
+}
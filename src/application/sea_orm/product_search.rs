@@ -1,11 +1,13 @@
 use std::sync::Arc;
 use async_trait::async_trait;
 use sea_orm::DatabaseConnection;
+use crate::infrastructure::transaction::{TransactionManager, UnitOfWork};
 use crate::application::app_service::ProductSearchAppService;
-use crate::application::transfers::{EntityToDto, ProductDto};
+use crate::application::transfers::{EntityToDto, PageDto, ProductDto};
 use crate::Result;
 use crate::domain::services::ProductService;
-use crate::domain::values::products::ProductName;
+use crate::domain::values::pages::PageRequest;
+use crate::domain::values::products::{ProductSearchCriteria, ProductSortKey};
 use crate::service::sea_orm::product::ProductServiceImpl;
 use crate::presentation::forms::{FormToDomain, ProductSearchForm};
 
@@ -13,27 +15,37 @@ use crate::presentation::forms::{FormToDomain, ProductSearchForm};
 ///
 /// 商品検索サービスの実装
 ///
-pub struct ProductSearchAppServiceImpl{
-    service: Arc<dyn ProductService<Database=DatabaseConnection>>
+pub struct ProductSearchAppServiceImpl<D: TransactionManager>{
+    service: Arc<dyn ProductService<Transaction=D::Transaction>>
 }
-impl  ProductSearchAppServiceImpl {
+impl ProductSearchAppServiceImpl<DatabaseConnection>{
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> Arc<dyn ProductSearchAppService<Pool=DatabaseConnection ,
                                                     Form=ProductSearchForm>>{
         Arc::new(Self{ service:ProductServiceImpl::new() })
     }
 }
+impl<D: TransactionManager> ProductSearchAppServiceImpl<D>{
+    // 指定されたサービスを利用するインスタンスを返す
+    pub fn with_services(service: Arc<dyn ProductService<Transaction=D::Transaction>>)
+        -> Arc<dyn ProductSearchAppService<Pool=D , Form=ProductSearchForm>> {
+        Arc::new(Self{ service })
+    }
+}
 #[async_trait]
-impl ProductSearchAppService for ProductSearchAppServiceImpl{
-    type Pool = DatabaseConnection;
+impl<D: TransactionManager> ProductSearchAppService for ProductSearchAppServiceImpl<D>{
+    type Pool = D;
     type Form = ProductSearchForm;
-    // キーワード検索
-    async fn search(&self, pool: &Self::Pool, form: &Self::Form) -> Result<Vec<ProductDto>> {
-        // キーワードをProductNameに変換する
-        let keyword:ProductName = form.convert()?;
+    // 検索条件による検索
+    async fn search(&self, pool: &Self::Pool, form: &Self::Form) -> Result<PageDto<ProductDto>> {
+        // 検索条件、ページと並び順を値オブジェクトに変換する
+        let criteria:ProductSearchCriteria = form.convert()?;
+        let page:PageRequest = form.convert()?;
+        let sort:ProductSortKey = form.convert()?;
         // 検索を実行する
-        match  self.service.by_keyword(pool , &keyword).await {
-            Ok(results) => Ok(ProductDto::converts(&results)) ,
-            Err(error) => Err(error)
-        }
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.service.by_criteria(uow.transaction() , &criteria , &page , &sort).await;
+        let results = uow.complete(result).await?;
+        Ok(PageDto::convert(&results))
     }
 }
\ No newline at end of file
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use crate::infrastructure::transaction::{TransactionManager, UnitOfWork};
+use crate::application::app_service::ProductUpdateAppService;
+use crate::application::transfers::{CategoryDto, EntityToDto, ProductDto};
+use crate::{AppError, Result};
+use crate::domain::entities::{Characteristic, Product};
+use crate::domain::services::{CategoryService, ProductService};
+use crate::domain::values::products::ProductId;
+use crate::service::sea_orm::category::CategoryServiceImpl;
+use crate::service::sea_orm::product::ProductServiceImpl;
+use crate::presentation::forms::{FormToDomain, ProductUpdateForm};
+use crate::i18n::Message;
+use crate::presentation::validate::{FieldError, ValidationError};
+
+
+///
+/// 商品変更アプリケーションサービスの実装
+///
+pub struct ProductUpdateAppServiceImpl<D: TransactionManager>{
+    // カテゴリサービス
+    category_service: Arc<dyn CategoryService<Transaction=D::Transaction>> ,
+    // 商品サービス
+    product_service: Arc<dyn ProductService<Transaction=D::Transaction>>
+}
+impl ProductUpdateAppServiceImpl<DatabaseConnection>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn ProductUpdateAppService<
+                                Pool=DatabaseConnection,Form=ProductUpdateForm>>{
+        Arc::new(Self{
+            category_service:CategoryServiceImpl::new() ,
+            product_service:ProductServiceImpl::new()
+        })
+    }
+}
+impl<D: TransactionManager> ProductUpdateAppServiceImpl<D>{
+    // 指定されたサービスを利用するインスタンスを返す
+    pub fn with_services(category_service: Arc<dyn CategoryService<Transaction=D::Transaction>> ,
+                         product_service: Arc<dyn ProductService<Transaction=D::Transaction>>)
+        -> Arc<dyn ProductUpdateAppService<Pool=D , Form=ProductUpdateForm>> {
+        Arc::new(Self{ category_service , product_service })
+    }
+}
+#[async_trait]
+impl<D: TransactionManager> ProductUpdateAppService for ProductUpdateAppServiceImpl<D>{
+    type Pool = D;
+    type Form = ProductUpdateForm;
+
+    // 商品カテゴリを取得する
+    async fn categories(&self, pool: &Self::Pool) -> Result<Vec<CategoryDto>> {
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.category_service.all(uow.transaction()).await;
+        let categories = uow.complete(result).await?;
+        // 取得結果をVec<CategoryDto>に変換して返す
+        Ok(CategoryDto::converts(&categories))
+    }
+    // 変更対象の商品を取得する
+    async fn product(&self, pool: &Self::Pool, id: i32) -> Result<ProductDto> {
+        // 不正な商品番号は利用者の入力誤りなので検証エラーにする
+        let id = ProductId::try_from(id).map_err(|error| error.into_validation("id"))?;
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.product_service.by_id(uow.transaction() , &id).await;
+        let product = uow.complete(result).await?;
+        Ok(ProductDto::convert(&product))
+    }
+    // 商品を変更する
+    async fn execute(&self, pool: &Self::Pool, form: &Self::Form) -> Result<ProductDto> {
+        let product = form.convert()?;
+        // 存在チェックと変更を一つのトランザクションで実行する
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.update(uow.transaction() , product).await;
+        let product = uow.complete(result).await?;
+        Ok(ProductDto::convert(&product))
+    }
+}
+impl<D: TransactionManager> ProductUpdateAppServiceImpl<D>{
+    // 変更対象、カテゴリと商品名を確認して商品を変更する
+    async fn update(&self, tran: &D::Transaction, mut product: Product) -> Result<Product> {
+        // 変更対象の商品を取得する
+        let current = self.product_service.by_id(tran , &product.get()).await?;
+        // カテゴリの存在チェック、存在しない場合はcategory_idの検証エラーを返す
+        let category_id = match product.category.as_ref() {
+            Some(category) => category.get() ,
+            None => return Err(AppError::from("商品のカテゴリがありません。"))
+        };
+        let category = match self.category_service.by_id(tran , &category_id).await {
+            Ok(category) => category ,
+            Err(AppError::NotFound(_)) => {
+                let mut errors = ValidationError::new();
+                errors.add("category_id" , FieldError::new("invalid" ,
+                    Message::new("validation.invalid_choice").with_label("label" , "label.product.category")));
+                return Err(AppError::from(errors));
+            },
+            Err(error) => return Err(error)
+        };
+        // 商品名を変更する場合は同一名称の商品が存在しないか確認する
+        if current.name != product.name {
+            self.product_service.exists(tran , &product.name).await?;
+        }
+        product.category = Some(category);
+        // 商品を変更する
+        self.product_service.update(tran , &product).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::memory::repositories::{category, product};
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+    use crate::config::AppConfig;
+    use crate::application::sea_orm::product_register::ProductRegisterAppServiceImpl;
+    use crate::infrastructure::sea_orm::models;
+    use crate::infrastructure::sea_orm::models::prelude::SeaOrmProduct;
+    use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
+    use crate::presentation::forms::ProductRegisterForm;
+
+    #[actix::test]
+    async fn product() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = ProductUpdateAppServiceImpl::with_services(
+            CategoryServiceImpl::with_repository(category::CategoryRepositoryImpl::new()) ,
+            ProductServiceImpl::with_repository(product::ProductRepositoryImpl::new()));
+        assert_eq!(service.product(&database , 1).await?.id , "1");
+        // 負の商品番号は内部エラーではなく検証エラーを返す
+        match service.product(&database , -1).await {
+            Err(AppError::Validation(errors)) => assert_eq!(errors.messages("id").len() , 1) ,
+            result => panic!("{:?}" , result)
+        }
+        Ok(())
+    }
+
+    // 2つの商品を同時に同じ名称へ変更すると、一方は登録と同じ重複エラーになる
+    #[actix::test]
+    async fn concurrent_execute() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let prefix = format!("並行変更{}" , &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let mut ids = Vec::new();
+        for suffix in ["a" , "b"] {
+            let form = ProductRegisterForm{ name: Some(format!("{}{}" , prefix , suffix)) , price: Some(100) , category_id: Some(1) };
+            ids.push(ProductRegisterAppServiceImpl::new().execute(&conn , &form).await?.id.parse::<i32>()?);
+        }
+        let handles: Vec<_> = ids.into_iter().map(|id| {
+            let conn = conn.clone();
+            let form = ProductUpdateForm{ id: Some(id) , name: Some(prefix.clone()) , price: Some(200) , category_id: Some(1) };
+            actix::spawn(async move { ProductUpdateAppServiceImpl::new().execute(&conn , &form).await })
+        }).collect();
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await?);
+        }
+        SeaOrmProduct::delete_many().filter(models::product::Column::Name.starts_with(prefix.as_str())).exec(&conn).await?;
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count() , 1);
+        match results.into_iter().find_map(|result| result.err()).unwrap() {
+            AppError::Conflict(message) => assert_eq!(message.to_string() , format!("{}は登録済です。" , prefix)) ,
+            error => panic!("{:?}" , error)
+        }
+        Ok(())
+    }
+}
@@ -1,30 +1,127 @@
 use std::sync::Arc;
+use std::time::Duration;
 use sea_orm::DatabaseConnection;
-use crate::application::app_service::{AuthenticateAppService, ProductRegisterAppService, ProductSearchAppService};
+use crate::Result;
+use crate::config::AppConfig;
+use crate::application::app_service::{AuthenticateAppService, PasswordChangeAppService, ProductDeleteAppService, ProductRegisterAppService, ProductSearchAppService, ProductUpdateAppService, TokenAppService, UserRegisterAppService};
 use crate::application::sea_orm::authenticate::AuthenticateAppServiceImpl;
+use crate::application::sea_orm::password_change::PasswordChangeAppServiceImpl;
+use crate::application::sea_orm::product_delete::ProductDeleteAppServiceImpl;
 use crate::application::sea_orm::product_register::ProductRegisterAppServiceImpl;
 use crate::application::sea_orm::product_search::ProductSearchAppServiceImpl;
-use crate::presentation::forms::{LoginForm, ProductRegisterForm, ProductSearchForm};
+use crate::application::sea_orm::product_update::ProductUpdateAppServiceImpl;
+use crate::application::sea_orm::token::TokenAppServiceImpl;
+use crate::application::sea_orm::user_register::UserRegisterAppServiceImpl;
+use crate::infrastructure::pool::PoolProvider;
+use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
+use crate::presentation::claims::UserPrincipal;
+use crate::presentation::jwt::JwtKeys;
+use crate::presentation::forms::{LoginForm, PasswordChangeForm, ProductDeleteForm, ProductRegisterForm, ProductSearchForm, ProductUpdateForm, UserRegisterForm};
 
 ///
 /// アプリケーションサービスプロバイダ
 ///
 #[derive(Clone)]
 pub struct AppServiceProvider {
+    // アプリケーションの設定
+    pub config: Arc<AppConfig> ,
+    // JWTの署名鍵と検証鍵
+    pub jwt_keys: Arc<JwtKeys> ,
     // 商品検索サービス
     pub search_service: Arc<dyn ProductSearchAppService<Pool=DatabaseConnection,Form=ProductSearchForm>> ,
     // 商品登録ービス
     pub register_service: Arc<dyn ProductRegisterAppService<Pool=DatabaseConnection,Form=ProductRegisterForm>> ,
+    // 商品変更サービス
+    pub update_service: Arc<dyn ProductUpdateAppService<Pool=DatabaseConnection,Form=ProductUpdateForm>> ,
+    // 商品削除サービス
+    pub delete_service: Arc<dyn ProductDeleteAppService<Pool=DatabaseConnection,Form=ProductDeleteForm>> ,
     // ユーザー認証サービス
-    pub authenticate_service: Arc<dyn AuthenticateAppService<Pool=DatabaseConnection,Form=LoginForm>>
+    pub authenticate_service: Arc<dyn AuthenticateAppService<Pool=DatabaseConnection,Form=LoginForm>> ,
+    // ユーザー登録サービス
+    pub user_register_service: Arc<dyn UserRegisterAppService<Pool=DatabaseConnection,Form=UserRegisterForm>> ,
+    // パスワード変更サービス
+    pub password_change_service: Arc<dyn PasswordChangeAppService<Pool=DatabaseConnection,Form=PasswordChangeForm>> ,
+    // リフレッシュトークンと失効サービス
+    pub token_service: Arc<dyn TokenAppService<Pool=DatabaseConnection>>
 }
 impl AppServiceProvider {
-    pub fn new() -> Arc<Self> {
-        Arc::new(
+    pub fn new(config: Arc<AppConfig>) -> Result<Arc<Self>> {
+        let jwt_keys = Arc::new(JwtKeys::from_config(&config.jwt)?);
+        let policy = Arc::new(config.password.policy()?);
+        Ok(Arc::new(
             Self{
+                jwt_keys ,
                 search_service:ProductSearchAppServiceImpl::new() ,
                 register_service:ProductRegisterAppServiceImpl::new() ,
-                authenticate_service:AuthenticateAppServiceImpl::new()
-            })
+                update_service:ProductUpdateAppServiceImpl::new() ,
+                delete_service:ProductDeleteAppServiceImpl::new() ,
+                authenticate_service:AuthenticateAppServiceImpl::new(config.lockout.policy()) ,
+                user_register_service:UserRegisterAppServiceImpl::new(policy.clone()) ,
+                password_change_service:PasswordChangeAppServiceImpl::new(policy) ,
+                token_service:TokenAppServiceImpl::new(config.jwt.refresh_expires_in) ,
+                config
+            }))
     }
-}
\ No newline at end of file
+    // 設定に従って接続した共有コネクションプールを返す
+    pub async fn pool(&self) -> Result<Arc<DatabaseConnection>> {
+        SeaOrmPool::get(&self.config.database).await
+    }
+    // 永続化した失効済のトークンを検証に反映する
+    pub async fn load_denylist(&self) -> Result<()> {
+        let pool = self.pool().await?;
+        for (jti , expires_at) in self.token_service.denied(&pool).await? {
+            self.jwt_keys.denylist().deny(&jti , expires_at);
+        }
+        Ok(())
+    }
+    // 失効済のトークンを設定した間隔で読込み、他のインスタンスで失効したトークンも検証に反映する
+    // ランタイム(actix-webのサーバーなど)の起動後に呼び出す、最初の読込みはすぐに行う
+    pub fn spawn_denylist_reload(self: &Arc<Self>) {
+        let provider = self.clone();
+        actix::spawn(async move {
+            let mut interval = actix::clock::interval(Duration::from_secs(provider.config.jwt.denylist_reload));
+            loop {
+                interval.tick().await;
+                if let Err(error) = provider.load_denylist().await {
+                    log::warn!("失効したトークンを読込めません: {}" , error);
+                }
+            }
+        });
+    }
+    // 認証済ユーザーのアクセストークンとリフレッシュトークンを失効させる(ログアウト)
+    pub async fn revoke(&self , principal: &UserPrincipal , refresh_token: Option<&str>) -> Result<()> {
+        let pool = self.pool().await?;
+        self.token_service.revoke(&pool , &principal.token_id , principal.expires_at , refresh_token).await?;
+        self.jwt_keys.denylist().deny(&principal.token_id , principal.expires_at);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    fn principal() -> UserPrincipal {
+        UserPrincipal{ user_id: String::from("5772a800-fef1-40bf-888b-68fddd29d881") , user_name: String::from("user001") ,
+                       roles: vec![String::from("user")] , token_id: uuid::Uuid::new_v4().to_string() ,
+                       expires_at: jsonwebtoken::get_current_timestamp() + 60 }
+    }
+
+    // 他のインスタンスで失効したトークンは次の読込みで検証に反映される
+    #[actix::test]
+    async fn denylist_reload() -> Result<()> {
+        let mut config = AppConfig::load()?;
+        config.jwt.denylist_reload = 1;
+        let provider = AppServiceProvider::new(Arc::new(config.clone()))?;
+        let other = AppServiceProvider::new(Arc::new(config))?;
+        provider.spawn_denylist_reload();
+        let principal = principal();
+        other.revoke(&principal , None).await?;
+        assert!(other.jwt_keys.denylist().is_denied(&principal.token_id));
+        assert!(!provider.jwt_keys.denylist().is_denied(&principal.token_id));
+        actix::clock::sleep(Duration::from_millis(1500)).await;
+        assert!(provider.jwt_keys.denylist().is_denied(&principal.token_id));
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use crate::infrastructure::transaction::{TransactionManager, UnitOfWork};
+use crate::application::app_service::TokenAppService;
+use crate::application::transfers::{EntityToDto, UserDto};
+use crate::{AppError, Result};
+use crate::domain::services::{TokenService, UserService};
+use crate::domain::values::users::UserId;
+use crate::service::sea_orm::token::TokenServiceImpl;
+use crate::service::sea_orm::user::UserServiceImpl;
+
+///
+/// トークンアプリケーションサービスの実装
+///
+pub struct TokenAppServiceImpl<D: TransactionManager>{
+    token_service: Arc<dyn TokenService<Transaction=D::Transaction>> ,
+    user_service: Arc<dyn UserService<Transaction=D::Transaction>>
+}
+impl TokenAppServiceImpl<DatabaseConnection>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(refresh_expires_in: u64) -> Arc<dyn TokenAppService<Pool=DatabaseConnection>>{
+        Arc::new(Self{ token_service: TokenServiceImpl::new(refresh_expires_in) ,
+                       user_service: UserServiceImpl::new() })
+    }
+}
+impl<D: TransactionManager> TokenAppServiceImpl<D>{
+    // 指定されたサービスを利用するインスタンスを返す
+    pub fn with_services(token_service: Arc<dyn TokenService<Transaction=D::Transaction>> ,
+                         user_service: Arc<dyn UserService<Transaction=D::Transaction>>)
+        -> Arc<dyn TokenAppService<Pool=D>> {
+        Arc::new(Self{ token_service , user_service })
+    }
+    // ローテーションしたトークンの利用者を取得する
+    async fn rotate(&self , tran: &D::Transaction , refresh_token: &str) -> Result<(String , UserDto)> {
+        let (token , user_id) = self.token_service.rotate(tran , refresh_token).await?;
+        let user = self.user_service.by_id(tran , &user_id).await?;
+        Ok((token , UserDto::convert(&user)))
+    }
+    // アクセストークンとリフレッシュトークンを失効させる
+    async fn deny(&self , tran: &D::Transaction , jti: &str , expires_at: u64 , refresh_token: Option<&str>) -> Result<()> {
+        self.token_service.deny(tran , jti , expires_at as i64).await?;
+        if let Some(refresh_token) = refresh_token {
+            self.token_service.revoke(tran , refresh_token).await?;
+        }
+        Ok(())
+    }
+}
+#[async_trait]
+impl<D: TransactionManager> TokenAppService for TokenAppServiceImpl<D>{
+    type Pool = D;
+
+    async fn issue(&self, pool: &Self::Pool, user: &UserDto) -> Result<String> {
+        let user_id = UserId::try_from(user.user_id.clone())?;
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.token_service.issue(uow.transaction() , &user_id).await;
+        uow.complete(result).await
+    }
+
+    async fn refresh(&self, pool: &Self::Pool, refresh_token: &str) -> Result<(String , UserDto)> {
+        // 再利用を検出した場合のファミリーの失効は確定させ、それ以外のエラーは取り消す
+        let uow = UnitOfWork::begin(pool).await?;
+        match self.rotate(uow.transaction() , refresh_token).await {
+            Err(AppError::Unauthorized(message)) if message.key == "token.refresh_used" => {
+                uow.complete(Ok(())).await?;
+                Err(AppError::Unauthorized(message))
+            },
+            result => uow.complete(result).await
+        }
+    }
+
+    async fn revoke(&self, pool: &Self::Pool, jti: &str, expires_at: u64, refresh_token: Option<&str>) -> Result<()> {
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.deny(uow.transaction() , jti , expires_at , refresh_token).await;
+        uow.complete(result).await
+    }
+
+    async fn denied(&self, pool: &Self::Pool) -> Result<Vec<(String , u64)>> {
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.token_service.denied(uow.transaction()).await;
+        let denied = uow.complete(result).await?;
+        Ok(denied.into_iter().map(|(jti , expires_at)| (jti , expires_at as u64)).collect())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::memory::repositories::token::{RefreshTokenRepositoryImpl, RevokedTokenRepositoryImpl};
+    use crate::infrastructure::memory::repositories::user::UserRepositoryImpl;
+
+    fn service() -> Arc<dyn TokenAppService<Pool=MemoryDatabase>> {
+        TokenAppServiceImpl::with_services(
+            TokenServiceImpl::with_repositories(RefreshTokenRepositoryImpl::new() , RevokedTokenRepositoryImpl::new() , 600) ,
+            UserServiceImpl::with_repository(UserRepositoryImpl::new()))
+    }
+
+    #[actix::test]
+    async fn refresh() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = service();
+        let store = database.snapshot()?;
+        let user = UserDto::convert(store.users.values().next().unwrap());
+        let first = service.issue(&database , &user).await?;
+        let (second , refreshed) = service.refresh(&database , &first).await?;
+        assert_eq!(refreshed.user_id , user.user_id);
+        // 再利用の検出によるファミリーの失効はエラーでもコミットされる
+        let error = service.refresh(&database , &first).await.err().unwrap();
+        assert!(matches!(error , AppError::Unauthorized(_)));
+        assert!(database.snapshot()?.refresh_tokens.values().all(|token| token.revoked));
+        assert!(service.refresh(&database , &second).await.is_err());
+        Ok(())
+    }
+
+    // ローテーション後に失敗した場合は変更を取り消し、元のトークンを使い続けられる
+    #[actix::test]
+    async fn refresh_rollback() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = service();
+        let user = UserDto::convert(database.snapshot()?.users.values().next().unwrap());
+        let token = service.issue(&database , &user).await?;
+        let users = database.snapshot()?.users;
+        let uow = UnitOfWork::begin(&database).await?;
+        uow.transaction().store()?.users.clear();
+        uow.complete(Ok(())).await?;
+        let error = service.refresh(&database , &token).await.err().unwrap();
+        assert!(matches!(error , AppError::NotFound(_)) , "{:?}" , error);
+        let store = database.snapshot()?;
+        assert_eq!(store.refresh_tokens.len() , 1);
+        assert!(store.refresh_tokens.values().all(|token| !token.is_used()));
+        let uow = UnitOfWork::begin(&database).await?;
+        uow.transaction().store()?.users.extend(users);
+        uow.complete(Ok(())).await?;
+        assert!(service.refresh(&database , &token).await.is_ok());
+        Ok(())
+    }
+
+    #[actix::test]
+    async fn revoke() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = service();
+        let user = UserDto::convert(database.snapshot()?.users.values().next().unwrap());
+        let refresh_token = service.issue(&database , &user).await?;
+        let expires_at = jsonwebtoken::get_current_timestamp() + 60;
+        service.revoke(&database , "jti-001" , expires_at , Some(&refresh_token)).await?;
+        assert_eq!(service.denied(&database).await? , vec![(String::from("jti-001") , expires_at)]);
+        assert!(service.refresh(&database , &refresh_token).await.is_err());
+        Ok(())
+    }
+}
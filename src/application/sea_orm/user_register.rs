@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use crate::infrastructure::transaction::{TransactionManager, UnitOfWork};
+use crate::application::app_service::UserRegisterAppService;
+use crate::{AppError, Result};
+use crate::domain::entities::User;
+use crate::domain::password_policy::PasswordPolicy;
+use crate::domain::services::UserService;
+use crate::service::sea_orm::user::UserServiceImpl;
+use crate::presentation::forms::{FormToDomain, UserRegisterForm};
+use crate::presentation::validate::{FieldError, ValidationError};
+
+
+///
+/// ユーザー登録アプリケーションサービスの実装
+///
+pub struct UserRegisterAppServiceImpl<D: TransactionManager>{
+    service: Arc<dyn UserService<Transaction=D::Transaction>> ,
+    // 新しいパスワードに適用するポリシー
+    policy: Arc<PasswordPolicy>
+}
+impl UserRegisterAppServiceImpl<DatabaseConnection>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(policy: Arc<PasswordPolicy>) -> Arc<dyn UserRegisterAppService<Pool=DatabaseConnection , Form=UserRegisterForm>>{
+        Arc::new(Self{ service: UserServiceImpl::new() , policy })
+    }
+}
+impl<D: TransactionManager> UserRegisterAppServiceImpl<D>{
+    // 指定されたサービスを利用するインスタンスを返す
+    pub fn with_services(service: Arc<dyn UserService<Transaction=D::Transaction>> , policy: Arc<PasswordPolicy>)
+        -> Arc<dyn UserRegisterAppService<Pool=D , Form=UserRegisterForm>> {
+        Arc::new(Self{ service , policy })
+    }
+}
+#[async_trait]
+impl<D: TransactionManager> UserRegisterAppService for UserRegisterAppServiceImpl<D>{
+    type Pool = D;
+    type Form = UserRegisterForm;
+    // 新しいユーザーを登録する
+    async fn execute(&self, pool: &Self::Pool, form: &Self::Form) -> Result<()> {
+        // FormをUserに変換する
+        let user = form.convert()?;
+        // 重複チェックと登録を一つのトランザクションで実行する
+        let uow = UnitOfWork::begin(pool).await?;
+        let result = self.register(uow.transaction() , &user).await;
+        uow.complete(result).await
+    }
+}
+impl<D: TransactionManager> UserRegisterAppServiceImpl<D>{
+    // パスワードポリシーとユーザー名、メールアドレスの重複を確認してユーザーを登録する
+    async fn register(&self, tran: &D::Transaction, user: &User) -> Result<()> {
+        // ポリシーに違反した項目と重複した項目のエラー
+        let mut errors = ValidationError::new();
+        for violation in self.policy.check(&user.password , user) {
+            errors.add("password" , FieldError::new(violation.code() , violation.message()));
+        }
+        // ユーザー名とメールアドレスの重複チェック
+        match self.service.exists_name(tran , &user.user_name).await {
+            Err(AppError::Conflict(message)) => errors.add("name" , FieldError::new("unique" , message)) ,
+            Err(error) => return Err(error) ,
+            Ok(_) => ()
+        }
+        match self.service.exists_mail(tran , &user.mail).await {
+            Err(AppError::Conflict(message)) => errors.add("mail" , FieldError::new("unique" , message)) ,
+            Err(error) => return Err(error) ,
+            Ok(_) => ()
+        }
+        errors.into_result()?;
+        // ユーザーを登録する
+        self.service.register(tran , user).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::config::PasswordConfig;
+    use crate::domain::values::ValueInto;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::memory::repositories::user::UserRepositoryImpl;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+    use crate::config::AppConfig;
+    use crate::infrastructure::sea_orm::models;
+    use crate::infrastructure::sea_orm::models::prelude::SeaOrmUser;
+    use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
+
+    #[actix::test]
+    async fn execute() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = UserRegisterAppServiceImpl::with_services(
+            UserServiceImpl::with_repository(UserRepositoryImpl::new()) , Arc::new(PasswordConfig::default().policy()?));
+        let form = UserRegisterForm{
+            name: Some(String::from("user003")) ,
+            password: Some(String::from("sakura2022")) ,
+            confirmation: Some(String::from("sakura2022")) ,
+            mail: Some(String::from("user003@sample.com")) };
+        service.execute(&database , &form).await?;
+        // パスワードはハッシュ変換して保存される
+        let store = database.snapshot()?;
+        let user = store.users.values().find(|user| user.user_name.value() == "user003").unwrap();
+        assert!(user.password.value().starts_with("$argon2id$"));
+        // ユーザー名とメールアドレスの重複
+        let form = UserRegisterForm{
+            name: Some(String::from("user001")) ,
+            password: Some(String::from("sakura2023")) ,
+            confirmation: Some(String::from("sakura2023")) ,
+            mail: Some(String::from("suzuki@sample.com")) };
+        match service.execute(&database , &form).await.err().unwrap() {
+            AppError::Validation(error) => {
+                assert!(error.errors.contains_key("name"));
+                assert!(error.errors.contains_key("mail"));
+                assert!(!error.errors.contains_key("password"));
+            },
+            error => panic!("{:?}" , error)
+        }
+        // パスワードポリシーの違反はすべて返す
+        let form = UserRegisterForm{
+            name: Some(String::from("user004")) ,
+            password: Some(String::from("password")) ,
+            confirmation: Some(String::from("password")) ,
+            mail: Some(String::from("user004@sample.com")) };
+        match service.execute(&database , &form).await.err().unwrap() {
+            AppError::Validation(error) => {
+                let codes: Vec<&str> = error.errors["password"].iter().map(|error| error.code.as_str()).collect();
+                assert_eq!(codes , vec!["character_class" , "common"]);
+            },
+            error => panic!("{:?}" , error)
+        }
+        Ok(())
+    }
+
+    // 同時に同じユーザー名を登録しても一件だけ登録され、他は重複エラーになる
+    #[actix::test]
+    async fn concurrent_execute() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let policy = Arc::new(PasswordConfig::default().policy()?);
+        let name = format!("u{}" , &uuid::Uuid::new_v4().simple().to_string()[..8]);
+        let form = UserRegisterForm{
+            name: Some(name.clone()) ,
+            password: Some(String::from("sakura2022")) ,
+            confirmation: Some(String::from("sakura2022")) ,
+            mail: Some(format!("{}@sample.com" , name)) };
+        let handles: Vec<_> = (0..2).map(|_| {
+            let (conn , form , policy) = (conn.clone() , form.clone() , policy.clone());
+            actix::spawn(async move { UserRegisterAppServiceImpl::new(policy).execute(&conn , &form).await })
+        }).collect();
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await?);
+        }
+        SeaOrmUser::delete_many().filter(models::user::Column::UserName.eq(name.as_str())).exec(&conn).await?;
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count() , 1);
+        assert!(results.iter().any(|result| matches!(result , Err(AppError::Conflict(_)) | Err(AppError::Validation(_)))));
+        Ok(())
+    }
+}
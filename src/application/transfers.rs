@@ -1,6 +1,7 @@
 use serde::{Serialize, Deserialize};
 use rusty_money::{iso, Money};
 use crate::domain::entities::{Category, Characteristic, Product, User};
+use crate::domain::values::pages::Page;
 use crate::domain::values::ValueInto;
 
 // EntityからDTOへの変換トレイト
@@ -8,7 +9,7 @@ pub trait EntityToDto<T>{
     // 1つのEntityからDTOに変換する
     fn convert(value:&T) -> Self;
     // 複数のEntityから複数のDTOに変換する
-    fn converts(values: &Vec<T>) -> Vec<Self> where Self:Sized;
+    fn converts(values: &[T]) -> Vec<Self> where Self:Sized;
 }
 
 ///
@@ -27,7 +28,7 @@ impl EntityToDto<Category> for CategoryDto {
             name: value.name.value()
         }
     }
-    fn converts(values: &Vec<Category>) -> Vec<Self> where Self: Sized {
+    fn converts(values: &[Category]) -> Vec<Self> where Self: Sized {
         let mut results:Vec<Self> = Vec::new();
         for value in values {
             results.push(Self::convert(value));
@@ -58,7 +59,7 @@ impl EntityToDto<Product> for ProductDto {
             category: _category ,
         }
     }
-    fn converts(values: &Vec<Product>) -> Vec<Self> where Self: Sized {
+    fn converts(values: &[Product]) -> Vec<Self> where Self: Sized {
         let mut results:Vec<Self> = Vec::new();
         for value in values {
             results.push(Self::convert(value));
@@ -86,7 +87,38 @@ impl EntityToDto<User> for UserDto {
             mail: value.mail.value()
         }
     }
-    fn converts(values: &Vec<User>) -> Vec<Self> where Self: Sized {
+    fn converts(values: &[User]) -> Vec<Self> where Self: Sized {
+        let mut results:Vec<Self> = Vec::new();
+        for value in values {
+            results.push(Self::convert(value));
+        }
+        results
+    }
+}
+
+///
+/// ページ単位の検索結果DTO
+///
+#[derive(Serialize , Deserialize , Debug , Clone)]
+pub struct PageDto<T>{
+    pub items:          Vec<T> ,
+    pub total_count:    u64 ,
+    pub total_pages:    u64 ,
+    pub page:           u64 ,
+    pub page_size:      u64
+}
+// EntityからDTOに変換
+impl EntityToDto<Page<Product>> for PageDto<ProductDto> {
+    fn convert(value: &Page<Product>) -> Self {
+        Self{
+            items: ProductDto::converts(&value.items) ,
+            total_count: value.total_count ,
+            total_pages: value.total_pages ,
+            page: value.page ,
+            page_size: value.page_size
+        }
+    }
+    fn converts(values: &[Page<Product>]) -> Vec<Self> where Self: Sized {
         let mut results:Vec<Self> = Vec::new();
         for value in values {
             results.push(Self::convert(value));
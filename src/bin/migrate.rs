@@ -0,0 +1,28 @@
+//! マイグレーションの実行
+//! 使い方: migrate [up|down|fresh|status|seed]  (省略時はup)
+//! 接続先はアプリケーションの設定(config/app.toml、環境変数のDATABASE_URL)
+use std::env;
+use sea_orm_migration::MigratorTrait;
+use app_commons::config::AppConfig;
+use app_commons::infrastructure::sea_orm::migration::Migrator;
+use app_commons::infrastructure::sea_orm::migration::seed::Seeder;
+use app_commons::infrastructure::sea_orm::pool_impl::SeaOrmPool;
+
+#[actix::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let db = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+    let command = env::args().nth(1).unwrap_or_else(|| String::from("up"));
+    match command.as_str() {
+        "up" => Migrator::up(&db , None).await? ,
+        "down" => Migrator::down(&db , Some(1)).await? ,
+        "fresh" => Migrator::fresh(&db).await? ,
+        "status" => Migrator::status(&db).await? ,
+        "seed" => {
+            let count = Seeder::run(&db).await?;
+            println!("{}件のサンプルデータを登録しました。" , count);
+        }
+        _ => anyhow::bail!("不明なコマンドです: {}" , command)
+    }
+    Ok(())
+}
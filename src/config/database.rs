@@ -0,0 +1,100 @@
+use std::str::FromStr;
+use std::time::Duration;
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use crate::config::{override_with, Profile};
+use crate::Result;
+
+///
+/// データベース接続の設定
+///
+#[derive(Debug , Clone , PartialEq , Serialize , Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub url:                String ,  // 接続URL
+    pub min_connections:    u32 ,     // 最小接続数
+    pub max_connections:    u32 ,     // 最大接続数
+    pub connect_timeout:    u64 ,     // 接続タイムアウト(秒)
+    pub idle_timeout:       u64 ,     // アイドル接続の破棄までの時間(秒)
+    pub max_lifetime:       u64 ,     // 接続の最大生存時間(秒)
+    pub sqlx_logging:       String ,  // SQLのログレベル(off,error,warn,info,debug,trace)
+    pub slow_statement_threshold: Option<u64> // 警告ログを出力する実行時間(ミリ秒)
+}
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self{
+            url: String::new() ,
+            min_connections: 5 ,
+            max_connections: 100 ,
+            connect_timeout: 8 ,
+            idle_timeout: 600 ,
+            max_lifetime: 1800 ,
+            sqlx_logging: String::from("info") ,
+            slow_statement_threshold: None
+        }
+    }
+}
+impl DatabaseConfig {
+    /// プロファイルごとの既定値
+    pub fn for_profile(profile: Profile) -> Self {
+        match profile {
+            Profile::Dev => Self::default() ,
+            Profile::Test => Self{ min_connections: 1 , max_connections: 10 ,
+                                   sqlx_logging: String::from("warn") , ..Self::default() } ,
+            Profile::Prod => Self{ sqlx_logging: String::from("warn") ,
+                                   slow_statement_threshold: Some(1000) , ..Self::default() }
+        }
+    }
+    /// DATABASE_で始まる環境変数で設定を上書きする
+    pub fn apply_env(&mut self) -> Result<()> {
+        override_with(&mut self.url , "DATABASE_URL")?;
+        override_with(&mut self.min_connections , "DATABASE_MIN_CONNECTIONS")?;
+        override_with(&mut self.max_connections , "DATABASE_MAX_CONNECTIONS")?;
+        override_with(&mut self.connect_timeout , "DATABASE_CONNECT_TIMEOUT")?;
+        override_with(&mut self.idle_timeout , "DATABASE_IDLE_TIMEOUT")?;
+        override_with(&mut self.max_lifetime , "DATABASE_MAX_LIFETIME")?;
+        override_with(&mut self.sqlx_logging , "DATABASE_SQLX_LOGGING")?;
+        let mut threshold = self.slow_statement_threshold.unwrap_or_default();
+        if override_with(&mut threshold , "DATABASE_SLOW_STATEMENT_THRESHOLD")? {
+            self.slow_statement_threshold = Some(threshold);
+        }
+        Ok(())
+    }
+    /// 設定値を検証し、不正な項目のメッセージを返す
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.url.is_empty() {
+            errors.push(String::from("database.url: 接続URL(DATABASE_URL)が設定されていません。"));
+        }
+        if self.max_connections == 0 {
+            errors.push(String::from("database.max_connections: 1以上を指定してください。"));
+        }
+        if self.min_connections > self.max_connections {
+            errors.push(format!("database.min_connections: 最大接続数({})以下を指定してください。" , self.max_connections));
+        }
+        if LevelFilter::from_str(&self.sqlx_logging).is_err() {
+            errors.push(format!("database.sqlx_logging: ログレベル:{}は不正です。" , self.sqlx_logging));
+        }
+        errors
+    }
+    /// SQLのログレベルを返す
+    pub fn logging_level(&self) -> LevelFilter {
+        LevelFilter::from_str(&self.sqlx_logging).unwrap_or(LevelFilter::Off)
+    }
+    /// 接続タイムアウト
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout)
+    }
+    /// アイドル接続の破棄までの時間
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout)
+    }
+    /// 接続の最大生存時間
+    pub fn max_lifetime(&self) -> Duration {
+        Duration::from_secs(self.max_lifetime)
+    }
+    /// 警告ログを出力する実行時間
+    pub fn slow_statement_threshold(&self) -> Option<Duration> {
+        self.slow_statement_threshold.map(Duration::from_millis)
+    }
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use crate::config::{override_with, Profile};
+use crate::Result;
+
+/// 開発、テスト用のシークレットキー
+pub const DEV_SECRET_KEY: &str = "app-secret";
+/// シークレットキーの最小長(prodプロファイル)
+pub const MIN_SECRET_LENGTH: usize = 32;
+/// 対応する署名アルゴリズム
+pub const ALGORITHMS: [&str; 3] = ["HS256" , "RS256" , "ES256"];
+
+///
+/// JWTの署名鍵
+/// HS256はsecret、RS256,ES256はPEM形式の鍵(文字列またはファイル)を指定する
+///
+#[derive(Debug , Clone , PartialEq , Serialize , Deserialize)]
+#[serde(default)]
+pub struct JwtKeyConfig {
+    pub kid:              String ,          // 鍵の識別子(JWTヘッダーのkid)
+    pub algorithm:        String ,          // 署名アルゴリズム(HS256,RS256,ES256)
+    pub secret:           String ,          // HS256のシークレットキー
+    pub private_key:      Option<String> ,  // 署名用の秘密鍵(PEM)
+    pub private_key_file: Option<String> ,  // 署名用の秘密鍵のファイル
+    pub public_key:       Option<String> ,  // 検証用の公開鍵(PEM)
+    pub public_key_file:  Option<String>    // 検証用の公開鍵のファイル
+}
+impl Default for JwtKeyConfig {
+    fn default() -> Self {
+        Self{
+            kid: String::new() ,
+            algorithm: String::from("HS256") ,
+            secret: String::new() ,
+            private_key: None ,
+            private_key_file: None ,
+            public_key: None ,
+            public_key_file: None
+        }
+    }
+}
+impl JwtKeyConfig {
+    /// HMACのアルゴリズムか判定する
+    pub fn is_hmac(&self) -> bool {
+        self.algorithm == "HS256"
+    }
+    // 鍵の設定値を検証する、署名に使う鍵は秘密鍵も必須とする
+    fn errors(&self , name: &str , signing: bool , profile: Profile) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !ALGORITHMS.contains(&self.algorithm.as_str()) {
+            errors.push(format!("{}.algorithm: {}は未対応です。({})" , name , self.algorithm , ALGORITHMS.join(",")));
+        } else if self.is_hmac() {
+            if self.secret.is_empty() {
+                errors.push(format!("{}.secret: シークレットキー(JWT_SECRET)が設定されていません。" , name));
+            } else if profile == Profile::Prod &&
+                (self.secret == DEV_SECRET_KEY || self.secret.len() < MIN_SECRET_LENGTH) {
+                errors.push(format!("{}.secret: 本番環境では{}文字以上の固有のキーを指定してください。" , name , MIN_SECRET_LENGTH));
+            }
+        } else {
+            if signing && self.private_key.is_none() && self.private_key_file.is_none() {
+                errors.push(format!("{}.private_key_file: {}の秘密鍵が設定されていません。" , name , self.algorithm));
+            }
+            if self.public_key.is_none() && self.public_key_file.is_none() {
+                errors.push(format!("{}.public_key_file: {}の公開鍵が設定されていません。" , name , self.algorithm));
+            }
+        }
+        errors
+    }
+}
+
+///
+/// JWTの設定
+/// 署名は現在の鍵で行い、検証は現在の鍵とkeys(ローテーション前の鍵)で行う
+///
+#[derive(Debug , Clone , PartialEq , Serialize , Deserialize)]
+#[serde(default)]
+pub struct JwtConfig {
+    #[serde(flatten)]
+    pub key:        JwtKeyConfig ,       // 署名に使う現在の鍵
+    pub keys:       Vec<JwtKeyConfig> ,  // 検証のみに使う鍵
+    pub issuer:     Option<String> ,     // 発行者(iss)
+    pub audience:   Option<String> ,     // 対象者(aud)
+    pub leeway:     u64 ,                // 有効期限の許容誤差(秒)
+    pub expires_in: u64 ,                // アクセストークンの有効期間(秒)
+    pub refresh_expires_in: u64 ,        // リフレッシュトークンの有効期間(秒)
+    pub denylist_reload: u64 ,           // 失効したトークンを読込み直す間隔(秒)
+    pub roles:      Vec<String>          // 認証したユーザーに付与するロール
+}
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self{
+            key: JwtKeyConfig{ secret: String::from(DEV_SECRET_KEY) , ..Default::default() } ,
+            keys: Vec::new() ,
+            issuer: None ,
+            audience: None ,
+            leeway: 60 ,
+            expires_in: 900 ,
+            refresh_expires_in: 1209600 ,
+            denylist_reload: 30 ,
+            roles: vec![String::from("user")]
+        }
+    }
+}
+impl JwtConfig {
+    /// プロファイルごとの既定値、prodはシークレットキーの指定を必須とする
+    pub fn for_profile(profile: Profile) -> Self {
+        match profile {
+            Profile::Prod => {
+                let mut config = Self::default();
+                config.key.secret = String::new();
+                config
+            },
+            _ => Self::default()
+        }
+    }
+    /// JWT_で始まる環境変数で設定を上書きする
+    pub fn apply_env(&mut self) -> Result<()> {
+        override_with(&mut self.key.kid , "JWT_KID")?;
+        override_with(&mut self.key.algorithm , "JWT_ALGORITHM")?;
+        override_with(&mut self.key.secret , "JWT_SECRET")?;
+        override_option(&mut self.key.private_key , "JWT_PRIVATE_KEY")?;
+        override_option(&mut self.key.private_key_file , "JWT_PRIVATE_KEY_FILE")?;
+        override_option(&mut self.key.public_key , "JWT_PUBLIC_KEY")?;
+        override_option(&mut self.key.public_key_file , "JWT_PUBLIC_KEY_FILE")?;
+        override_option(&mut self.issuer , "JWT_ISSUER")?;
+        override_option(&mut self.audience , "JWT_AUDIENCE")?;
+        override_with(&mut self.leeway , "JWT_LEEWAY")?;
+        override_with(&mut self.expires_in , "JWT_EXPIRES_IN")?;
+        override_with(&mut self.refresh_expires_in , "JWT_REFRESH_EXPIRES_IN")?;
+        override_with(&mut self.denylist_reload , "JWT_DENYLIST_RELOAD")?;
+        Ok(())
+    }
+    /// 設定値を検証し、不正な項目のメッセージを返す
+    pub fn errors(&self , profile: Profile) -> Vec<String> {
+        let mut errors = self.key.errors("jwt" , true , profile);
+        for (index , key) in self.keys.iter().enumerate() {
+            let name = format!("jwt.keys[{}]" , index);
+            if key.kid.is_empty() {
+                errors.push(format!("{}.kid: 検証用の鍵には識別子が必要です。" , name));
+            }
+            errors.extend(key.errors(&name , false , profile));
+        }
+        let mut kids: Vec<&str> = self.keys.iter().map(|key| key.kid.as_str()).collect();
+        kids.push(self.key.kid.as_str());
+        kids.sort_unstable();
+        if kids.windows(2).any(|pair| pair[0] == pair[1]) {
+            errors.push(String::from("jwt.keys: 鍵の識別子(kid)が重複しています。"));
+        }
+        if self.expires_in == 0 {
+            errors.push(String::from("jwt.expires_in: 1以上を指定してください。"));
+        }
+        if self.refresh_expires_in <= self.expires_in {
+            errors.push(String::from("jwt.refresh_expires_in: アクセストークンの有効期間より長い期間を指定してください。"));
+        }
+        if self.denylist_reload == 0 {
+            errors.push(String::from("jwt.denylist_reload: 1以上を指定してください。"));
+        }
+        errors
+    }
+}
+// 環境変数が設定されていれば省略可能な値を上書きする
+fn override_option(value: &mut Option<String> , key: &str) -> Result<()> {
+    let mut text = String::new();
+    if override_with(&mut text , key)? {
+        *value = Some(text);
+    }
+    Ok(())
+}
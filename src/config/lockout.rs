@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use crate::config::override_with;
+use crate::domain::lockout_policy::LockoutPolicy;
+use crate::Result;
+
+///
+/// 認証の失敗によるロックアウトの設定
+///
+#[derive(Debug , Clone , PartialEq , Serialize , Deserialize)]
+#[serde(default)]
+pub struct LockoutConfig {
+    pub user_threshold:     u32 ,   // ユーザー名ごとのロックまでの失敗回数
+    pub client_threshold:   u32 ,   // クライアントごとのロックまでの失敗回数、0はロックしない
+    pub base_lock:          u64 ,   // 最初のロック時間(秒)、以降の失敗ごとに2倍にする
+    pub max_lock:           u64 ,   // ロック時間の上限(秒)
+    pub reset_after:        u64     // 失敗回数を数え直すまでの時間(秒)
+}
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        let policy = LockoutPolicy::default();
+        Self{
+            user_threshold: policy.user_threshold ,
+            client_threshold: policy.client_threshold ,
+            base_lock: policy.base_lock ,
+            max_lock: policy.max_lock ,
+            reset_after: policy.reset_after
+        }
+    }
+}
+impl LockoutConfig {
+    /// LOCKOUT_で始まる環境変数で設定を上書きする
+    pub fn apply_env(&mut self) -> Result<()> {
+        override_with(&mut self.user_threshold , "LOCKOUT_USER_THRESHOLD")?;
+        override_with(&mut self.client_threshold , "LOCKOUT_CLIENT_THRESHOLD")?;
+        override_with(&mut self.base_lock , "LOCKOUT_BASE_LOCK")?;
+        override_with(&mut self.max_lock , "LOCKOUT_MAX_LOCK")?;
+        override_with(&mut self.reset_after , "LOCKOUT_RESET_AFTER")?;
+        Ok(())
+    }
+    /// 設定値を検証し、不正な項目のメッセージを返す
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.user_threshold == 0 {
+            errors.push(String::from("lockout.user_threshold: 1以上を指定してください。"));
+        }
+        if self.base_lock == 0 {
+            errors.push(String::from("lockout.base_lock: 1以上を指定してください。"));
+        }
+        if self.max_lock < self.base_lock {
+            errors.push(format!("lockout.max_lock: 最初のロック時間({})以上を指定してください。" , self.base_lock));
+        }
+        errors
+    }
+    /// 設定からロックアウトのポリシーを生成する
+    pub fn policy(&self) -> LockoutPolicy {
+        LockoutPolicy{
+            user_threshold: self.user_threshold ,
+            client_threshold: self.client_threshold ,
+            base_lock: self.base_lock ,
+            max_lock: self.max_lock ,
+            reset_after: self.reset_after
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use crate::config::{override_with, Profile};
+use crate::domain::password_policy::{CharacterClass, PasswordPolicy};
+use crate::domain::values::rules::LengthRule;
+use crate::Result;
+
+/// 既定で禁止するよく使われるパスワード、実行時の作業ディレクトリに依存しないよう埋め込む
+pub const DEFAULT_COMMON_PASSWORDS: &str = include_str!("../../config/common_passwords.txt");
+
+///
+/// パスワードポリシーの設定
+///
+#[derive(Debug , Clone , PartialEq , Serialize , Deserialize)]
+#[serde(default)]
+pub struct PasswordConfig {
+    pub min_length:         usize ,                 // 最小文字数
+    pub max_length:         usize ,                 // 最大文字数
+    pub required_classes:   Vec<CharacterClass> ,   // 必須の文字種(lower,upper,digit,symbol)
+    pub forbid_user_info:   bool ,                  // ユーザー名、メールアドレスを含むパスワードを禁止する
+    pub common_passwords_file: Option<String>       // 禁止するパスワードのファイル(1行1件、#で始まる行はコメント、省略時は埋め込みの一覧)
+}
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self{
+            min_length: 8 ,
+            max_length: 64 ,
+            required_classes: vec![CharacterClass::Lower , CharacterClass::Digit] ,
+            forbid_user_info: true ,
+            common_passwords_file: None
+        }
+    }
+}
+impl PasswordConfig {
+    /// プロファイルごとの既定値、prodは文字数と文字種を厳しくする
+    pub fn for_profile(profile: Profile) -> Self {
+        match profile {
+            Profile::Prod => Self{ min_length: 10 ,
+                                   required_classes: vec![CharacterClass::Lower , CharacterClass::Upper , CharacterClass::Digit] ,
+                                   ..Self::default() } ,
+            _ => Self::default()
+        }
+    }
+    /// PASSWORD_で始まる環境変数で設定を上書きする
+    pub fn apply_env(&mut self) -> Result<()> {
+        override_with(&mut self.min_length , "PASSWORD_MIN_LENGTH")?;
+        override_with(&mut self.max_length , "PASSWORD_MAX_LENGTH")?;
+        override_with(&mut self.forbid_user_info , "PASSWORD_FORBID_USER_INFO")?;
+        let mut file = String::new();
+        if override_with(&mut file , "PASSWORD_COMMON_PASSWORDS_FILE")? {
+            self.common_passwords_file = Some(file).filter(|file| !file.is_empty());
+        }
+        Ok(())
+    }
+    /// 設定値を検証し、不正な項目のメッセージを返す
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.min_length == 0 {
+            errors.push(String::from("password.min_length: 1以上を指定してください。"));
+        }
+        if self.max_length < self.min_length {
+            errors.push(format!("password.max_length: 最小文字数({})以上を指定してください。" , self.min_length));
+        }
+        errors
+    }
+    /// 設定からパスワードポリシーを生成する、禁止するパスワードはファイルが指定されていれば読込み、なければ埋め込みの一覧を使う
+    pub fn policy(&self) -> Result<PasswordPolicy> {
+        let policy = PasswordPolicy::new(LengthRule::new(self.min_length , self.max_length) ,
+                                         self.required_classes.clone() , self.forbid_user_info);
+        let text = match self.common_passwords_file.as_ref() {
+            Some(file) => fs::read_to_string(file)
+                .with_context(|| format!("パスワードのファイル:{}を読込めません。" , file))? ,
+            None => String::from(DEFAULT_COMMON_PASSWORDS)
+        };
+        let passwords = text.lines().map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        Ok(policy.with_common_passwords(passwords))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy() -> anyhow::Result<()> {
+        let policy = PasswordConfig::for_profile(Profile::Prod).policy()?;
+        assert_eq!(policy.length , LengthRule::new(10 , 64));
+        assert_eq!(policy.required_classes.len() , 3);
+        assert!(policy.common_passwords() > 0);
+        let config = PasswordConfig{ common_passwords_file: Some(String::from("config/not_found.txt")) , ..Default::default() };
+        assert!(config.policy().is_err());
+        let config = PasswordConfig{ min_length: 0 , ..Default::default() };
+        assert_eq!(config.errors().len() , 1);
+        Ok(())
+    }
+
+    // ファイルを指定しない場合は作業ディレクトリに関係なく埋め込みの一覧を使う
+    #[test]
+    fn embedded_common_passwords() -> anyhow::Result<()> {
+        let config = PasswordConfig::default();
+        assert_eq!(config.common_passwords_file , None);
+        let embedded = config.policy()?;
+        assert!(embedded.common_passwords() > 0);
+        let file = std::env::temp_dir().join(format!("common_passwords_{}.txt" , std::process::id()));
+        fs::write(&file , "# 上書き\nletmein\n\n")?;
+        let config = PasswordConfig{ common_passwords_file: Some(file.to_string_lossy().into_owned()) , ..Default::default() };
+        let policy = config.policy();
+        fs::remove_file(&file)?;
+        assert_eq!(policy?.common_passwords() , 1);
+        Ok(())
+    }
+}
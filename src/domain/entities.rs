@@ -1,7 +1,7 @@
-use easy_hasher::easy_hasher::sha3_512;
 use uuid::Uuid;
 use crate::domain::values::products::{ProductId, ProductName, ProductPrice};
 use crate::domain::values::categories::{CategoryId, CategoryName};
+use crate::domain::lockout_policy::LockoutPolicy;
 use crate::domain::values::users::{Mail, Password, UserId, UserName};
 use crate::domain::values::ValueInto;
 use crate::Result;
@@ -81,7 +81,7 @@ impl Characteristic for Product {
     }
     // 識別子の同一性を検証する
     fn equals(&self, value: &Self::Identifier) -> bool {
-        self.id.eq(&value)
+        self.id.eq(value)
     }
 }
 
@@ -97,15 +97,12 @@ pub struct User {
 }
 impl User {
     /// 値を生成する
-    /// user_idの生成、パスワードのハッシュ変換をする
+    /// user_idを生成する、パスワードのハッシュ変換は永続化時にPasswordHasherで行う
     pub fn new(user_name: UserName , password:Password , mail:Mail) -> Result<Self> {
         // uuidでユーザーIdを生成する
         let _user_id = Uuid::new_v4().to_string();
-        // 受け取ったパスワードをSHA3-512でハッシュ変換する
-        let _password = sha3_512(&password.value()).to_hex_string();
         // 値を生成した結果を返す
-        Ok(Self {user_id: UserId::try_from(_user_id)? , user_name,
-            password: Password::try_from(_password)? , mail})
+        Ok(Self {user_id: UserId::try_from(_user_id)? , user_name, password , mail})
     }
     /// すべての値を受け取って値を生成する
     pub fn rebuilding(user_id: UserId , user_name: UserName , password: Password , mail: Mail) -> Self{
@@ -130,6 +127,126 @@ impl Characteristic for User {
     }
 }
 
+///
+/// リフレッシュトークンを表すEntity
+/// トークン本体は保存せずハッシュ値で識別し、ローテーションで発行したトークンは同じfamily_idを持つ
+///
+#[derive(Clone , PartialEq , Eq , Debug)]
+pub struct RefreshToken {
+    pub token_hash:     String ,            // トークンのハッシュ値
+    pub family_id:      String ,            // 最初の発行から引き継ぐ識別子
+    pub user_id:        UserId ,            // ユーザーID
+    pub expires_at:     i64 ,               // 有効期限(UNIX時間)
+    pub revoked:        bool ,              // 失効済
+    pub replaced_by:    Option<String>      // ローテーション後のトークンのハッシュ値
+}
+impl RefreshToken {
+    /// 新しいファミリーのトークンを生成する
+    pub fn new(token_hash: String , user_id: UserId , expires_at: i64) -> Self {
+        Self{ token_hash , family_id: Uuid::new_v4().to_string() , user_id , expires_at ,
+              revoked: false , replaced_by: None }
+    }
+    /// ローテーションで発行する次のトークンを生成する
+    pub fn rotate(&self , token_hash: String , expires_at: i64) -> Self {
+        Self{ token_hash , family_id: self.family_id.clone() , user_id: self.user_id.clone() , expires_at ,
+              revoked: false , replaced_by: None }
+    }
+    /// 失効済またはローテーション済で、利用できないトークンか判定する
+    pub fn is_used(&self) -> bool {
+        self.revoked || self.replaced_by.is_some()
+    }
+    /// 有効期限切れか判定する
+    pub fn is_expired(&self , now: i64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+///
+/// 認証の失敗回数を表すEntity
+/// ユーザー名とクライアントの識別子ごとに数え、複数のインスタンスで共有するため永続化する
+///
+#[derive(Clone , PartialEq , Eq , Debug)]
+pub struct LoginAttempt {
+    pub key:            String ,        // 対象(user:ユーザー名,client:クライアントの識別子)
+    pub failures:       u32 ,           // 連続した失敗回数
+    pub last_failed_at: i64 ,           // 最後に失敗した日時(UNIX時間)
+    pub locked_until:   Option<i64>     // ロックの期限(UNIX時間)
+}
+impl LoginAttempt {
+    /// ユーザー名の失敗回数のキー
+    pub fn user_key(user_name: &UserName) -> String {
+        format!("user:{}" , user_name.value())
+    }
+    /// クライアントの失敗回数のキー
+    pub fn client_key(client: &str) -> String {
+        format!("client:{}" , client)
+    }
+    pub fn new(key: String) -> Self {
+        Self{ key , failures: 0 , last_failed_at: 0 , locked_until: None }
+    }
+    /// ロック中であれば残りの秒数を返す
+    pub fn locked(&self , now: i64) -> Option<u64> {
+        self.locked_until.filter(|until| *until > now).map(|until| (until - now) as u64)
+    }
+    /// 失敗を記録し、しきい値に達した場合はロックする
+    /// 最後の失敗から一定時間が経過していれば失敗回数を数え直す
+    pub fn fail(&mut self , now: i64 , threshold: u32 , policy: &LockoutPolicy) {
+        if now - self.last_failed_at > policy.reset_after as i64 && self.locked(now).is_none() {
+            self.failures = 0;
+        }
+        self.failures = self.failures.saturating_add(1);
+        self.last_failed_at = now;
+        let seconds = policy.lock_seconds(self.failures , threshold);
+        if seconds > 0 {
+            self.locked_until = Some(now + seconds as i64);
+        }
+    }
+}
+
+///
+/// 認証の結果
+///
+#[derive(Clone , Copy , PartialEq , Eq , Debug)]
+pub enum AuthOutcome {
+    Success ,   // 成功
+    Failure ,   // ユーザー名またはパスワードの誤り
+    Locked      // ロック中のため照合しなかった
+}
+impl AuthOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthOutcome::Success => "success" ,
+            AuthOutcome::Failure => "failure" ,
+            AuthOutcome::Locked => "locked"
+        }
+    }
+    /// 文字列から変換する
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "success" => Some(AuthOutcome::Success) ,
+            "failure" => Some(AuthOutcome::Failure) ,
+            "locked" => Some(AuthOutcome::Locked) ,
+            _ => None
+        }
+    }
+}
+///
+/// 認証の試行を記録するEntity
+///
+#[derive(Clone , PartialEq , Eq , Debug)]
+pub struct AuthEvent {
+    pub user_name:      String ,            // 入力されたユーザー名
+    pub user_id:        Option<UserId> ,    // 認証に成功したユーザーのID
+    pub client:         String ,            // クライアントの識別子
+    pub outcome:        AuthOutcome ,       // 認証の結果
+    pub occurred_at:    i64                 // 発生日時(UNIX時間)
+}
+impl AuthEvent {
+    pub fn new(user_name: &UserName , user_id: Option<UserId> , client: &str , outcome: AuthOutcome , occurred_at: i64) -> Self {
+        Self{ user_name: user_name.value() , user_id , client: client.to_string() , outcome , occurred_at }
+    }
+}
+
 #[cfg(test)]
 mod tests{
     use super::*;
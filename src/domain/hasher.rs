@@ -0,0 +1,14 @@
+use crate::domain::values::users::Password;
+use crate::Result;
+
+///
+/// パスワードのハッシュ変換と照合を行うトレイト
+///
+pub trait PasswordHasher : Send + Sync + 'static {
+    /// 平文のパスワードをハッシュ変換する
+    fn hash(&self , password: &Password) -> Result<Password>;
+    /// 平文のパスワードと保存済のハッシュを照合する
+    fn verify(&self , password: &Password , hashed: &Password) -> Result<bool>;
+    /// 保存済のハッシュを現在の方式で再変換する必要があるか判定する
+    fn needs_rehash(&self , hashed: &Password) -> bool;
+}
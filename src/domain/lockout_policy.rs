@@ -0,0 +1,47 @@
+///
+/// 認証の失敗によるロックアウトのポリシー
+/// 失敗回数がしきい値に達するとロックし、以降の失敗ごとにロック時間を2倍にする
+///
+#[derive(Clone , Copy , PartialEq , Eq , Debug)]
+pub struct LockoutPolicy {
+    pub user_threshold:     u32 ,   // ユーザー名ごとのロックまでの失敗回数
+    pub client_threshold:   u32 ,   // クライアントごとのロックまでの失敗回数
+    pub base_lock:          u64 ,   // 最初のロック時間(秒)
+    pub max_lock:           u64 ,   // ロック時間の上限(秒)
+    pub reset_after:        u64     // 最後の失敗からこの時間(秒)が経過したら失敗回数を数え直す
+}
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self{ user_threshold: 5 , client_threshold: 20 , base_lock: 60 , max_lock: 3600 , reset_after: 900 }
+    }
+}
+impl LockoutPolicy {
+    /// 失敗回数に対するロック時間(秒)、しきい値未満は0
+    pub fn lock_seconds(&self , failures: u32 , threshold: u32) -> u64 {
+        if threshold == 0 || failures < threshold {
+            return 0;
+        }
+        // 2の累乗が上限を超えないようにシフト量を制限する
+        let exponent = (failures - threshold).min(32);
+        self.base_lock.saturating_mul(1u64 << exponent).min(self.max_lock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_seconds() {
+        let policy = LockoutPolicy::default();
+        assert_eq!(policy.lock_seconds(4 , 5) , 0);
+        assert_eq!(policy.lock_seconds(5 , 5) , 60);
+        assert_eq!(policy.lock_seconds(6 , 5) , 120);
+        assert_eq!(policy.lock_seconds(8 , 5) , 480);
+        // 上限を超えない
+        assert_eq!(policy.lock_seconds(11 , 5) , 3600);
+        assert_eq!(policy.lock_seconds(u32::MAX , 5) , 3600);
+        // しきい値0はロックしない
+        assert_eq!(policy.lock_seconds(100 , 0) , 0);
+    }
+}
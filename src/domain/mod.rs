@@ -1,4 +1,7 @@
 pub mod values;
 pub mod entities;
 pub mod repositories;
-pub mod services;
\ No newline at end of file
+pub mod services;
+pub mod hasher;
+pub mod lockout_policy;
+pub mod password_policy;
\ No newline at end of file
@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::domain::entities::User;
+use crate::domain::values::rules::LengthRule;
+use crate::domain::values::users::Password;
+use crate::domain::values::ValueInto;
+use crate::i18n::Message;
+
+/// ユーザー名、メールアドレスの一部として検査する最小文字数
+const MIN_USER_INFO_LENGTH: usize = 3;
+
+///
+/// パスワードに含める文字種
+///
+#[derive(Clone , Copy , PartialEq , Eq , Debug , Serialize , Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CharacterClass {
+    Lower ,     // 英小文字
+    Upper ,     // 英大文字
+    Digit ,     // 数字
+    Symbol      // 記号(英数字以外のASCII文字)
+}
+impl CharacterClass {
+    /// 文字がこの文字種か判定する
+    pub fn matches(&self , c: char) -> bool {
+        match self {
+            CharacterClass::Lower => c.is_ascii_lowercase() ,
+            CharacterClass::Upper => c.is_ascii_uppercase() ,
+            CharacterClass::Digit => c.is_ascii_digit() ,
+            CharacterClass::Symbol => c.is_ascii_punctuation() || c == ' '
+        }
+    }
+    /// 文字種名のメッセージキー
+    pub fn label(&self) -> &'static str {
+        match self {
+            CharacterClass::Lower => "password.class.lower" ,
+            CharacterClass::Upper => "password.class.upper" ,
+            CharacterClass::Digit => "password.class.digit" ,
+            CharacterClass::Symbol => "password.class.symbol"
+        }
+    }
+}
+
+///
+/// パスワードポリシーの違反
+///
+#[derive(Clone , PartialEq , Eq , Debug)]
+pub enum PasswordViolation {
+    Length(LengthRule) ,            // 文字数が範囲外
+    MissingClass(CharacterClass) ,  // 必須の文字種を含まない
+    ContainsUserName ,              // ユーザー名を含む
+    ContainsMail ,                  // メールアドレスの一部を含む
+    Common                          // よく使われるパスワード
+}
+impl PasswordViolation {
+    /// エラーコード
+    pub fn code(&self) -> &'static str {
+        match self {
+            PasswordViolation::Length(_) => "length" ,
+            PasswordViolation::MissingClass(_) => "character_class" ,
+            PasswordViolation::ContainsUserName => "user_name" ,
+            PasswordViolation::ContainsMail => "mail" ,
+            PasswordViolation::Common => "common"
+        }
+    }
+    /// 違反のメッセージ
+    pub fn message(&self) -> Message {
+        match self {
+            PasswordViolation::Length(rule) => Message::new("password.length")
+                .with_param("min" , rule.min).with_param("max" , rule.max) ,
+            PasswordViolation::MissingClass(class) => Message::new("password.character_class")
+                .with_label("class" , class.label()) ,
+            PasswordViolation::ContainsUserName => Message::new("password.user_name") ,
+            PasswordViolation::ContainsMail => Message::new("password.mail") ,
+            PasswordViolation::Common => Message::new("password.common")
+        }
+    }
+}
+
+///
+/// パスワードポリシー
+/// 登録とパスワード変更で新しいパスワードに適用する、認証では適用しない
+///
+#[derive(Clone , PartialEq , Debug)]
+pub struct PasswordPolicy {
+    pub length:             LengthRule ,            // 文字数
+    pub required_classes:   Vec<CharacterClass> ,   // 必須の文字種
+    pub forbid_user_info:   bool ,                  // ユーザー名、メールアドレスを含むパスワードを禁止する
+    common_passwords:       HashSet<String>         // 禁止するパスワード(小文字)
+}
+impl PasswordPolicy {
+    pub fn new(length: LengthRule , required_classes: Vec<CharacterClass> , forbid_user_info: bool) -> Self {
+        Self{ length , required_classes , forbid_user_info , common_passwords: HashSet::new() }
+    }
+    /// 禁止するパスワードを追加する、大文字小文字は区別しない
+    pub fn with_common_passwords<I , S>(mut self , passwords: I) -> Self
+        where I: IntoIterator<Item=S> , S: AsRef<str> {
+        self.common_passwords.extend(passwords.into_iter().map(|password| password.as_ref().to_lowercase()));
+        self
+    }
+    /// 禁止するパスワードの件数
+    pub fn common_passwords(&self) -> usize {
+        self.common_passwords.len()
+    }
+    /// ユーザーの新しいパスワードを検査し、違反したすべてのルールを返す
+    pub fn check(&self , password: &Password , user: &User) -> Vec<PasswordViolation> {
+        let password = password.value();
+        let mut violations = Vec::new();
+        if !self.length.contains(&password) {
+            violations.push(PasswordViolation::Length(self.length));
+        }
+        for class in self.required_classes.iter() {
+            if !password.chars().any(|c| class.matches(c)) {
+                violations.push(PasswordViolation::MissingClass(*class));
+            }
+        }
+        let lower = password.to_lowercase();
+        if self.forbid_user_info {
+            if contains_part(&lower , &user.user_name.value()) {
+                violations.push(PasswordViolation::ContainsUserName);
+            }
+            // ローカル部とドメインの先頭のラベルを検査する
+            let mail = [user.mail.local_part() , user.mail.domain().split('.').next().unwrap_or_default()];
+            if mail.iter().any(|part| contains_part(&lower , part)) {
+                violations.push(PasswordViolation::ContainsMail);
+            }
+        }
+        if self.common_passwords.contains(&lower) {
+            violations.push(PasswordViolation::Common);
+        }
+        violations
+    }
+}
+// 短すぎる値は偶然の一致を避けるため検査しない
+fn contains_part(password: &str , part: &str) -> bool {
+    part.chars().count() >= MIN_USER_INFO_LENGTH && password.contains(&part.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::domain::values::users::{Mail, UserName};
+
+    fn user() -> Result<User> {
+        Ok(User::new(UserName::try_from(String::from("yamada01"))? ,
+                     Password::try_from(String::from("dummy"))? ,
+                     Mail::try_from(String::from("taro@sample.com"))?)?)
+    }
+
+    #[test]
+    fn check() -> Result<()> {
+        let policy = PasswordPolicy::new(LengthRule::new(8 , 64) ,
+            vec![CharacterClass::Lower , CharacterClass::Upper , CharacterClass::Digit] , true)
+            .with_common_passwords(["Password1" , "qwerty"]);
+        let user = user()?;
+        let check = |password: &str| policy.check(&Password::try_from(password.to_string()).unwrap() , &user);
+        assert!(check("Kiyomizu2022").is_empty());
+        // 違反したすべてのルールを返す
+        assert_eq!(check("abc") , vec![PasswordViolation::Length(policy.length) ,
+            PasswordViolation::MissingClass(CharacterClass::Upper) , PasswordViolation::MissingClass(CharacterClass::Digit)]);
+        assert_eq!(check("PASSWORD1") , vec![PasswordViolation::MissingClass(CharacterClass::Lower) , PasswordViolation::Common]);
+        // ユーザー名とメールアドレスは大文字小文字を区別しない
+        assert_eq!(check("My-Yamada01") , vec![PasswordViolation::ContainsUserName]);
+        assert_eq!(check("Taro12345") , vec![PasswordViolation::ContainsMail]);
+        assert_eq!(check("Sample2022") , vec![PasswordViolation::ContainsMail]);
+        assert!(PasswordPolicy{ forbid_user_info: false , ..policy.clone() }.check(
+            &Password::try_from(String::from("Yamada0123"))? , &user).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn messages() {
+        assert_eq!(PasswordViolation::Length(LengthRule::new(8 , 64)).message().to_string() ,
+                   "パスワードは8文字以上64文字以内にして下さい。");
+        assert_eq!(PasswordViolation::MissingClass(CharacterClass::Digit).message().render(crate::i18n::Locale::En) ,
+                   "Password must contain a digit.");
+    }
+}
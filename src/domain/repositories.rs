@@ -1,20 +1,28 @@
 use async_trait::async_trait;
-use crate::domain::entities::{Category, Product, User};
+use crate::domain::entities::{AuthEvent, Category, LoginAttempt, Product, RefreshToken, User};
 use crate::domain::values::categories::CategoryId;
-use crate::domain::values::products::ProductName;
-use crate::domain::values::users::UserName;
+use crate::domain::values::pages::{Page, PageRequest};
+use crate::domain::values::products::{ProductId, ProductName, ProductSearchCriteria, ProductSortKey};
+use crate::domain::values::users::{Mail, UserId, UserName};
 use crate::Result;
 
 /// 商品 Repository
 #[async_trait]
 pub trait ProductRepository: Send + Sync + 'static {
     type Transaction;
-    /// 商品キーワード検索する
-    async fn select_by_name_like(&self , _: &Self::Transaction , keyword: &ProductName) -> Result<Vec<Product>>;
+    /// 検索条件に該当する商品を、指定された並び順で指定ページ分取得する
+    async fn select_by_criteria(&self , _: &Self::Transaction , criteria: &ProductSearchCriteria ,
+                                page: &PageRequest , sort: &ProductSortKey) -> Result<Page<Product>>;
     /// 新しい商品を永続化する
     async fn insert(&self , _: &Self::Transaction , product: &Product) -> Result<Product>;
     /// 商品名で検索する
     async fn exists(&self , _:&Self::Transaction , name: &ProductName) -> Result<bool>;
+    /// 指定された商品番号で商品を取得する
+    async fn select_by_id(&self , _: &Self::Transaction , id: &ProductId) -> Result<Option<Product>>;
+    /// 商品を変更する
+    async fn update(&self , _: &Self::Transaction , product: &Product) -> Result<Product>;
+    /// 指定された商品番号の商品を削除する
+    async fn delete(&self , _: &Self::Transaction , id: &ProductId) -> Result<bool>;
 }
 /// 商品カテゴリ Repository
 #[async_trait]
@@ -31,6 +39,59 @@ pub trait UserRepository : Send + Sync + 'static {
     type Transaction;
     /// 指定されたユーザー名で問合せする
     async fn select_by_name(&self , _: &Self::Transaction, user_name: &UserName) -> Result<Option<User>>;
+    /// 指定されたユーザーIDで問合せする
+    async fn select_by_id(&self , _: &Self::Transaction, user_id: &UserId) -> Result<Option<User>>;
+    /// 指定されたユーザー名のユーザーが存在するか確認する
+    async fn exists_by_name(&self , _: &Self::Transaction , user_name: &UserName) -> Result<bool>;
+    /// 指定されたメールアドレスのユーザーが存在するか確認する
+    async fn exists_by_mail(&self , _: &Self::Transaction , mail: &Mail) -> Result<bool>;
     /// 新しいユーザーを永続化する
     async fn insert(&self , _: &Self::Transaction , user: &User) -> Result<User>;
-}
\ No newline at end of file
+    /// ユーザーのパスワードを変更する
+    async fn update_password(&self , _: &Self::Transaction , user: &User) -> Result<User>;
+}
+/// リフレッシュトークン Repository
+#[async_trait]
+pub trait RefreshTokenRepository : Send + Sync + 'static {
+    type Transaction;
+    /// 新しいトークンを永続化する
+    async fn insert(&self , _: &Self::Transaction , token: &RefreshToken) -> Result<()>;
+    /// ハッシュ値でトークンを取得する
+    async fn select_by_hash(&self , _: &Self::Transaction , token_hash: &str) -> Result<Option<RefreshToken>>;
+    /// 未使用のトークンをローテーション済にする、既に使用済の場合はfalseを返す
+    async fn replace(&self , _: &Self::Transaction , token_hash: &str , replaced_by: &str) -> Result<bool>;
+    /// ファミリーのトークンをすべて失効させる
+    async fn revoke_family(&self , _: &Self::Transaction , family_id: &str) -> Result<u64>;
+}
+/// 失効したアクセストークン(jti) Repository
+#[async_trait]
+pub trait RevokedTokenRepository : Send + Sync + 'static {
+    type Transaction;
+    /// 失効したトークンの識別子を永続化する
+    async fn insert(&self , _: &Self::Transaction , jti: &str , expires_at: i64) -> Result<()>;
+    /// 有効期限内の失効したトークンを取得する
+    async fn select_active(&self , _: &Self::Transaction , now: i64) -> Result<Vec<(String , i64)>>;
+    /// 有効期限を過ぎたトークンを削除する
+    async fn delete_expired(&self , _: &Self::Transaction , now: i64) -> Result<u64>;
+}
+/// 認証の失敗回数 Repository
+#[async_trait]
+pub trait LoginAttemptRepository : Send + Sync + 'static {
+    type Transaction;
+    /// キーで失敗回数を取得する
+    async fn select_by_key(&self , _: &Self::Transaction , key: &str) -> Result<Option<LoginAttempt>>;
+    /// キーで失敗回数を更新のためにロックして取得する、ない場合は0回で登録する
+    /// 同じキーの更新はトランザクションの終了まで待たされる
+    async fn select_for_update(&self , _: &Self::Transaction , key: &str) -> Result<LoginAttempt>;
+    /// 失敗回数を永続化する、登録済の場合は更新する
+    async fn save(&self , _: &Self::Transaction , attempt: &LoginAttempt) -> Result<()>;
+    /// 失敗回数を削除する
+    async fn delete(&self , _: &Self::Transaction , key: &str) -> Result<()>;
+}
+/// 認証イベント Repository
+#[async_trait]
+pub trait AuthEventRepository : Send + Sync + 'static {
+    type Transaction;
+    /// 認証イベントを永続化する
+    async fn insert(&self , _: &Self::Transaction , event: &AuthEvent) -> Result<()>;
+}
@@ -1,38 +1,79 @@
 use async_trait::async_trait;
 use crate::domain::entities::{Category, Product, User};
 use crate::domain::values::categories::CategoryId;
-use crate::domain::values::products::ProductName;
+use crate::domain::values::pages::{Page, PageRequest};
+use crate::domain::values::products::{ProductId, ProductName, ProductSearchCriteria, ProductSortKey};
+use crate::domain::values::users::{Credentials, Mail, PasswordChange, UserId, UserName};
 use crate::Result;
 /// カテゴリを扱うService
 #[async_trait]
 pub trait CategoryService : Send + Sync + 'static {
-    type Database;
+    type Transaction;
     /// すべてのカテゴリを取得する
-    async fn all(&self , _: &Self::Database) -> Result<Vec<Category>>;
+    async fn all(&self , _: &Self::Transaction) -> Result<Vec<Category>>;
     /// 指定されたカテゴリIdのカテゴリを取得する
-    async fn by_id(&self , _: &Self::Database , id: &CategoryId) -> Result<Category>;
+    async fn by_id(&self , _: &Self::Transaction , id: &CategoryId) -> Result<Category>;
 }
 /// 商品を扱うService
 #[async_trait]
 pub trait ProductService : Send + Sync + 'static  {
-    type Database;
-    // 指定されたキーワードの商品を取得する
-    async fn by_keyword(&self , _: &Self::Database , keyword: &ProductName) -> Result<Vec<Product>>;
+    type Transaction;
+    // 検索条件に該当する商品をページ単位で取得する
+    async fn by_criteria(&self , _: &Self::Transaction , criteria: &ProductSearchCriteria ,
+                         page: &PageRequest , sort: &ProductSortKey) -> Result<Page<Product>>;
     // 商品を永続化する
-    async fn register(&self , _: &Self::Database, product: &Product) -> Result<Product>;
+    async fn register(&self , _: &Self::Transaction, product: &Product) -> Result<Product>;
     // 商品の存在確認する
-    async fn exists(&self , _: &Self::Database , name: &ProductName) -> Result<()>;
+    async fn exists(&self , _: &Self::Transaction , name: &ProductName) -> Result<()>;
+    // 指定された商品番号の商品を取得する
+    async fn by_id(&self , _: &Self::Transaction , id: &ProductId) -> Result<Product>;
+    // 商品を変更する
+    async fn update(&self , _: &Self::Transaction , product: &Product) -> Result<Product>;
+    // 指定された商品番号の商品を削除する
+    async fn delete(&self , _: &Self::Transaction , id: &ProductId) -> Result<()>;
 }
 /// ユーザーを扱うService
 #[async_trait]
 pub trait UserService : Send + Sync + 'static {
-    type Database;
+    type Transaction;
     /// ユーザーを永続化する
-    async fn register(&self , _: &Self::Database , user: &User) -> Result<User>;
+    async fn register(&self , _: &Self::Transaction , user: &User) -> Result<User>;
+    /// ユーザー名の存在確認する
+    async fn exists_name(&self , _: &Self::Transaction , user_name: &UserName) -> Result<()>;
+    /// メールアドレスの存在確認する
+    async fn exists_mail(&self , _: &Self::Transaction , mail: &Mail) -> Result<()>;
     /// ユーザーを認証する
-    async fn authenticate(&self , _:&Self::Database , user: &User) -> Result<User>;
+    async fn authenticate(&self , _:&Self::Transaction , credentials: &Credentials) -> Result<User>;
+    /// 指定されたユーザーIDのユーザーを取得する
+    async fn by_id(&self , _: &Self::Transaction , user_id: &UserId) -> Result<User>;
+    /// 現在のパスワードを照合してパスワードを変更する
+    async fn change_password(&self , _: &Self::Transaction , user: &User , change: &PasswordChange) -> Result<User>;
+}
+/// リフレッシュトークンと失効したアクセストークンを扱うService
+#[async_trait]
+pub trait TokenService : Send + Sync + 'static {
+    type Transaction;
+    /// 新しいリフレッシュトークンを発行する
+    async fn issue(&self , _: &Self::Transaction , user_id: &UserId) -> Result<String>;
+    /// リフレッシュトークンをローテーションし、新しいトークンとユーザーIDを返す
+    /// ローテーション済のトークンが再利用された場合はファミリーを失効させる
+    async fn rotate(&self , _: &Self::Transaction , token: &str) -> Result<(String , UserId)>;
+    /// リフレッシュトークンのファミリーを失効させる
+    async fn revoke(&self , _: &Self::Transaction , token: &str) -> Result<()>;
+    /// アクセストークンの識別子(jti)を失効させる
+    async fn deny(&self , _: &Self::Transaction , jti: &str , expires_at: i64) -> Result<()>;
+    /// 有効期限内の失効したアクセストークンの識別子を取得する
+    async fn denied(&self , _: &Self::Transaction) -> Result<Vec<(String , i64)>>;
+}
+/// 認証の失敗回数とロックアウトを扱うService
+#[async_trait]
+pub trait LoginAttemptService : Send + Sync + 'static {
+    type Transaction;
+    /// ユーザー名とクライアントがロックされていないか確認する
+    /// ロック中の場合は認証イベントを記録してエラーを返す
+    async fn check(&self , _: &Self::Transaction , user_name: &UserName , client: &str) -> Result<()>;
+    /// 認証の成功を記録し、ユーザー名の失敗回数を消去する
+    async fn succeeded(&self , _: &Self::Transaction , user: &User , client: &str) -> Result<()>;
+    /// 認証の失敗を記録し、失敗回数に応じてロックする
+    async fn failed(&self , _: &Self::Transaction , user_name: &UserName , client: &str) -> Result<()>;
 }
-
-
-
-
@@ -1,5 +1,7 @@
+use crate::domain::values::rules::LengthRule;
 use crate::domain::values::ValueInto;
 use crate::{Result,AppError};
+use crate::i18n::Message;
 
 ///
 ///  カテゴリ番号を表す値オブジェクト
@@ -10,17 +12,18 @@ pub struct CategoryId(i32);
 impl TryFrom<i32> for CategoryId{
     type Error = AppError;
     fn try_from(value: i32) -> Result<Self> {
-        if value >= 1 && value <= 3 {
+        // 存在するカテゴリかはCategoryServiceで確認する
+        if value >= 1 {
             Ok(Self(value))
         }else {
-            Err(AppError::from("不正な商品カテゴリ番号です。"))
+            Err(AppError::from(Message::new("value.invalid").with_label("label" , "label.category.id")))
         }
     }
 }
 // 保持している値を返す
 impl ValueInto<i32> for CategoryId{
     fn value(&self) -> i32 {
-        self.0.clone()
+        self.0
     }
 }
 
@@ -29,17 +32,16 @@ impl ValueInto<i32> for CategoryId{
 ///
 #[derive(Clone , Debug , PartialEq , Eq)]
 pub struct CategoryName(String);
+impl CategoryName {
+    /// カテゴリ名の文字数
+    pub const LENGTH: LengthRule = LengthRule::new(1 , 20);
+}
 // 値を生成して返す、ルール違反の場合はAppErrorを返す
 impl TryFrom<String> for CategoryName{
     type Error = AppError;
     fn try_from(value: String) -> Result<Self> {
-        if value.is_empty() {
-            Err(AppError::from("カテゴリ名がありません。"))
-        }else if value.chars().count() > 20 {
-            Err(AppError::from("カテゴリ名の長さは20文字以内です。"))
-        }else {
-            Ok(Self(value))
-        }
+        Self::LENGTH.check("label.category.name" , &value)?;
+        Ok(Self(value))
     }
 }
 // 保持している値を返す
@@ -1,6 +1,8 @@
 pub mod categories;
 pub mod products;
 pub mod users;
+pub mod pages;
+pub mod rules;
 
 // Value Objectが保持する値を返す
 pub trait ValueInto<T> {
@@ -0,0 +1,66 @@
+use crate::{AppError, Result};
+use crate::i18n::Message;
+
+/// 1ページあたりの件数の既定値
+pub const DEFAULT_PAGE_SIZE: u64 = 10;
+/// 1ページあたりの件数の上限
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+///
+/// 取得するページを表す値オブジェクト
+///
+#[derive(Clone , Copy , PartialEq , Eq , Debug)]
+pub struct PageRequest{
+    page:       u64 ,   // ページ番号(1から始まる)
+    page_size:  u64     // 1ページあたりの件数
+}
+impl PageRequest {
+    /// 値を生成する、ルール違反の場合はAppErrorを返す
+    pub fn new(page: u64 , page_size: u64) -> Result<Self> {
+        if !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+            Err(AppError::from(Message::new("value.range").with_label("label" , "label.search.page_size")
+                .with_param("min" , 1).with_param("max" , MAX_PAGE_SIZE)))
+        }else if page < 1 {
+            Err(AppError::from(Message::new("value.min").with_label("label" , "label.search.page").with_param("min" , 1)))
+        }else if page > Self::max_page(page_size) {
+            Err(AppError::from(Message::new("value.range").with_label("label" , "label.search.page")
+                .with_param("min" , 1).with_param("max" , Self::max_page(page_size))))
+        }else{
+            Ok(Self{page , page_size})
+        }
+    }
+    /// 指定できるページ番号の上限を返す
+    /// 読み飛ばす件数がデータベースのOFFSET(i64)に収まる範囲に制限する
+    pub fn max_page(page_size: u64) -> u64 {
+        i64::MAX as u64 / page_size.max(1) + 1
+    }
+    /// ページ番号を返す
+    pub fn page(&self) -> u64 {
+        self.page
+    }
+    /// 1ページあたりの件数を返す
+    pub fn page_size(&self) -> u64 {
+        self.page_size
+    }
+    /// 読み飛ばす件数を返す
+    pub fn offset(&self) -> u64 {
+        (self.page - 1) * self.page_size
+    }
+}
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self{ page: 1 , page_size: DEFAULT_PAGE_SIZE }
+    }
+}
+
+///
+/// ページ単位の検索結果
+///
+#[derive(Clone , PartialEq , Eq , Debug)]
+pub struct Page<T>{
+    pub items:          Vec<T> ,    // 現在のページの内容
+    pub total_count:    u64 ,       // 全件数
+    pub total_pages:    u64 ,       // 全ページ数
+    pub page:           u64 ,       // 現在のページ番号
+    pub page_size:      u64         // 1ページあたりの件数
+}
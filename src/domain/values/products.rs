@@ -1,6 +1,9 @@
 use anyhow::Result;
+use crate::domain::values::categories::CategoryId;
+use crate::domain::values::rules::{LengthRule, RangeRule};
 use crate::domain::values::ValueInto;
 use crate::error::AppError;
+use crate::i18n::Message;
 
 ///
 /// 商品番号を表す値オブジェクト
@@ -11,7 +14,7 @@ impl TryFrom<i32> for ProductId {
     type Error = AppError;
     fn try_from(value: i32) -> Result<Self, Self::Error> {
         if value < 0 {
-            Err(AppError::from("不正な商品番号です。"))
+            Err(AppError::from(Message::new("value.invalid").with_label("label" , "label.product.id")))
         } else {
             Ok(Self(value))
         }
@@ -19,7 +22,7 @@ impl TryFrom<i32> for ProductId {
 }
 impl ValueInto<i32> for ProductId{
     fn value(&self) -> i32 {
-        self.0.clone()
+        self.0
     }
 }
 
@@ -28,16 +31,15 @@ impl ValueInto<i32> for ProductId{
 ///
 #[derive(Clone , PartialEq , Eq , Debug)]
 pub struct ProductName(String);
+impl ProductName {
+    /// 商品名の文字数
+    pub const LENGTH: LengthRule = LengthRule::new(4 , 20);
+}
 impl TryFrom<String> for ProductName{
     type Error = AppError;
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            Err(AppError::from("商品名がありません。"))
-        }else if value.chars().count() > 20 {
-            Err(AppError::from("商品名の長さは20文字以内です。"))
-        }else {
-            Ok(Self(value))
-        }
+        Self::LENGTH.check("label.product.name" , &value)?;
+        Ok(Self(value))
     }
 }
 impl ValueInto<String> for ProductName{
@@ -51,18 +53,138 @@ impl ValueInto<String> for ProductName{
 ///
 #[derive(Clone , Copy , PartialEq , Eq , Debug)]
 pub struct ProductPrice(i32);
+impl ProductPrice {
+    /// 単価の範囲
+    pub const RANGE: RangeRule = RangeRule::new(50 , 10000);
+}
 impl TryFrom<i32> for ProductPrice{
     type Error = AppError;
     fn try_from(value: i32) -> Result<Self, Self::Error> {
-        if value >= 50 && value <= 10000 {
-            Ok(Self(value))
-        }else{
-            Err(AppError::from("不正な単価です。"))
-        }
+        Self::RANGE.check("label.product.price" , value)?;
+        Ok(Self(value))
     }
 }
 impl ValueInto<i32> for ProductPrice{
     fn value(&self) -> i32 {
-        self.0.clone()
+        self.0
+    }
+}
+
+///
+/// 商品の並び順を表す値オブジェクト
+///
+#[derive(Clone , Copy , PartialEq , Eq , Debug , Default)]
+pub enum ProductSortKey {
+    #[default]
+    IdAsc ,
+    IdDesc ,
+    NameAsc ,
+    NameDesc ,
+    PriceAsc ,
+    PriceDesc
+}
+impl TryFrom<String> for ProductSortKey {
+    type Error = AppError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "id_asc" => Ok(Self::IdAsc) ,
+            "id_desc" => Ok(Self::IdDesc) ,
+            "name_asc" => Ok(Self::NameAsc) ,
+            "name_desc" => Ok(Self::NameDesc) ,
+            "price_asc" => Ok(Self::PriceAsc) ,
+            "price_desc" => Ok(Self::PriceDesc) ,
+            _ => Err(AppError::from(Message::new("value.invalid").with_label("label" , "label.search.sort")))
+        }
+    }
+}
+impl ValueInto<String> for ProductSortKey {
+    fn value(&self) -> String {
+        match self {
+            Self::IdAsc => "id_asc" ,
+            Self::IdDesc => "id_desc" ,
+            Self::NameAsc => "name_asc" ,
+            Self::NameDesc => "name_desc" ,
+            Self::PriceAsc => "price_asc" ,
+            Self::PriceDesc => "price_desc"
+        }.to_string()
+    }
+}
+
+///
+/// 複数キーワードの結合方法を表す値オブジェクト
+///
+#[derive(Clone , Copy , PartialEq , Eq , Debug , Default)]
+pub enum KeywordMatch {
+    #[default]
+    All ,   // すべてのキーワードを含む(AND)
+    Any     // いずれかのキーワードを含む(OR)
+}
+impl TryFrom<String> for KeywordMatch {
+    type Error = AppError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.to_ascii_lowercase().as_str() {
+            "and" => Ok(Self::All) ,
+            "or" => Ok(Self::Any) ,
+            _ => Err(AppError::from(Message::new("value.invalid").with_label("label" , "label.search.keyword_match")))
+        }
+    }
+}
+
+/// 検索キーワードの最大数
+pub const MAX_SEARCH_KEYWORDS: usize = 10;
+
+///
+/// 商品の検索条件を表す値オブジェクト
+/// 指定された条件はすべて満たす(AND)必要がある
+///
+#[derive(Clone , PartialEq , Eq , Debug , Default)]
+pub struct ProductSearchCriteria {
+    keywords:       Vec<String> ,       // 商品名に含むキーワード
+    keyword_match:  KeywordMatch ,      // キーワードの結合方法
+    min_price:      Option<i32> ,       // 単価の下限
+    max_price:      Option<i32> ,       // 単価の上限
+    category_ids:   Vec<CategoryId>     // いずれかに該当するカテゴリ
+}
+impl ProductSearchCriteria {
+    /// 値を生成する、ルール違反の場合はAppErrorを返す
+    pub fn new(keywords: Vec<String> , keyword_match: KeywordMatch ,
+               min_price: Option<i32> , max_price: Option<i32> ,
+               category_ids: Vec<CategoryId>) -> Result<Self , AppError> {
+        // 前後の空白を取り除き、空のキーワードは除外する
+        let keywords:Vec<String> = keywords.iter()
+            .map(|keyword| keyword.trim().to_string())
+            .filter(|keyword| !keyword.is_empty()).collect();
+        if keywords.len() > MAX_SEARCH_KEYWORDS {
+            return Err(AppError::from(Message::new("value.max_count")
+                .with_label("label" , "label.search.keyword").with_param("max" , MAX_SEARCH_KEYWORDS)));
+        }
+        if min_price.is_some_and(|price| price < 0) || max_price.is_some_and(|price| price < 0) {
+            return Err(AppError::from(Message::new("value.min").with_label("label" , "label.search.price_range").with_param("min" , 0)));
+        }
+        if let (Some(min) , Some(max)) = (min_price , max_price) {
+            if min > max {
+                return Err(AppError::from(Message::new("value.price_order")));
+            }
+        }
+        Ok(Self{ keywords , keyword_match , min_price , max_price , category_ids })
+    }
+    /// 1つのキーワードだけを条件にする
+    pub fn keyword(keyword: &ProductName) -> Self {
+        Self{ keywords: vec![keyword.value()] , ..Default::default() }
+    }
+    pub fn keywords(&self) -> &Vec<String> {
+        &self.keywords
+    }
+    pub fn keyword_match(&self) -> KeywordMatch {
+        self.keyword_match
+    }
+    pub fn min_price(&self) -> Option<i32> {
+        self.min_price
+    }
+    pub fn max_price(&self) -> Option<i32> {
+        self.max_price
+    }
+    pub fn category_ids(&self) -> &Vec<CategoryId> {
+        &self.category_ids
     }
 }
@@ -0,0 +1,122 @@
+use crate::{AppError, Result};
+use crate::i18n::Message;
+
+///
+/// 文字数の制約
+/// 値オブジェクトが定義し、入力値検証とテーブル定義(列の長さ)もこの値に従う
+///
+#[derive(Clone , Copy , PartialEq , Eq , Debug)]
+pub struct LengthRule {
+    pub min: usize ,    // 最小文字数
+    pub max: usize      // 最大文字数(列の長さ)
+}
+impl LengthRule {
+    pub const fn new(min: usize , max: usize) -> Self {
+        Self{ min , max }
+    }
+    /// 文字数が制約を満たすか判定する
+    pub fn contains(&self , value: &str) -> bool {
+        (self.min..=self.max).contains(&value.chars().count())
+    }
+    /// 制約を満たさない場合はAppErrorを返す、labelは項目名のメッセージキー
+    pub fn check(&self , label: &str , value: &str) -> Result<()> {
+        if value.is_empty() {
+            Err(AppError::from(Message::new("value.required").with_label("label" , label)))
+        }else if !self.contains(value) {
+            Err(AppError::from(self.message("value" , label)))
+        }else{
+            Ok(())
+        }
+    }
+    /// 制約違反のメッセージ、最小文字数が1の場合は最大文字数のみを示す
+    /// prefixはメッセージキーの分類(value:値オブジェクト,validation:入力値検証)
+    pub fn message(&self , prefix: &str , label: &str) -> Message {
+        if self.min <= 1 {
+            Message::new(&format!("{}.max_length" , prefix)).with_label("label" , label).with_param("max" , self.max)
+        }else{
+            Message::new(&format!("{}.length" , prefix)).with_label("label" , label)
+                .with_param("min" , self.min).with_param("max" , self.max)
+        }
+    }
+}
+
+///
+/// 数値の範囲の制約
+/// 値オブジェクトが定義し、入力値検証とテーブル定義(検査制約)もこの値に従う
+///
+#[derive(Clone , Copy , PartialEq , Eq , Debug)]
+pub struct RangeRule {
+    pub min: i32 ,  // 下限
+    pub max: i32    // 上限
+}
+impl RangeRule {
+    pub const fn new(min: i32 , max: i32) -> Self {
+        Self{ min , max }
+    }
+    /// 値が範囲内か判定する
+    pub fn contains(&self , value: i32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+    /// 範囲外の場合はAppErrorを返す、labelは項目名のメッセージキー
+    pub fn check(&self , label: &str , value: i32) -> Result<()> {
+        if self.contains(value) {
+            Ok(())
+        }else{
+            Err(AppError::from(self.message("value" , label)))
+        }
+    }
+    /// 制約違反のメッセージ
+    /// prefixはメッセージキーの分類(value:値オブジェクト,validation:入力値検証)
+    pub fn message(&self , prefix: &str , label: &str) -> Message {
+        Message::new(&format!("{}.range" , prefix)).with_label("label" , label)
+            .with_param("min" , self.min).with_param("max" , self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::values::categories::CategoryName;
+    use crate::domain::values::products::{ProductName, ProductPrice};
+    use crate::domain::values::users::{Mail, UserName};
+
+    // 値オブジェクトは制約の境界値で受け入れと拒否が切り替わる
+    #[test]
+    fn value_objects_follow_rules() {
+        fn text(length: usize) -> String {
+            "a".repeat(length)
+        }
+        type Accepts = fn(String) -> bool;
+        let rules: [(LengthRule , Accepts); 3] = [
+            (ProductName::LENGTH , |value| ProductName::try_from(value).is_ok()) ,
+            (CategoryName::LENGTH , |value| CategoryName::try_from(value).is_ok()) ,
+            (UserName::LENGTH , |value| UserName::try_from(value).is_ok())
+        ];
+        for (rule , accepts) in rules {
+            assert!(accepts(text(rule.min)) , "{:?}" , rule);
+            assert!(accepts(text(rule.max)) , "{:?}" , rule);
+            assert!(!accepts(text(rule.min - 1)) , "{:?}" , rule);
+            assert!(!accepts(text(rule.max + 1)) , "{:?}" , rule);
+        }
+        let rule = ProductPrice::RANGE;
+        assert!(ProductPrice::try_from(rule.min).is_ok());
+        assert!(ProductPrice::try_from(rule.max).is_ok());
+        assert!(ProductPrice::try_from(rule.min - 1).is_err());
+        assert!(ProductPrice::try_from(rule.max + 1).is_err());
+        // メールアドレスは形式を満たす値で上限を確認する
+        let mail = |length: usize| format!("{}@{}.{}.{}.com" , "a".repeat(length - 196) , "b".repeat(63) , "c".repeat(63) , "d".repeat(63));
+        assert!(Mail::try_from(mail(Mail::LENGTH.max)).is_ok());
+        assert!(Mail::try_from(mail(Mail::LENGTH.max + 1)).is_err());
+        // 文字数はバイト数ではなく文字の数で数える
+        assert!(ProductName::LENGTH.contains(&"あ".repeat(ProductName::LENGTH.max)));
+    }
+
+    #[test]
+    fn messages() {
+        let rule = LengthRule::new(4 , 20);
+        assert_eq!(rule.message("validation" , "label.product.name").to_string() , "商品名は4文字以上20文字以内で入力して下さい。");
+        assert_eq!(LengthRule::new(1 , 36).message("value" , "label.user.mail").to_string() , "メールアドレスの長さは36文字以内です。");
+        assert_eq!(rule.check("label.product.name" , "").err().unwrap().to_string() , "商品名がありません。");
+        assert_eq!(RangeRule::new(50 , 10000).check("label.product.price" , 10).err().unwrap().to_string() , "単価は50～10000です。");
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
+use crate::domain::values::rules::LengthRule;
 use crate::domain::values::ValueInto;
 use crate::error::AppError;
+use crate::i18n::Message;
 
 ///
 /// ユーザーIDを表す値オブジェクト
@@ -11,7 +13,7 @@ impl TryFrom<String>  for UserId{
     type Error = AppError;
     fn try_from(value: String) -> Result<Self, Self::Error> {
         if value.is_empty() {
-            Err(AppError::from("ユーザーIDがありません。"))
+            Err(AppError::from(Message::new("value.required").with_label("label" , "label.user.id")))
         }else {
             Ok(Self(value))
         }
@@ -27,16 +29,15 @@ impl ValueInto<String> for UserId{
 ///
 #[derive(Clone , PartialEq , Eq , Debug)]
 pub struct UserName(String);
+impl UserName {
+    /// ユーザー名の文字数
+    pub const LENGTH: LengthRule = LengthRule::new(6 , 20);
+}
 impl TryFrom<String> for UserName{
     type Error = AppError;
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            Err(AppError::from("ユーザー名がありません。"))
-        }else if value.chars().count() > 20 {
-            Err(AppError::from("ユーザー名の長さは20文字以内です。"))
-        } else {
-            Ok(Self(value))
-        }
+        Self::LENGTH.check("label.user.name" , &value)?;
+        Ok(Self(value))
     }
 }
 impl ValueInto<String> for UserName{
@@ -53,7 +54,7 @@ impl TryFrom<String> for Password {
     type Error = AppError;
     fn try_from(value: String) -> Result<Self, Self::Error> {
         if value.is_empty() {
-            Err(AppError::from("パスワードがありません。"))
+            Err(AppError::from(Message::new("value.required").with_label("label" , "label.user.password")))
         }else {
             Ok(Self(value))
         }
@@ -66,20 +67,60 @@ impl ValueInto<String> for Password{
 }
 
 ///
-/// パスワードを表す値オブジェクト
+/// メールアドレスを表す値オブジェクト
+/// ローカル部はRFC 5322のドットアトム形式、ドメインはRFC 1035のホスト名として検証する
+/// 国際化ドメイン名(IDN)はPunycodeに変換し、ドメインを小文字にした正規化済の値を保持する
+/// ローカル部は大文字小文字を区別するため変換しない、比較は正規化した値で行う
 ///
-#[derive(Clone , PartialEq , Eq , Debug)]
+#[derive(Clone , PartialEq , Eq , Hash , Debug)]
 pub struct Mail(String);
+impl Mail {
+    /// メールアドレスの文字数(RFC 5321のパスの上限)
+    pub const LENGTH: LengthRule = LengthRule::new(1 , 254);
+    /// ローカル部の最大文字数(RFC 5321)
+    pub const LOCAL_PART_MAX: usize = 64;
+    /// ドメインの最大文字数(RFC 1035)
+    pub const DOMAIN_MAX: usize = 253;
+
+    /// 形式を検証して正規化したメールアドレスを返す、形式が不正な場合はNone
+    pub fn normalize(value: &str) -> Option<String> {
+        let (local_part , domain) = value.trim().rsplit_once('@')?;
+        if local_part.len() > Self::LOCAL_PART_MAX || !is_dot_atom(local_part) {
+            return None;
+        }
+        // UTS #46の変換で大文字は小文字に、Unicodeの文字はPunycodeになる
+        let domain = idna::domain_to_ascii(domain).ok()?;
+        if domain.len() > Self::DOMAIN_MAX || !is_host_name(&domain) {
+            return None;
+        }
+        Some(format!("{}@{}" , local_part , domain))
+    }
+    /// ローカル部
+    pub fn local_part(&self) -> &str {
+        self.0.rsplit_once('@').map(|(local_part , _)| local_part).unwrap_or_default()
+    }
+    /// 正規化したドメイン(ASCII)
+    pub fn domain(&self) -> &str {
+        self.0.rsplit_once('@').map(|(_ , domain)| domain).unwrap_or_default()
+    }
+    /// ドメインをUnicodeで表したメールアドレス(画面表示用)
+    pub fn to_unicode(&self) -> String {
+        let (domain , _) = idna::domain_to_unicode(self.domain());
+        format!("{}@{}" , self.local_part() , domain)
+    }
+}
 impl TryFrom<String> for Mail {
     type Error = AppError;
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value.is_empty() {
-            Err(AppError::from("メールアドレスがありません。"))
-        }else if value.chars().count() > 36 {
-            Err(AppError::from("メールアドレスの長さは36文字以内です。"))
-        }else {
-            Ok(Self(value))
+        if value.trim().is_empty() {
+            return Err(AppError::from(Message::new("value.required").with_label("label" , "label.user.mail")));
         }
+        let normalized = match Self::normalize(&value) {
+            Some(normalized) => normalized ,
+            None => return Err(AppError::from(Message::new("value.invalid").with_label("label" , "label.user.mail")))
+        };
+        Self::LENGTH.check("label.user.mail" , &normalized)?;
+        Ok(Self(normalized))
     }
 }
 impl ValueInto<String> for Mail {
@@ -87,3 +128,89 @@ impl ValueInto<String> for Mail {
         self.0.clone()
     }
 }
+// ドットアトム形式(ドットで区切ったatext)か判定する、先頭と末尾のドットと連続するドットは認めない
+fn is_dot_atom(value: &str) -> bool {
+    !value.is_empty() && value.split('.').all(|atom| !atom.is_empty() &&
+        atom.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c)))
+}
+// 2つ以上のラベルからなるホスト名か判定する、トップレベルは数字のみを認めない
+fn is_host_name(value: &str) -> bool {
+    let labels: Vec<&str> = value.split('.').collect();
+    labels.len() >= 2 && labels.iter().all(|label| (1..=63).contains(&label.len()) &&
+        !label.starts_with('-') && !label.ends_with('-') &&
+        label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')) &&
+        !labels.last().is_some_and(|label| label.chars().all(|c| c.is_ascii_digit()))
+}
+
+///
+/// 認証情報を表す値オブジェクト
+///
+#[derive(Clone , PartialEq , Eq , Debug)]
+pub struct Credentials {
+    pub user_name:  UserName ,  // ユーザー名
+    pub password:   Password    // パスワード(平文)
+}
+impl Credentials {
+    pub fn new(user_name: UserName , password: Password) -> Self {
+        Self{ user_name , password }
+    }
+}
+///
+/// パスワードの変更を表す値オブジェクト
+///
+#[derive(Clone , PartialEq , Eq , Debug)]
+pub struct PasswordChange {
+    pub current:    Password ,  // 現在のパスワード(平文)
+    pub password:   Password    // 新しいパスワード(平文)
+}
+impl PasswordChange {
+    pub fn new(current: Password , password: Password) -> Self {
+        Self{ current , password }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mail_syntax() {
+        for valid in ["yamada@sample.com" , "first.last+tag@sub.example.co.jp" , "o'neil@example.org" ,
+                      "user_01@xn--r8jz45g.jp" , "a@b.cd"] {
+            assert!(Mail::try_from(valid.to_string()).is_ok() , "{}" , valid);
+        }
+        for invalid in ["dummy" , "@example.com" , "user@" , ".user@example.com" , "user.@example.com" ,
+                        "us..er@example.com" , "us er@example.com" , "user@localhost" , "user@-example.com" ,
+                        "user@example.com." , "user@exa_mple.com" , "user@example.123" , "a@b@example.com"] {
+            assert!(Mail::try_from(invalid.to_string()).is_err() , "{}" , invalid);
+        }
+        // ローカル部とドメイン、全体の長さの上限
+        let label = "a".repeat(63);
+        assert!(Mail::try_from(format!("{}@example.com" , "a".repeat(Mail::LOCAL_PART_MAX))).is_ok());
+        assert!(Mail::try_from(format!("{}@example.com" , "a".repeat(Mail::LOCAL_PART_MAX + 1))).is_err());
+        assert!(Mail::try_from(format!("user@{}.com" , "a".repeat(64))).is_err());
+        let domain = format!("{}.{}.{}.{}" , label , label , label , "a".repeat(61));
+        assert_eq!(domain.len() , Mail::DOMAIN_MAX);
+        assert!(Mail::try_from(format!("a@{}" , domain)).is_err());
+        let mail = |tld: usize| format!("{}@{}.{}.{}" , "a".repeat(Mail::LOCAL_PART_MAX) , label , label , "a".repeat(tld));
+        assert_eq!(mail(61).len() , Mail::LENGTH.max);
+        assert!(Mail::try_from(mail(61)).is_ok());
+        assert!(Mail::try_from(mail(62)).is_err());
+    }
+
+    #[test]
+    fn mail_normalize() -> anyhow::Result<()> {
+        // ドメインは小文字にし、ローカル部はそのまま保持する
+        let mail = Mail::try_from(String::from("  Yamada@Sample.COM "))?;
+        assert_eq!(mail.value() , "Yamada@sample.com");
+        assert_eq!(mail , Mail::try_from(String::from("Yamada@sample.com"))?);
+        assert_ne!(mail , Mail::try_from(String::from("yamada@sample.com"))?);
+        // 国際化ドメイン名はPunycodeで比較する
+        let mail = Mail::try_from(String::from("info@例え.JP"))?;
+        assert_eq!(mail.domain() , "xn--r8jz45g.jp");
+        assert_eq!(mail , Mail::try_from(String::from("info@xn--r8jz45g.jp"))?);
+        assert_eq!(mail.to_unicode() , "info@例え.jp");
+        assert_eq!(mail.local_part() , "info");
+        Ok(())
+    }
+}
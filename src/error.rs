@@ -1,30 +1,297 @@
+use std::fmt::{Display, Formatter};
 use sea_orm::DbErr;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use crate::i18n::{Locale, Message};
+use crate::infrastructure::converter::ConversionError;
+use crate::presentation::validate::{FieldError, ValidationError};
 ///
 /// アプリケーション全体で利用するエラー型
+/// Displayは内部の詳細を含むためログに使い、利用者にはmessage()またはproblem()を返す
+/// 利用者向けのメッセージはメッセージカタログでロケールの文字列にする
 ///
 #[derive(Debug , Error)]
 pub enum AppError{
     #[error("{0}")]
-    SearchError(String) ,  // 検索処理エラー
+    NotFound(Message) ,      // 該当データがない
     #[error("{0}")]
-    RegisterError(String) , // 登録処理エラー
+    Conflict(Message) ,      // 登録済のデータと重複する
+    #[error(transparent)]
+    Validation(#[from] ValidationError) , // 項目単位の検証エラー
+    #[error("{0}")]
+    Unauthorized(Message) ,  // 認証されていない、認証に失敗した
+    #[error("{0}")]
+    Forbidden(Message) ,     // 権限がない
     #[error("{0}")]
-    AuthenticateError(String) ,// 認証エラー
+    Rule(Message) ,          // 値オブジェクト、ドメインルールの違反
+    #[error("{0}")]
+    Unavailable(anyhow::Error) , // データベースの接続断など一時的に利用できない
+    #[error(transparent)]
+    Conversion(#[from] ConversionError) , // ORMモデルの変換エラー
     #[error(transparent)]
-    InternalError(#[from] anyhow::Error) // 永続化層のエラー , ドメインルールエラー
+    Internal(#[from] anyhow::Error) // 永続化層などの内部エラー
 }
-// SeaOrmのエラーをラップした内部エラーを生成する
+impl AppError {
+    /// 機械可読なエラーコード
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::NotFound(_) => ErrorCode::NotFound ,
+            AppError::Conflict(_) => ErrorCode::Conflict ,
+            AppError::Validation(_) | AppError::Rule(_) => ErrorCode::Validation ,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized ,
+            AppError::Forbidden(_) => ErrorCode::Forbidden ,
+            AppError::Unavailable(_) => ErrorCode::Unavailable ,
+            AppError::Conversion(_) | AppError::Internal(_) => ErrorCode::Internal
+        }
+    }
+    /// 値オブジェクトの生成エラーを指定された項目の検証エラーにする、それ以外のエラーはそのまま返す
+    pub fn into_validation(self , field: &str) -> AppError {
+        match self {
+            AppError::Rule(message) => {
+                let mut errors = ValidationError::new();
+                errors.add(field , FieldError::new("invalid" , message));
+                AppError::Validation(errors)
+            },
+            error => error
+        }
+    }
+    /// 登録済の値のルール違反は入力の誤りではないため内部エラーにする、それ以外のエラーはそのまま返す
+    pub fn into_internal(self) -> AppError {
+        match self {
+            AppError::Rule(message) => AppError::Internal(anyhow::Error::new(message)) ,
+            error => error
+        }
+    }
+    /// 推奨するHTTPステータスコード
+    pub fn status(&self) -> u16 {
+        self.code().status()
+    }
+    /// 利用者に返してよいメッセージ、内部エラーの詳細は含めない
+    pub fn message(&self) -> String {
+        self.message_in(Locale::default())
+    }
+    /// 指定されたロケールの利用者に返してよいメッセージ
+    pub fn message_in(&self , locale: Locale) -> String {
+        match self {
+            AppError::NotFound(message) | AppError::Conflict(message) |
+            AppError::Unauthorized(message) | AppError::Forbidden(message) |
+            AppError::Rule(message) => message.render(locale) ,
+            _ => self.code().title_in(locale)
+        }
+    }
+    /// RFC 7807のProblem Details
+    pub fn problem(&self) -> ProblemDetails {
+        self.problem_in(Locale::default())
+    }
+    /// 指定されたロケールのRFC 7807のProblem Details
+    pub fn problem_in(&self , locale: Locale) -> ProblemDetails {
+        let code = self.code();
+        ProblemDetails{
+            problem_type: code.type_uri() ,
+            title: code.title_in(locale) ,
+            status: code.status() ,
+            detail: Some(self.message_in(locale)) ,
+            instance: None ,
+            code ,
+            errors: match self {
+                AppError::Validation(error) => Some(error.localize(locale)) ,
+                _ => None
+            }
+        }
+    }
+}
+// SeaOrmのエラーを分類する、接続できない場合は一時的なエラーとする
 impl From<DbErr> for AppError{
     fn from(err: DbErr) -> Self {
-        AppError::InternalError(anyhow::Error::new(err))
+        match &err {
+            DbErr::Conn(_) => AppError::Unavailable(anyhow::Error::new(err)) ,
+            DbErr::Exec(message) | DbErr::Query(message) if is_timeout(message) =>
+                AppError::Unavailable(anyhow::Error::new(err)) ,
+            DbErr::Exec(message) | DbErr::Query(message) if is_unique_violation(message) => {
+                log::warn!("{}" , err);
+                AppError::Conflict(Message::new("error.conflict"))
+            },
+            _ => AppError::Internal(anyhow::Error::new(err))
+        }
+    }
+}
+// 値オブジェクト、ドメインルールの違反のメッセージを入力値の誤りにする
+impl From<Message> for AppError{
+    fn from(message: Message) -> Self {
+        AppError::Rule(message)
     }
 }
 // メッセージをラップした内部エラーを生成する
 impl From<&str> for AppError{
     fn from(msg: &str) -> Self {
-        AppError::InternalError(anyhow::Error::msg(msg.to_string()))
+        AppError::Internal(anyhow::Error::msg(msg.to_string()))
+    }
+}
+// コネクションの取得待ちがタイムアウトしたか判定する
+fn is_timeout(message: &str) -> bool {
+    message.contains("PoolTimedOut") || message.contains("pool timed out")
+}
+// 一意制約違反か判定する(PostgreSQL,SQLite)
+fn is_unique_violation(message: &str) -> bool {
+    message.contains("duplicate key value") || message.contains("UNIQUE constraint failed")
+}
+
+///
+/// 機械可読なエラーコード
+///
+#[derive(Debug , Clone , Copy , PartialEq , Eq , Hash , Serialize , Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    NotFound ,
+    Conflict ,
+    Validation ,
+    Unauthorized ,
+    Forbidden ,
+    Unavailable ,
+    Internal
+}
+impl ErrorCode {
+    /// エラーコードの文字列
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "NOT_FOUND" ,
+            ErrorCode::Conflict => "CONFLICT" ,
+            ErrorCode::Validation => "VALIDATION" ,
+            ErrorCode::Unauthorized => "UNAUTHORIZED" ,
+            ErrorCode::Forbidden => "FORBIDDEN" ,
+            ErrorCode::Unavailable => "UNAVAILABLE" ,
+            ErrorCode::Internal => "INTERNAL"
+        }
+    }
+    /// 推奨するHTTPステータスコード
+    pub fn status(&self) -> u16 {
+        match self {
+            ErrorCode::NotFound => 404 ,
+            ErrorCode::Conflict => 409 ,
+            ErrorCode::Validation => 400 ,
+            ErrorCode::Unauthorized => 401 ,
+            ErrorCode::Forbidden => 403 ,
+            ErrorCode::Unavailable => 503 ,
+            ErrorCode::Internal => 500
+        }
+    }
+    /// 種類ごとの概要
+    pub fn title(&self) -> String {
+        self.title_in(Locale::default())
+    }
+    /// 指定されたロケールの種類ごとの概要
+    pub fn title_in(&self , locale: Locale) -> String {
+        Message::new(&format!("error.{}" , self.as_str().to_lowercase())).render(locale)
+    }
+    /// 種類を識別するURI(Problem Detailsのtype)
+    pub fn type_uri(&self) -> String {
+        format!("urn:app-commons:error:{}" , self.as_str().to_lowercase().replace('_' , "-"))
+    }
+}
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f , "{}" , self.as_str())
     }
 }
 
+///
+/// RFC 7807のProblem Details
+/// 拡張メンバーとしてエラーコードと項目ごとのエラーを持つ
+///
+#[derive(Debug , Clone , PartialEq , Serialize , Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String ,          // 種類を識別するURI
+    pub title:    String ,              // 種類ごとの概要
+    pub status:   u16 ,                 // HTTPステータスコード
+    #[serde(default , skip_serializing_if = "Option::is_none")]
+    pub detail:   Option<String> ,      // 発生したエラーの説明
+    #[serde(default , skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String> ,      // 発生したリソースのURI
+    pub code:     ErrorCode ,           // エラーコード
+    #[serde(default , skip_serializing_if = "Option::is_none")]
+    pub errors:   Option<ValidationError> // 項目ごとのエラー
+}
+impl ProblemDetails {
+    /// Problem Detailsのメディアタイプ
+    pub const CONTENT_TYPE: &'static str = "application/problem+json";
+    /// 発生したリソースのURIを設定する
+    pub fn with_instance(mut self , instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::presentation::validate::FieldError;
+    use anyhow::anyhow;
+
+    #[test]
+    fn codes_and_messages() {
+        let error = AppError::NotFound(Message::new("product.not_found").with_param("id" , 1));
+        assert_eq!(error.code() , ErrorCode::NotFound);
+        assert_eq!(error.status() , 404);
+        assert_eq!(error.message() , "商品番号1に該当データがありません。");
+        assert_eq!(error.message_in(Locale::En) , "No product found with ID 1.");
+        // 内部エラーの詳細は利用者向けのメッセージに含めない
+        let error = AppError::from(anyhow!("接続文字列:postgres://secret"));
+        assert_eq!(error.status() , 500);
+        assert!(error.to_string().contains("secret"));
+        assert!(!error.message().contains("secret"));
+        // データベースの接続断は一時的なエラー
+        let error = AppError::from(DbErr::Conn(String::from("connection refused")));
+        assert_eq!(error.code() , ErrorCode::Unavailable);
+        assert_eq!(error.status() , 503);
+        let error = AppError::from(DbErr::Exec(String::from(
+            "error returned from database: duplicate key value violates unique constraint \"user_mail_key\"")));
+        assert_eq!(error.code() , ErrorCode::Conflict);
+        assert_eq!(AppError::from(DbErr::Query(String::from("syntax error"))).code() , ErrorCode::Internal);
+    }
+
+    #[test]
+    fn into_validation() {
+        // 値オブジェクトの生成エラーは項目の検証エラーにする
+        let error = AppError::from(Message::new("value.invalid").with_label("label" , "label.product.id")).into_validation("id");
+        assert_eq!(error.status() , 400);
+        match error {
+            AppError::Validation(errors) => assert_eq!(errors.messages("id").len() , 1) ,
+            error => panic!("{:?}" , error)
+        }
+        // ルール違反は入力値の誤り、登録済の値のルール違反は内部エラー
+        let error = AppError::from(Message::new("value.price_order"));
+        assert_eq!(error.status() , 400);
+        assert_eq!(error.message() , "単価の下限が上限を超えています。");
+        assert_eq!(error.into_internal().status() , 500);
+        // それ以外のエラーは変えない
+        assert_eq!(AppError::from(anyhow!("接続できません。")).into_validation("id").status() , 500);
+        assert_eq!(AppError::NotFound(Message::new("product.not_found")).into_validation("id").status() , 404);
+    }
 
+    #[test]
+    fn problem_details() -> anyhow::Result<()> {
+        let mut errors = HashMap::new();
+        errors.insert(String::from("name") , String::from("商品名を入力して下さい。"));
+        let problem = AppError::from(ValidationError::from(errors)).problem().with_instance("/products");
+        let json = serde_json::to_value(&problem)?;
+        assert_eq!(json["type"] , "urn:app-commons:error:validation");
+        assert_eq!(json["status"] , 400);
+        assert_eq!(json["code"] , "VALIDATION");
+        assert_eq!(json["instance"] , "/products");
+        assert_eq!(json["errors"]["name"][0]["message"] , "商品名を入力して下さい。");
+        let json = serde_json::to_value(AppError::Forbidden(Message::from("管理者のみ利用できます。")).problem())?;
+        assert_eq!(json["code"] , "FORBIDDEN");
+        assert_eq!(json["detail"] , "管理者のみ利用できます。");
+        assert!(json.get("errors").is_none());
+        // 概要と項目ごとのエラーもロケールに合わせる
+        let mut errors = ValidationError::new();
+        errors.add("name" , FieldError::new("required" ,
+            Message::new("validation.required").with_label("label" , "label.product.name")));
+        let problem = AppError::from(errors).problem_in(Locale::En);
+        assert_eq!(problem.title , "The input is invalid.");
+        assert_eq!(problem.errors.unwrap().messages("name") , vec!["Product name is required."]);
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+//! メッセージの多言語化
+//! メッセージはキーと引数で表し、表示するときにロケールのメッセージカタログで文字列にする
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 日本語のメッセージカタログ
+const JA_BUNDLE: &str = include_str!("ja.toml");
+/// 英語のメッセージカタログ
+const EN_BUNDLE: &str = include_str!("en.toml");
+
+///
+/// メッセージのロケール
+///
+#[derive(Clone , Copy , PartialEq , Eq , Hash , Debug , Default , Serialize , Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Ja ,
+    En
+}
+impl Locale {
+    /// 言語タグ
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::Ja => "ja" ,
+            Locale::En => "en"
+        }
+    }
+    /// 言語タグ(en-USなど)から対応するロケールを返す
+    pub fn parse(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-' , '_']).next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "ja" => Some(Locale::Ja) ,
+            "en" => Some(Locale::En) ,
+            _ => None
+        }
+    }
+    /// Accept-Languageヘッダーの値から品質値(q)の最も高いロケールを選ぶ
+    /// 対応するロケールがなければ既定(日本語)を返す
+    pub fn negotiate(accept_language: &str) -> Self {
+        let mut selected: Option<(Locale , f32)> = None;
+        for range in accept_language.split(',') {
+            let mut parts = range.split(';');
+            let tag = parts.next().unwrap_or_default();
+            let quality = parts.find_map(|part| part.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok()).unwrap_or(1.0);
+            if let Some(locale) = Locale::parse(tag) {
+                // 同じ品質値の場合は先に指定されたロケールを優先する
+                if quality > 0.0 && selected.map_or(true , |(_ , current)| quality > current) {
+                    selected = Some((locale , quality));
+                }
+            }
+        }
+        selected.map(|(locale , _)| locale).unwrap_or_default()
+    }
+}
+impl Display for Locale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f , "{}" , self.as_str())
+    }
+}
+
+///
+/// キーと引数で表したメッセージ
+/// カタログにないキーはそのまま表示するため、固定の文字列もメッセージにできる
+///
+#[derive(Clone , PartialEq , Debug , Serialize , Deserialize)]
+pub struct Message {
+    pub key:    String ,                    // メッセージのキー
+    #[serde(default , skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String , Value> ,  // メッセージの引数
+    #[serde(default , skip_serializing_if = "BTreeSet::is_empty")]
+    pub labels: BTreeSet<String>            // 表示するときに翻訳する引数の名前
+}
+impl Message {
+    pub fn new(key: &str) -> Self {
+        Self{ key: key.to_string() , params: BTreeMap::new() , labels: BTreeSet::new() }
+    }
+    /// 引数を追加する、値は翻訳せずにそのまま表示する
+    pub fn with_param<T: Serialize>(mut self , name: &str , value: T) -> Self {
+        self.params.insert(name.to_string() , serde_json::to_value(value).unwrap_or(Value::Null));
+        self.labels.remove(name);
+        self
+    }
+    /// カタログのキーを引数に追加する、表示するときにロケールの文字列に翻訳する
+    pub fn with_label(mut self , name: &str , key: &str) -> Self {
+        self.params.insert(name.to_string() , Value::String(key.to_string()));
+        self.labels.insert(name.to_string());
+        self
+    }
+    /// 指定されたロケールの文字列にする
+    pub fn render(&self , locale: Locale) -> String {
+        MessageCatalog::global().render(locale , self)
+    }
+}
+// 既定のロケール(日本語)で表示する
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f , "{}" , self.render(Locale::default()))
+    }
+}
+impl std::error::Error for Message {}
+impl From<&str> for Message {
+    fn from(key: &str) -> Self {
+        Self::new(key)
+    }
+}
+impl From<String> for Message {
+    fn from(key: String) -> Self {
+        Self{ key , params: BTreeMap::new() , labels: BTreeSet::new() }
+    }
+}
+
+///
+/// ロケールごとのメッセージカタログ
+/// キーは"product.not_found"のようにTOMLのテーブル名と項目名をドットで結合する
+///
+#[derive(Debug , Default)]
+pub struct MessageCatalog {
+    bundles: HashMap<Locale , HashMap<String , String>>
+}
+impl MessageCatalog {
+    /// 組み込みのカタログ(ja.toml,en.toml)
+    pub fn global() -> &'static MessageCatalog {
+        static CATALOG: OnceLock<MessageCatalog> = OnceLock::new();
+        CATALOG.get_or_init(|| {
+            let mut catalog = MessageCatalog::default();
+            for (locale , bundle) in [(Locale::Ja , JA_BUNDLE) , (Locale::En , EN_BUNDLE)] {
+                if let Err(error) = catalog.load(locale , bundle) {
+                    log::error!("メッセージカタログ({})を読込めません。{}" , locale , error);
+                }
+            }
+            catalog
+        })
+    }
+    /// TOML形式のメッセージを読込む
+    pub fn load(&mut self , locale: Locale , bundle: &str) -> Result<() , toml::de::Error> {
+        let value: toml::Value = toml::from_str(bundle)?;
+        let messages = self.bundles.entry(locale).or_default();
+        flatten(None , &value , messages);
+        Ok(())
+    }
+    /// キーに対応するメッセージ、ロケールになければ既定のロケールから探す
+    pub fn text(&self , locale: Locale , key: &str) -> Option<&str> {
+        [locale , Locale::default()].iter()
+            .find_map(|locale| self.bundles.get(locale).and_then(|messages| messages.get(key)))
+            .map(String::as_str)
+    }
+    /// ロケールのキー一覧
+    pub fn keys(&self , locale: Locale) -> Vec<&str> {
+        match self.bundles.get(&locale) {
+            Some(messages) => messages.keys().map(String::as_str).collect() ,
+            None => Vec::new()
+        }
+    }
+    /// メッセージの{引数名}を引数の値で置き換える
+    /// 置き換えた値に含まれる{引数名}は置き換えないよう、メッセージを先頭から1回だけ走査する
+    pub fn render(&self , locale: Locale , message: &Message) -> String {
+        let mut rest = self.text(locale , &message.key).unwrap_or(&message.key);
+        let mut text = String::with_capacity(rest.len());
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            match after.find('}').map(|end| (&after[..end] , end)) {
+                Some((name , end)) if message.params.contains_key(name) => {
+                    text.push_str(&self.param(locale , message , name));
+                    rest = &after[end + 1..];
+                },
+                _ => {
+                    text.push('{');
+                    rest = after;
+                }
+            }
+        }
+        text.push_str(rest);
+        text
+    }
+    // 引数の表示する値、with_labelで追加した引数だけを翻訳する
+    fn param(&self , locale: Locale , message: &Message , name: &str) -> String {
+        match &message.params[name] {
+            Value::String(value) if message.labels.contains(name) => self.text(locale , value).unwrap_or(value).to_string() ,
+            Value::String(value) => value.clone() ,
+            Value::Null => String::new() ,
+            value => value.to_string()
+        }
+    }
+}
+// 入れ子のテーブルをドット区切りのキーに展開する
+fn flatten(prefix: Option<&str> , value: &toml::Value , messages: &mut HashMap<String , String>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (name , value) in table {
+                let key = match prefix {
+                    Some(prefix) => format!("{}.{}" , prefix , name) ,
+                    None => name.clone()
+                };
+                flatten(Some(&key) , value , messages);
+            }
+        },
+        toml::Value::String(text) => {
+            messages.insert(prefix.unwrap_or_default().to_string() , text.clone());
+        },
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiate() {
+        assert_eq!(Locale::negotiate("en-US,en;q=0.9,ja;q=0.8") , Locale::En);
+        assert_eq!(Locale::negotiate("fr-FR, ja;q=0.5, en;q=0.7") , Locale::En);
+        assert_eq!(Locale::negotiate("ja,en;q=0.9") , Locale::Ja);
+        assert_eq!(Locale::negotiate("en;q=0, fr") , Locale::Ja);
+        assert_eq!(Locale::negotiate("") , Locale::Ja);
+    }
+
+    #[test]
+    fn render() {
+        let message = Message::new("value.length").with_label("label" , "label.product.name")
+            .with_param("min" , 4).with_param("max" , 20);
+        assert_eq!(message.to_string() , "商品名の長さは4文字以上20文字以内です。");
+        assert_eq!(message.render(Locale::En) , "Product name must be 4 to 20 characters long.");
+        // カタログにないキーと引数はそのまま表示する
+        assert_eq!(Message::from("任意のメッセージ").render(Locale::En) , "任意のメッセージ");
+        assert_eq!(Message::new("value.invalid").with_label("label" , "code").render(Locale::En) , "code is invalid.");
+    }
+
+    // 利用者が入力した値は翻訳せず、値に含まれる{引数名}も置き換えない
+    #[test]
+    fn render_user_values() {
+        let message = Message::new("product.duplicate").with_param("name" , "error.internal");
+        assert_eq!(message.render(Locale::Ja) , "error.internalは登録済です。");
+        let message = Message::new("value.length").with_label("label" , "label.product.name")
+            .with_param("min" , "{max}").with_param("max" , 20);
+        assert_eq!(message.render(Locale::En) , "Product name must be {max} to 20 characters long.");
+        // 引数のない{}はそのまま表示する
+        assert_eq!(Message::from("{x} {").with_param("y" , 1).render(Locale::En) , "{x} {");
+    }
+
+    // すべてのロケールが同じキーと引数を持つ
+    #[test]
+    fn bundles_are_complete() {
+        let catalog = MessageCatalog::global();
+        let mut ja = catalog.keys(Locale::Ja);
+        let mut en = catalog.keys(Locale::En);
+        ja.sort();
+        en.sort();
+        assert!(!ja.is_empty());
+        assert_eq!(ja , en);
+        let params = |text: &str| {
+            let mut names: Vec<String> = text.split('{').skip(1)
+                .filter_map(|part| part.split_once('}').map(|(name , _)| name.to_string())).collect();
+            names.sort();
+            names
+        };
+        for key in ja {
+            assert_eq!(params(catalog.text(Locale::Ja , key).unwrap()) ,
+                       params(catalog.text(Locale::En , key).unwrap()) , "{}" , key);
+        }
+    }
+}
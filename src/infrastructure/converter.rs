@@ -1,4 +1,61 @@
-use crate::Result;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+use crate::{AppError, Result};
+
+///
+/// ORMモデルからEntityへの変換エラー
+/// 変換できなかったテーブル、行の識別子と列を保持する
+///
+#[derive(Debug , Clone , PartialEq , Eq , Error)]
+#[error("{table}(id={id})の{column}を変換できません: {cause}")]
+pub struct ConversionError {
+    pub table:  String ,  // テーブル名
+    pub id:     String ,  // 行の識別子
+    pub column: String ,  // 列名
+    pub cause:  ConversionCause // 変換できない理由
+}
+impl ConversionError {
+    /// 列の値がNULLであることを表すエラーを生成する
+    pub fn null(table: &str , id: impl ToString , column: &str) -> Self {
+        Self::new(table , id , column , ConversionCause::Null)
+    }
+    /// 列が参照する行が存在しないことを表すエラーを生成する
+    pub fn missing_reference(table: &str , id: impl ToString , column: &str) -> Self {
+        Self::new(table , id , column , ConversionCause::MissingReference)
+    }
+    fn new(table: &str , id: impl ToString , column: &str , cause: ConversionCause) -> Self {
+        Self{ table: table.to_string() , id: id.to_string() , column: column.to_string() , cause }
+    }
+}
+///
+/// 変換できない理由
+///
+#[derive(Debug , Clone , Copy , PartialEq , Eq)]
+pub enum ConversionCause {
+    Null ,             // 値がNULL
+    MissingReference   // 参照先の行が存在しない
+}
+impl Display for ConversionCause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionCause::Null => write!(f , "値がNULLです。") ,
+            ConversionCause::MissingReference => write!(f , "参照先が存在しません。")
+        }
+    }
+}
+///
+/// NULLを許容する列の値を取り出す、NULLの場合はConversionErrorを返す
+///
+pub fn required<T>(value: Option<T> , table: &str , id: impl ToString , column: &str)
+    -> std::result::Result<T , ConversionError> {
+    value.ok_or_else(|| ConversionError::null(table , id , column))
+}
+///
+/// 登録済の値から値オブジェクトを生成する、ルール違反は入力値の誤りではないため内部エラーにする
+///
+pub fn stored<T>(build: impl FnOnce() -> Result<T>) -> Result<T> {
+    build().map_err(AppError::into_internal)
+}
 
 ///
 /// ORMのActiveModel生成トレイト
@@ -0,0 +1,52 @@
+//! サンプルデータ(migration::seed::Seederで登録する)
+
+/// 商品カテゴリ (カテゴリ番号 , カテゴリ名)
+pub const CATEGORIES: [(i32 , &str); 3] = [
+    (1 , "文房具") ,
+    (2 , "雑貨") ,
+    (3 , "パソコン周辺機器")
+];
+/// 商品 (商品番号 , 商品名 , 単価 , カテゴリ番号)
+pub const PRODUCTS: [(i32 , &str , i32 , i32); 28] = [
+    (1 , "水性ボールペン(黒)" , 120 , 1) ,
+    (2 , "水性ボールペン(赤)" , 120 , 1) ,
+    (3 , "水性ボールペン(青)" , 120 , 1) ,
+    (4 , "油性ボールペン(黒)" , 100 , 1) ,
+    (5 , "油性ボールペン(赤)" , 100 , 1) ,
+    (6 , "油性ボールペン(青)" , 100 , 1) ,
+    (7 , "蛍光ペン(黄)" , 130 , 1) ,
+    (8 , "蛍光ペン(赤)" , 130 , 1) ,
+    (9 , "蛍光ペン(青)" , 130 , 1) ,
+    (10 , "蛍光ペン(緑)" , 130 , 1) ,
+    (11 , "鉛筆(黒)" , 100 , 1) ,
+    (12 , "鉛筆(赤)" , 100 , 1) ,
+    (13 , "色鉛筆(12色)" , 400 , 1) ,
+    (14 , "色鉛筆(48色)" , 1300 , 1) ,
+    (15 , "レザーネックレス" , 300 , 2) ,
+    (16 , "ワンタッチ開閉傘" , 3000 , 2) ,
+    (17 , "金魚風呂敷" , 500 , 2) ,
+    (18 , "折畳トートバッグ" , 600 , 2) ,
+    (19 , "アイマスク" , 900 , 2) ,
+    (20 , "防水スプレー" , 500 , 2) ,
+    (21 , "キーホルダ" , 800 , 2) ,
+    (22 , "ワイヤレスマウス" , 900 , 3) ,
+    (23 , "ワイヤレストラックボール" , 1300 , 3) ,
+    (24 , "有線光学式マウス" , 500 , 3) ,
+    (25 , "光学式ゲーミングマウス" , 4800 , 3) ,
+    (26 , "有線ゲーミングマウス" , 3800 , 3) ,
+    (27 , "USB有線式キーボード" , 1400 , 3) ,
+    (28 , "無線式キーボード" , 1900 , 3)
+];
+/// ユーザー (ユーザーID , ユーザー名 , パスワード(SHA3-512) , メールアドレス)
+pub const USERS: [(&str , &str , &str , &str); 2] = [
+    // password = pass001
+    ("5772a800-fef1-40bf-888b-68fddd29d881" ,
+     "user001" ,
+     "a034408b78dfee92cdbfc6e5247cf0ece119f30e6ba7653f4b7a6f2f384f92a3c7cd4a0ec914ae3fb1ea93684b46f8ff2644ec0198d67be2fd2cbf68587f07b8" ,
+     "yamada@sample.com") ,
+    // password = pass002
+    ("5ca87702-a40a-4f08-85c3-534e92e36c0e" ,
+     "user002" ,
+     "51ca7a5622b4a5bcebc96c523dd464da5a62af27fa8ac0ba2d9d2a3efa46426424408865a980d5c71e770936b17b3502fa68993286ac958eff5bee0d7ec3ac3b" ,
+     "suzuki@sample.com")
+];
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordVerifier, Version};
+use argon2::password_hash::{PasswordHasher as _, SaltString};
+use argon2::password_hash::rand_core::OsRng;
+use easy_hasher::easy_hasher::sha3_512;
+use crate::{AppError, Result};
+use crate::domain::hasher::PasswordHasher;
+use crate::domain::values::users::Password;
+use crate::domain::values::ValueInto;
+
+///
+/// Argon2idによるパスワードハッシュの実装
+/// ハッシュはPHC形式の文字列で保存し、旧方式のSHA3-512(16進数)の照合にも対応する
+///
+pub struct Argon2PasswordHasher{
+    params: Params
+}
+impl Argon2PasswordHasher {
+    // インスタンスをPasswordHasher型に変換して返す
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn PasswordHasher> {
+        Arc::new(Self{ params: Params::default() })
+    }
+    // 現在のパラメータでArgon2idを生成する
+    fn argon2(&self) -> Argon2<'_> {
+        Argon2::new(Algorithm::Argon2id , Version::V0x13 , self.params.clone())
+    }
+    // 旧方式(SHA3-512の16進数文字列)のハッシュか判定する
+    fn is_legacy(hashed: &str) -> bool {
+        hashed.len() == 128 && hashed.chars().all(|c| c.is_ascii_hexdigit())
+    }
+    // 処理時間が一致箇所に依存しない文字列比較
+    fn constant_time_eq(a: &[u8] , b: &[u8]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).fold(0u8 , |acc , (x , y)| acc | (x ^ y)) == 0
+    }
+}
+impl PasswordHasher for Argon2PasswordHasher {
+    // ランダムなソルトを生成してArgon2idでハッシュ変換する
+    fn hash(&self, password: &Password) -> Result<Password> {
+        let salt = SaltString::generate(&mut OsRng);
+        match self.argon2().hash_password(password.value().as_bytes() , &salt) {
+            Ok(hash) => Password::try_from(hash.to_string()) ,
+            Err(error) => Err(AppError::Internal(anyhow::Error::msg(error.to_string())))
+        }
+    }
+    // 保存済ハッシュの形式に応じて照合する
+    fn verify(&self, password: &Password, hashed: &Password) -> Result<bool> {
+        let hashed = hashed.value();
+        if Self::is_legacy(&hashed) {
+            let legacy = sha3_512(&password.value()).to_hex_string();
+            return Ok(Self::constant_time_eq(legacy.as_bytes() , hashed.to_ascii_lowercase().as_bytes()));
+        }
+        match PasswordHash::new(&hashed) {
+            // PHC文字列に含まれるパラメータで照合する
+            Ok(parsed) => Ok(Argon2::default().verify_password(password.value().as_bytes() , &parsed).is_ok()) ,
+            Err(error) => Err(AppError::Internal(anyhow::Error::msg(error.to_string())))
+        }
+    }
+    // 旧方式、Argon2id以外、またはパラメータが異なる場合は再変換が必要
+    fn needs_rehash(&self, hashed: &Password) -> bool {
+        let hashed = hashed.value();
+        match PasswordHash::new(&hashed) {
+            Ok(parsed) => {
+                parsed.algorithm != Algorithm::Argon2id.ident() ||
+                    parsed.version != Some(Version::V0x13.into()) ||
+                    Params::try_from(&parsed).map(|params|
+                        params.m_cost() != self.params.m_cost() ||
+                        params.t_cost() != self.params.t_cost() ||
+                        params.p_cost() != self.params.p_cost()).unwrap_or(true)
+            },
+            Err(_) => true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn hash_and_verify() -> Result<()> {
+        let hasher = Argon2PasswordHasher::new();
+        let password = Password::try_from(String::from("pass001"))?;
+        let hashed1 = hasher.hash(&password)?;
+        let hashed2 = hasher.hash(&password)?;
+        // 同じパスワードでもソルトによって異なるハッシュになる
+        assert_ne!(hashed1 , hashed2);
+        assert!(hashed1.value().starts_with("$argon2id$"));
+        assert!(hasher.verify(&password , &hashed1)?);
+        assert!(hasher.verify(&password , &hashed2)?);
+        assert!(!hasher.verify(&Password::try_from(String::from("pass002"))? , &hashed1)?);
+        assert!(!hasher.needs_rehash(&hashed1));
+        Ok(())
+    }
+
+    #[test]
+    fn verify_legacy() -> Result<()> {
+        let hasher = Argon2PasswordHasher::new();
+        // サンプルデータのuser001のパスワード(pass001)
+        let legacy = Password::try_from(String::from("a034408b78dfee92cdbfc6e5247cf0ece119f30e6ba7653f4b7a6f2f384f92a3c7cd4a0ec914ae3fb1ea93684b46f8ff2644ec0198d67be2fd2cbf68587f07b8"))?;
+        assert!(hasher.verify(&Password::try_from(String::from("pass001"))? , &legacy)?);
+        assert!(!hasher.verify(&Password::try_from(String::from("pass002"))? , &legacy)?);
+        assert!(hasher.needs_rehash(&legacy));
+        Ok(())
+    }
+}
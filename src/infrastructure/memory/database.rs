@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use async_trait::async_trait;
+use crate::{AppError, Result};
+use crate::domain::entities::{AuthEvent, Category, Characteristic, LoginAttempt, Product, RefreshToken, User};
+use crate::domain::values::categories::{CategoryId, CategoryName};
+use crate::domain::values::products::{ProductId, ProductName, ProductPrice};
+use crate::domain::values::users::{Mail, Password, UserId, UserName};
+use crate::domain::values::ValueInto;
+use crate::infrastructure::fixtures::{CATEGORIES, PRODUCTS, USERS};
+use crate::infrastructure::transaction::{Transaction, TransactionManager};
+
+///
+/// メモリ上に保持するテーブル
+///
+#[derive(Clone , Debug , Default)]
+pub struct MemoryStore {
+    pub categories: BTreeMap<i32 , Category> ,  // カテゴリ番号をキーにしたカテゴリ
+    pub products:   BTreeMap<i32 , Product> ,   // 商品番号をキーにした商品
+    pub users:      BTreeMap<String , User> ,   // ユーザーIDをキーにしたユーザー
+    pub refresh_tokens: BTreeMap<String , RefreshToken> , // ハッシュ値をキーにしたリフレッシュトークン
+    pub revoked_tokens: BTreeMap<String , i64> ,  // jtiをキーにした失効したトークンの有効期限
+    pub login_attempts: BTreeMap<String , LoginAttempt> , // キーごとの認証の失敗回数
+    pub auth_events:    Vec<AuthEvent>              // 発生順の認証イベント
+}
+impl MemoryStore {
+    /// サンプルデータを格納した値を生成する
+    pub fn with_fixtures() -> Result<Self> {
+        let mut store = Self::default();
+        for (id , name) in CATEGORIES {
+            store.categories.insert(id , Category::new(
+                CategoryId::try_from(id)? , CategoryName::try_from(name.to_string())?));
+        }
+        for (id , name , price , category_id) in PRODUCTS {
+            let category = store.categories.get(&category_id).cloned();
+            store.products.insert(id , Product::new(
+                ProductId::try_from(id)? , ProductName::try_from(name.to_string())? ,
+                ProductPrice::try_from(price)? , category));
+        }
+        for (user_id , user_name , password , mail) in USERS {
+            let user = User::rebuilding(
+                UserId::try_from(user_id.to_string())? , UserName::try_from(user_name.to_string())? ,
+                Password::try_from(password.to_string())? , Mail::try_from(mail.to_string())?);
+            store.users.insert(user.get().value() , user);
+        }
+        Ok(store)
+    }
+}
+
+///
+/// メモリ上のデータベース
+/// 複製しても同じテーブルを共有する
+///
+#[derive(Clone , Default)]
+pub struct MemoryDatabase {
+    store: Arc<Mutex<MemoryStore>>
+}
+impl MemoryDatabase {
+    /// 空のデータベースを生成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// サンプルデータを格納したデータベースを生成する
+    pub fn with_fixtures() -> Result<Self> {
+        Ok(Self{ store: Arc::new(Mutex::new(MemoryStore::with_fixtures()?)) })
+    }
+    /// コミット済のテーブルの複製を返す
+    pub fn snapshot(&self) -> Result<MemoryStore> {
+        Ok(lock(&self.store)?.clone())
+    }
+}
+// トランザクションを開始する
+#[async_trait]
+impl TransactionManager for MemoryDatabase {
+    type Transaction = MemoryTransaction;
+    async fn begin(&self) -> Result<Self::Transaction> {
+        let store = lock(&self.store)?.clone();
+        Ok(MemoryTransaction{ database: self.store.clone() , store: Mutex::new(store) })
+    }
+}
+
+///
+/// メモリ上のデータベースのトランザクション
+/// 開始時点のテーブルの複製を更新し、コミットでデータベースに反映する
+/// コミットせずに破棄した場合はロールバックと同じになる
+///
+pub struct MemoryTransaction {
+    database:   Arc<Mutex<MemoryStore>> ,
+    store:      Mutex<MemoryStore>
+}
+impl MemoryTransaction {
+    /// トランザクション内のテーブルを返す
+    pub fn store(&self) -> Result<MutexGuard<'_ , MemoryStore>> {
+        lock(&self.store)
+    }
+}
+#[async_trait]
+impl Transaction for MemoryTransaction {
+    // 更新したテーブルでデータベースを置き換える
+    async fn commit(self) -> Result<()> {
+        let store = match self.store.into_inner() {
+            Ok(store) => store ,
+            Err(_) => return Err(AppError::from("トランザクションのテーブルが破損しています。"))
+        };
+        *lock(&self.database)? = store;
+        Ok(())
+    }
+    // 更新したテーブルを破棄する
+    async fn rollback(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// テーブルをロックする
+fn lock(store: &Mutex<MemoryStore>) -> Result<MutexGuard<'_ , MemoryStore>> {
+    match store.lock() {
+        Ok(guard) => Ok(guard) ,
+        Err(_) => Err(AppError::from("メモリ上のテーブルが破損しています。"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[actix::test]
+    async fn commit_and_rollback() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        assert_eq!(database.snapshot()?.products.len() , 28);
+        // ロールバックした変更は反映されない
+        let tran = database.begin().await?;
+        tran.store()?.products.remove(&1);
+        assert_eq!(tran.store()?.products.len() , 27);
+        tran.rollback().await?;
+        assert_eq!(database.snapshot()?.products.len() , 28);
+        // コミットした変更は反映される
+        let tran = database.begin().await?;
+        tran.store()?.products.remove(&1);
+        tran.commit().await?;
+        assert_eq!(database.snapshot()?.products.len() , 27);
+        Ok(())
+    }
+}
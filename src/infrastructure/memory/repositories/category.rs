@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::Result;
+use crate::domain::entities::Category;
+use crate::domain::repositories::CategoryRepository;
+use crate::domain::values::categories::CategoryId;
+use crate::domain::values::ValueInto;
+use crate::infrastructure::memory::database::MemoryTransaction;
+
+///
+///  メモリ上の商品カテゴリ Repository
+///
+pub struct CategoryRepositoryImpl;
+impl CategoryRepositoryImpl {
+    //  Repositoryの生成
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn CategoryRepository<Transaction=MemoryTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl CategoryRepository for CategoryRepositoryImpl{
+    type Transaction = MemoryTransaction;
+    ///　すべてのカテゴリを取得する
+    async fn select_all(&self, tran: &Self::Transaction) -> Result<Vec<Category>> {
+        Ok(tran.store()?.categories.values().cloned().collect())
+    }
+    ///　指定された識別子でカテゴリを取得する
+    async fn select_by_id(&self, tran: &Self::Transaction, id: &CategoryId) -> Result<Option<Category>> {
+        Ok(tran.store()?.categories.get(&id.value()).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::transaction::TransactionManager;
+    use super::*;
+
+    #[actix::test]
+    async fn select() -> Result<()> {
+        let tran = MemoryDatabase::with_fixtures()?.begin().await?;
+        let repository = CategoryRepositoryImpl::new();
+        assert_eq!(repository.select_all(&tran).await?.len() , 3);
+        assert!(repository.select_by_id(&tran , &CategoryId::try_from(1)?).await?.is_some());
+        assert!(repository.select_by_id(&tran , &CategoryId::try_from(10)?).await?.is_none());
+        Ok(())
+    }
+}
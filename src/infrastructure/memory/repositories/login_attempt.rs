@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::Result;
+use crate::domain::entities::{AuthEvent, LoginAttempt};
+use crate::domain::repositories::{AuthEventRepository, LoginAttemptRepository};
+use crate::infrastructure::memory::database::MemoryTransaction;
+
+///
+/// メモリ上の認証の失敗回数リポジトリ
+///
+pub struct LoginAttemptRepositoryImpl;
+impl LoginAttemptRepositoryImpl {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn LoginAttemptRepository<Transaction=MemoryTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl LoginAttemptRepository for LoginAttemptRepositoryImpl {
+    type Transaction = MemoryTransaction;
+    /// キーで失敗回数を取得する
+    async fn select_by_key(&self, tran: &Self::Transaction, key: &str) -> Result<Option<LoginAttempt>> {
+        Ok(tran.store()?.login_attempts.get(key).cloned())
+    }
+    /// キーで失敗回数を取得する、ない場合は0回で登録する
+    async fn select_for_update(&self, tran: &Self::Transaction, key: &str) -> Result<LoginAttempt> {
+        Ok(tran.store()?.login_attempts.entry(key.to_string())
+            .or_insert_with(|| LoginAttempt::new(key.to_string())).clone())
+    }
+    /// 失敗回数を永続化する
+    async fn save(&self, tran: &Self::Transaction, attempt: &LoginAttempt) -> Result<()> {
+        tran.store()?.login_attempts.insert(attempt.key.clone() , attempt.clone());
+        Ok(())
+    }
+    /// 失敗回数を削除する
+    async fn delete(&self, tran: &Self::Transaction, key: &str) -> Result<()> {
+        tran.store()?.login_attempts.remove(key);
+        Ok(())
+    }
+}
+
+///
+/// メモリ上の認証イベントリポジトリ
+///
+pub struct AuthEventRepositoryImpl;
+impl AuthEventRepositoryImpl {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn AuthEventRepository<Transaction=MemoryTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl AuthEventRepository for AuthEventRepositoryImpl {
+    type Transaction = MemoryTransaction;
+    /// 認証イベントを永続化する
+    async fn insert(&self, tran: &Self::Transaction, event: &AuthEvent) -> Result<()> {
+        tran.store()?.auth_events.push(event.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod category;
+pub mod login_attempt;
+pub mod product;
+pub mod token;
+pub mod user;
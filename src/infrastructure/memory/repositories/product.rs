@@ -0,0 +1,167 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::{AppError, Result};
+use crate::i18n::Message;
+use crate::domain::entities::{Characteristic, Product};
+use crate::domain::repositories::ProductRepository;
+use crate::domain::values::pages::{Page, PageRequest};
+use crate::domain::values::products::{KeywordMatch, ProductId, ProductName, ProductSearchCriteria, ProductSortKey};
+use crate::domain::values::ValueInto;
+use crate::infrastructure::memory::database::{MemoryStore, MemoryTransaction};
+
+///
+/// メモリ上の商品リポジトリ
+///
+pub struct ProductRepositoryImpl;
+impl ProductRepositoryImpl{
+    // インスタンスをProductRepository型に変換して返す
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn ProductRepository<Transaction=MemoryTransaction>> {
+        Arc::new(Self{})
+    }
+    // 検索条件に該当するか判定する
+    fn matches(criteria: &ProductSearchCriteria , product: &Product) -> bool {
+        let name = product.name.value();
+        let keywords = criteria.keywords();
+        let keyword = keywords.is_empty() || match criteria.keyword_match() {
+            KeywordMatch::All => keywords.iter().all(|keyword| name.contains(keyword.as_str())) ,
+            KeywordMatch::Any => keywords.iter().any(|keyword| name.contains(keyword.as_str()))
+        };
+        let price = product.price.value();
+        let min_price = criteria.min_price().map_or(true , |min| price >= min);
+        let max_price = criteria.max_price().map_or(true , |max| price <= max);
+        let category = criteria.category_ids().is_empty() || product.category.as_ref()
+            .is_some_and(|category| criteria.category_ids().contains(&category.get()));
+        keyword && min_price && max_price && category
+    }
+    // カテゴリ番号だけを保持している商品にカテゴリを結合する
+    fn join(store: &MemoryStore , product: &Product) -> Product {
+        let mut product = product.clone();
+        product.category = product.category.as_ref()
+            .and_then(|category| store.categories.get(&category.get().value()).cloned());
+        product
+    }
+}
+#[async_trait]
+impl ProductRepository for ProductRepositoryImpl{
+    type Transaction = MemoryTransaction;
+    /// 検索条件による検索
+    async fn select_by_criteria(&self, tran: &Self::Transaction, criteria: &ProductSearchCriteria ,
+                                page: &PageRequest , sort: &ProductSortKey) -> Result<Page<Product>> {
+        let store = tran.store()?;
+        // 商品番号順の商品から検索条件に該当する商品を抽出する
+        let mut products:Vec<Product> = store.products.values()
+            .filter(|product| Self::matches(criteria , product))
+            .map(|product| Self::join(&store , product)).collect();
+        // 指定された並び順でソートする(同じ値の商品は商品番号順のまま)
+        match sort {
+            ProductSortKey::IdAsc => () ,
+            ProductSortKey::IdDesc => products.reverse() ,
+            ProductSortKey::NameAsc => products.sort_by_key(|product| product.name.value()) ,
+            ProductSortKey::NameDesc => products.sort_by_key(|product| std::cmp::Reverse(product.name.value())) ,
+            ProductSortKey::PriceAsc => products.sort_by_key(|product| product.price.value()) ,
+            ProductSortKey::PriceDesc => products.sort_by_key(|product| std::cmp::Reverse(product.price.value()))
+        }
+        let total_count = products.len() as u64;
+        let items = products.into_iter()
+            .skip(usize::try_from(page.offset()).unwrap_or(usize::MAX))
+            .take(page.page_size() as usize).collect();
+        Ok(Page{
+            items ,
+            total_count ,
+            total_pages: (total_count + page.page_size() - 1) / page.page_size() ,
+            page: page.page() ,
+            page_size: page.page_size()
+        })
+    }
+    /// 新商品の追加
+    async fn insert(&self, tran: &Self::Transaction, product: &Product) -> Result<Product> {
+        let mut store = tran.store()?;
+        // 最大の商品番号の次の番号を採番する
+        let new_id = store.products.keys().next_back().map_or(1 , |id| id + 1);
+        let mut new_product = product.clone();
+        new_product.change(&ProductId::try_from(new_id)?)?;
+        store.products.insert(new_id , new_product.clone());
+        Ok(new_product)
+    }
+    /// 商品の存在チェック
+    async fn exists(&self, tran: &Self::Transaction, name: &ProductName) -> Result<bool> {
+        Ok(tran.store()?.products.values().any(|product| product.name.eq(name)))
+    }
+    /// 商品番号で商品を取得する
+    async fn select_by_id(&self, tran: &Self::Transaction, id: &ProductId) -> Result<Option<Product>> {
+        let store = tran.store()?;
+        Ok(store.products.get(&id.value()).map(|product| Self::join(&store , product)))
+    }
+    /// 商品の変更
+    async fn update(&self, tran: &Self::Transaction, product: &Product) -> Result<Product> {
+        match tran.store()?.products.get_mut(&product.get().value()) {
+            Some(current) => {
+                *current = product.clone();
+                Ok(product.clone())
+            },
+            None => Err(AppError::NotFound(Message::new("product.not_found").with_param("id" , product.get().value())))
+        }
+    }
+    /// 商品の削除
+    async fn delete(&self, tran: &Self::Transaction, id: &ProductId) -> Result<bool> {
+        Ok(tran.store()?.products.remove(&id.value()).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::entities::Category;
+    use crate::domain::values::categories::{CategoryId, CategoryName};
+    use crate::domain::values::products::ProductPrice;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::transaction::TransactionManager;
+    use super::*;
+
+    #[actix::test]
+    async fn select_by_criteria() -> Result<()> {
+        let tran = MemoryDatabase::with_fixtures()?.begin().await?;
+        let repository = ProductRepositoryImpl::new();
+        // sea_ormのRepositoryのテストと同じ結果になる
+        let products = repository.select_by_criteria(
+            &tran , &ProductSearchCriteria::keyword(&ProductName::try_from(String::from("ボールペン"))?) ,
+            &PageRequest::new(2 , 4)? , &ProductSortKey::PriceDesc).await?;
+        assert_eq!(products.total_count , 6);
+        assert_eq!(products.total_pages , 2);
+        assert_eq!(products.items.iter().map(|product| product.get().value()).collect::<Vec<i32>>() , vec![5 , 6]);
+        // 上限のページ番号は空のページになる
+        let products = repository.select_by_criteria(
+            &tran , &ProductSearchCriteria::keyword(&ProductName::try_from(String::from("ボールペン"))?) ,
+            &PageRequest::new(PageRequest::max_page(100) , 100)? , &ProductSortKey::PriceDesc).await?;
+        assert!(products.items.is_empty());
+        let criteria = ProductSearchCriteria::new(vec![] , KeywordMatch::All ,
+                                                  Some(500) , Some(1000) ,
+                                                  vec![CategoryId::try_from(2)? , CategoryId::try_from(3)?])?;
+        let products = repository.select_by_criteria(&tran , &criteria ,
+                                                     &PageRequest::default() , &ProductSortKey::default()).await?;
+        assert_eq!(products.total_count , 7);
+        assert_eq!(products.items[0].category.as_ref().unwrap().name.value() , "雑貨");
+        Ok(())
+    }
+
+    #[actix::test]
+    async fn insert_update_delete() -> Result<()> {
+        let tran = MemoryDatabase::with_fixtures()?.begin().await?;
+        let repository = ProductRepositoryImpl::new();
+        let category = Category::new(CategoryId::try_from(1)? , CategoryName::try_from(String::from("dummy"))?);
+        let product = Product::new(ProductId::try_from(0)? ,
+                                   ProductName::try_from(String::from("油性ボールペン(緑)"))? ,
+                                   ProductPrice::try_from(100)? , Some(category));
+        let mut product = repository.insert(&tran , &product).await?;
+        assert_eq!(product.get().value() , 29);
+        assert!(repository.exists(&tran , &product.name).await?);
+        product.price = ProductPrice::try_from(150)?;
+        repository.update(&tran , &product).await?;
+        let result = repository.select_by_id(&tran , &product.get()).await?.unwrap();
+        assert_eq!(result.price.value() , 150);
+        assert_eq!(result.category.unwrap().name.value() , "文房具");
+        assert!(repository.delete(&tran , &product.get()).await?);
+        assert!(!repository.delete(&tran , &product.get()).await?);
+        Ok(())
+    }
+}
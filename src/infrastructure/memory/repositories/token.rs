@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::Result;
+use crate::domain::entities::RefreshToken;
+use crate::domain::repositories::{RefreshTokenRepository, RevokedTokenRepository};
+use crate::infrastructure::memory::database::MemoryTransaction;
+
+///
+/// メモリ上のリフレッシュトークンリポジトリ
+///
+pub struct RefreshTokenRepositoryImpl;
+impl RefreshTokenRepositoryImpl {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn RefreshTokenRepository<Transaction=MemoryTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
+    type Transaction = MemoryTransaction;
+    /// 新しいトークンを永続化する
+    async fn insert(&self, tran: &Self::Transaction, token: &RefreshToken) -> Result<()> {
+        tran.store()?.refresh_tokens.insert(token.token_hash.clone() , token.clone());
+        Ok(())
+    }
+    /// ハッシュ値でトークンを取得する
+    async fn select_by_hash(&self, tran: &Self::Transaction, token_hash: &str) -> Result<Option<RefreshToken>> {
+        Ok(tran.store()?.refresh_tokens.get(token_hash).cloned())
+    }
+    /// 未使用のトークンをローテーション済にする
+    async fn replace(&self, tran: &Self::Transaction, token_hash: &str, replaced_by: &str) -> Result<bool> {
+        match tran.store()?.refresh_tokens.get_mut(token_hash) {
+            Some(token) if !token.is_used() => {
+                token.replaced_by = Some(replaced_by.to_string());
+                Ok(true)
+            },
+            _ => Ok(false)
+        }
+    }
+    /// ファミリーのトークンをすべて失効させる
+    async fn revoke_family(&self, tran: &Self::Transaction, family_id: &str) -> Result<u64> {
+        let mut count = 0;
+        for token in tran.store()?.refresh_tokens.values_mut()
+            .filter(|token| token.family_id == family_id && !token.revoked) {
+            token.revoked = true;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+///
+/// メモリ上の失効したアクセストークンリポジトリ
+///
+pub struct RevokedTokenRepositoryImpl;
+impl RevokedTokenRepositoryImpl {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn RevokedTokenRepository<Transaction=MemoryTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl RevokedTokenRepository for RevokedTokenRepositoryImpl {
+    type Transaction = MemoryTransaction;
+    /// 失効したトークンの識別子を永続化する
+    async fn insert(&self, tran: &Self::Transaction, jti: &str, expires_at: i64) -> Result<()> {
+        tran.store()?.revoked_tokens.entry(jti.to_string()).or_insert(expires_at);
+        Ok(())
+    }
+    /// 有効期限内の失効したトークンを取得する
+    async fn select_active(&self, tran: &Self::Transaction, now: i64) -> Result<Vec<(String , i64)>> {
+        Ok(tran.store()?.revoked_tokens.iter()
+            .filter(|(_ , expires_at)| **expires_at > now)
+            .map(|(jti , expires_at)| (jti.clone() , *expires_at)).collect())
+    }
+    /// 有効期限を過ぎたトークンを削除する
+    async fn delete_expired(&self, tran: &Self::Transaction, now: i64) -> Result<u64> {
+        let mut store = tran.store()?;
+        let before = store.revoked_tokens.len();
+        store.revoked_tokens.retain(|_ , expires_at| *expires_at > now);
+        Ok((before - store.revoked_tokens.len()) as u64)
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use crate::{AppError, Result};
+use crate::i18n::Message;
+use crate::domain::entities::{Characteristic, User};
+use crate::domain::repositories::UserRepository;
+use crate::domain::values::users::{Mail, UserId, UserName};
+use crate::domain::values::ValueInto;
+use crate::infrastructure::memory::database::MemoryTransaction;
+
+///
+/// メモリ上のユーザーリポジトリ
+///
+pub struct UserRepositoryImpl;
+impl UserRepositoryImpl{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn UserRepository<Transaction=MemoryTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl UserRepository for UserRepositoryImpl{
+    type Transaction = MemoryTransaction;
+    /// 指定されたユーザー名で問合せする
+    async fn select_by_name(&self, tran: &Self::Transaction, user_name: &UserName) -> Result<Option<User>> {
+        Ok(tran.store()?.users.values().find(|user| user.user_name.eq(user_name)).cloned())
+    }
+    /// 指定されたユーザーIDで問合せする
+    async fn select_by_id(&self, tran: &Self::Transaction, user_id: &UserId) -> Result<Option<User>> {
+        Ok(tran.store()?.users.get(&user_id.value()).cloned())
+    }
+    /// 指定されたユーザー名のユーザーが存在するか確認する
+    async fn exists_by_name(&self, tran: &Self::Transaction, user_name: &UserName) -> Result<bool> {
+        Ok(tran.store()?.users.values().any(|user| user.user_name.eq(user_name)))
+    }
+    /// 指定されたメールアドレスのユーザーが存在するか確認する
+    async fn exists_by_mail(&self, tran: &Self::Transaction, mail: &Mail) -> Result<bool> {
+        Ok(tran.store()?.users.values().any(|user| user.mail.eq(mail)))
+    }
+    /// 新しいユーザーを永続化する
+    async fn insert(&self, tran: &Self::Transaction, user: &User) -> Result<User> {
+        tran.store()?.users.insert(user.get().value() , user.clone());
+        Ok(user.clone())
+    }
+    /// ユーザーのパスワードを変更する
+    async fn update_password(&self, tran: &Self::Transaction, user: &User) -> Result<User> {
+        match tran.store()?.users.get_mut(&user.get().value()) {
+            Some(current) => {
+                current.password = user.password.clone();
+                Ok(user.clone())
+            },
+            None => Err(AppError::NotFound(Message::new("user.not_found").with_param("id" , user.get().value())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::values::users::Password;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::transaction::TransactionManager;
+    use super::*;
+
+    #[actix::test]
+    async fn select_and_insert() -> Result<()> {
+        let tran = MemoryDatabase::with_fixtures()?.begin().await?;
+        let repository = UserRepositoryImpl::new();
+        let user = repository.select_by_name(&tran ,
+            &UserName::try_from(String::from("user001"))?).await?;
+        assert!(user.is_some());
+        let user = repository.select_by_id(&tran , &user.unwrap().get()).await?;
+        assert_eq!(user.unwrap().user_name.value() , "user001");
+        assert!(repository.exists_by_mail(&tran ,
+            &Mail::try_from(String::from("suzuki@sample.com"))?).await?);
+        let user = User::new(
+            UserName::try_from(String::from("user003"))? ,
+            Password::try_from(String::from("pass003"))? ,
+            Mail::try_from(String::from("user003@sample.com"))?)?;
+        repository.insert(&tran , &user).await?;
+        assert!(repository.exists_by_name(&tran , &user.user_name).await?);
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
 pub mod pool;
 pub mod converter;
-pub mod sea_orm;
\ No newline at end of file
+pub mod transaction;
+pub mod fixtures;
+pub mod hasher_impl;
+pub mod sea_orm;
+pub mod memory;
@@ -1,5 +1,7 @@
 use async_trait::async_trait;
+use crate::config::DatabaseConfig;
+use crate::Result;
 #[async_trait]
 pub trait PoolProvider<T>{
-    async fn get() -> T;
-}
\ No newline at end of file
+    async fn get(config: &DatabaseConfig) -> Result<T>;
+}
@@ -1,15 +1,17 @@
 use sea_orm::ActiveValue::{NotSet, Set};
-use crate::Result;
-use crate::domain::entities::{Category, Product, User};
+use crate::{AppError, Result};
+use crate::domain::entities::{AuthEvent, Category, LoginAttempt, Product, RefreshToken, User};
 use crate::domain::values::categories::{CategoryId, CategoryName};
 use crate::domain::values::products::{ProductId, ProductName, ProductPrice};
 use crate::domain::values::users::{Mail, Password, UserId, UserName};
 use crate::domain::values::ValueInto;
 use crate::domain::entities::Characteristic;
+use crate::infrastructure::sea_orm::models::{auth_event, login_attempt};
 use crate::infrastructure::sea_orm::models::product_category;
 use crate::infrastructure::sea_orm::models::product;
+use crate::infrastructure::sea_orm::models::refresh_token;
 use crate::infrastructure::sea_orm::models::user;
-use crate::infrastructure::converter::{ActiveModelGenerator, ModelAndEntity, VecModelToVecEntity};
+use crate::infrastructure::converter::{required, stored, ActiveModelGenerator, ConversionError, ModelAndEntity, VecModelToVecEntity};
 
 ///
 /// 商品カテゴリの変換
@@ -21,9 +23,9 @@ impl ModelAndEntity for CategoryConverter{
     type Model  = product_category::Model;
     fn model_to_entity(model: &Self::Model) -> Result<Self::Entity> {
         let m = model.clone();
-        Ok(Category::new(
+        stored(|| Ok(Category::new(
             CategoryId::try_from(m.id)? ,
-            CategoryName::try_from(m.name.unwrap())?))
+            CategoryName::try_from(required(m.name , "product_category" , m.id , "name")?)?)))
     }
     fn entity_to_model(entity: &Self::Entity) -> Self::Model {
         Self::Model{
@@ -41,11 +43,7 @@ impl VecModelToVecEntity for CategoryConverter {
     fn entities(models: &Vec<Self::Model>) -> Result<Vec<Self::Entity>>{
         let mut categories:Vec<Self::Entity> = Vec::new();
         for model in models {
-            let m = model.clone();
-            let category = Category::new(
-                CategoryId::try_from(m.id)? ,
-                CategoryName::try_from(m.name.unwrap())?);
-            categories.push(category);
+            categories.push(Self::model_to_entity(model)?);
         }
         Ok(categories)
     }
@@ -62,16 +60,18 @@ impl ModelAndEntity for ProductConverter{
     // ORMモデルからEntityに変換する
     fn model_to_entity(model: &Self::Model) -> Result<Self::Entity> {
         let m = model.clone();
-        // カテゴリEntityを生成する
-        let category = Category::new(
-            CategoryId::try_from(m.category_id.unwrap())? ,
-            CategoryName::try_from(String::from("dummy"))?);
-        // 商品Entityを生成して返す
-        Ok(Product::new(
-            ProductId::try_from(m.id)? ,
-            ProductName::try_from(m.name.unwrap())? ,
-            ProductPrice::try_from(m.price.unwrap())? ,
-            Some(category)))
+        stored(|| {
+            // カテゴリEntityを生成する
+            let category = Category::new(
+                CategoryId::try_from(required(m.category_id , "product" , m.id , "category_id")?)? ,
+                CategoryName::try_from(String::from("dummy"))?);
+            // 商品Entityを生成して返す
+            Ok(Product::new(
+                ProductId::try_from(m.id)? ,
+                ProductName::try_from(required(m.name , "product" , m.id , "name")?)? ,
+                ProductPrice::try_from(required(m.price , "product" , m.id , "price")?)? ,
+                Some(category)))
+        })
     }
     // EntityをORMモデルに変換する
     fn entity_to_model(entity: &Self::Entity) -> Self::Model {
@@ -91,15 +91,17 @@ impl VecModelToVecEntity for ProductConverter {
     // 結合結果のORM ModelをEntityに変換する
     fn join_model_to_entities(models: &Vec<(Self::Model , Option<Self::JoinModel>)>) -> Result<Vec<Self::Entity>>{
         let mut products:Vec<Product> = Vec::new();
-        for model in models{
-            let m = model.clone();
-            let category = Category::new(
-                CategoryId::try_from(m.1.as_ref().unwrap().id)? ,
-                CategoryName::try_from(m.1.unwrap().name.unwrap())?);
+        for (product , category) in models{
+            // カテゴリが結合できない商品は変換エラーとする
+            let category = match category {
+                Some(category) => CategoryConverter::model_to_entity(category)? ,
+                None => return Err(AppError::from(
+                    ConversionError::missing_reference("product" , product.id , "category_id")))
+            };
             let product = Product::new(
-                ProductId::try_from(m.0.id)? ,
-                ProductName::try_from(m.0.name.unwrap())?,
-                ProductPrice::try_from(m.0.price.unwrap())?,
+                ProductId::try_from(product.id)? ,
+                ProductName::try_from(required(product.name.clone() , "product" , product.id , "name")?)?,
+                ProductPrice::try_from(required(product.price , "product" , product.id , "price")?)?,
                 Some(category));
             products.push(product);
         }
@@ -127,11 +129,11 @@ impl ModelAndEntity for UserConverter {
 
     fn model_to_entity(model: &Self::Model) -> Result<Self::Entity> {
         let m = model.clone();
-        Ok(User::rebuilding(
-            UserId::try_from(m.user_id.unwrap())?,
-            UserName::try_from(m.user_name.unwrap())?,
-            Password::try_from(m.password.unwrap())?,
-            Mail::try_from(m.mail.unwrap())?))
+        stored(|| Ok(User::rebuilding(
+            UserId::try_from(required(m.user_id , "user" , m.id , "user_id")?)?,
+            UserName::try_from(required(m.user_name , "user" , m.id , "user_name")?)?,
+            Password::try_from(required(m.password , "user" , m.id , "password")?)?,
+            Mail::try_from(required(m.mail , "user" , m.id , "mail")?)?)))
     }
     fn entity_to_model(entity: &Self::Entity) -> Self::Model {
         Self::Model{
@@ -155,4 +157,156 @@ impl ActiveModelGenerator for UserConverter{
             mail: Set(Some(entity.mail.value()))
         }
     }
-}
\ No newline at end of file
+}
+
+///
+/// リフレッシュトークンの変換
+///
+pub struct RefreshTokenConverter;
+impl ModelAndEntity for RefreshTokenConverter {
+    type Entity = RefreshToken;
+    type Model = refresh_token::Model;
+
+    fn model_to_entity(model: &Self::Model) -> Result<Self::Entity> {
+        let m = model.clone();
+        stored(|| Ok(RefreshToken{
+            token_hash: m.token_hash ,
+            family_id: m.family_id ,
+            user_id: UserId::try_from(m.user_id)? ,
+            expires_at: m.expires_at ,
+            revoked: m.revoked ,
+            replaced_by: m.replaced_by
+        }))
+    }
+    fn entity_to_model(entity: &Self::Entity) -> Self::Model {
+        Self::Model{
+            id: 0 ,
+            token_hash: entity.token_hash.clone() ,
+            family_id: entity.family_id.clone() ,
+            user_id: entity.user_id.value() ,
+            expires_at: entity.expires_at ,
+            revoked: entity.revoked ,
+            replaced_by: entity.replaced_by.clone()
+        }
+    }
+}
+impl ActiveModelGenerator for RefreshTokenConverter {
+    type Entity = RefreshToken;
+    type ActiveModel = refresh_token::ActiveModel;
+    fn active_model(entity: &Self::Entity) -> Self::ActiveModel {
+        Self::ActiveModel{
+            id: NotSet ,
+            token_hash: Set(entity.token_hash.clone()) ,
+            family_id: Set(entity.family_id.clone()) ,
+            user_id: Set(entity.user_id.value()) ,
+            expires_at: Set(entity.expires_at) ,
+            revoked: Set(entity.revoked) ,
+            replaced_by: Set(entity.replaced_by.clone())
+        }
+    }
+}
+///
+/// 認証の失敗回数の変換
+///
+pub struct LoginAttemptConverter;
+impl ModelAndEntity for LoginAttemptConverter {
+    type Entity = LoginAttempt;
+    type Model = login_attempt::Model;
+
+    fn model_to_entity(model: &Self::Model) -> Result<Self::Entity> {
+        let m = model.clone();
+        Ok(LoginAttempt{
+            key: m.key ,
+            failures: m.failures.max(0) as u32 ,
+            last_failed_at: m.last_failed_at ,
+            locked_until: m.locked_until
+        })
+    }
+    fn entity_to_model(entity: &Self::Entity) -> Self::Model {
+        Self::Model{
+            key: entity.key.clone() ,
+            failures: entity.failures.min(i32::MAX as u32) as i32 ,
+            last_failed_at: entity.last_failed_at ,
+            locked_until: entity.locked_until
+        }
+    }
+}
+impl ActiveModelGenerator for LoginAttemptConverter {
+    type Entity = LoginAttempt;
+    type ActiveModel = login_attempt::ActiveModel;
+    fn active_model(entity: &Self::Entity) -> Self::ActiveModel {
+        let model = Self::entity_to_model(entity);
+        Self::ActiveModel{
+            key: Set(model.key) ,
+            failures: Set(model.failures) ,
+            last_failed_at: Set(model.last_failed_at) ,
+            locked_until: Set(model.locked_until)
+        }
+    }
+}
+///
+/// 認証イベントの変換、記録のみで読込まない
+///
+pub struct AuthEventConverter;
+impl ActiveModelGenerator for AuthEventConverter {
+    type Entity = AuthEvent;
+    type ActiveModel = auth_event::ActiveModel;
+    fn active_model(entity: &Self::Entity) -> Self::ActiveModel {
+        Self::ActiveModel{
+            id: NotSet ,
+            user_name: Set(entity.user_name.clone()) ,
+            user_id: Set(entity.user_id.as_ref().map(|user_id| user_id.value())) ,
+            client: Set(entity.client.clone()) ,
+            outcome: Set(entity.outcome.as_str().to_string()) ,
+            occurred_at: Set(entity.occurred_at)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::converter::ConversionCause;
+
+    // NULLの列は変換エラーになり、テーブル、行と列が分かる
+    #[test]
+    fn model_to_entity_null() {
+        let model = product::Model{ id: 5 , name: None , price: Some(100) , category_id: Some(1) };
+        match ProductConverter::model_to_entity(&model).err().unwrap() {
+            AppError::Conversion(error) => {
+                assert_eq!(error , ConversionError::null("product" , 5 , "name"));
+                assert_eq!(error.to_string() , "product(id=5)のnameを変換できません: 値がNULLです。");
+            },
+            error => panic!("{:?}" , error)
+        }
+        let model = user::Model{ id: 2 , user_id: Some(String::from("5ca87702-a40a-4f08-85c3-534e92e36c0e")) ,
+            user_name: Some(String::from("user002")) , password: None , mail: Some(String::from("suzuki@sample.com")) };
+        assert!(matches!(UserConverter::model_to_entity(&model) ,
+            Err(AppError::Conversion(ConversionError{ cause: ConversionCause::Null , .. }))));
+    }
+
+    // 登録済の値のルール違反は入力値の誤りではなく内部エラーになる
+    #[test]
+    fn model_to_entity_rule() {
+        let model = product::Model{ id: 5 , name: Some(String::from("鉛筆(黒)")) , price: Some(-1) , category_id: Some(1) };
+        let error = ProductConverter::model_to_entity(&model).err().unwrap();
+        assert!(matches!(error , AppError::Internal(_)) , "{:?}" , error);
+        assert_eq!(error.status() , 500);
+    }
+
+    // カテゴリが結合できない商品は変換エラーになる
+    #[test]
+    fn join_model_to_entities() {
+        let category = product_category::Model{ id: 1 , name: Some(String::from("文房具")) };
+        let models = vec![
+            (product::Model{ id: 1 , name: Some(String::from("鉛筆(黒)")) , price: Some(100) , category_id: Some(1) } , Some(category)) ,
+            (product::Model{ id: 2 , name: Some(String::from("鉛筆(赤)")) , price: Some(100) , category_id: Some(9) } , None)
+        ];
+        match ProductConverter::join_model_to_entities(&models).err().unwrap() {
+            AppError::Conversion(error) =>
+                assert_eq!(error , ConversionError::missing_reference("product" , 2 , "category_id")) ,
+            error => panic!("{:?}" , error)
+        }
+        assert_eq!(ProductConverter::join_model_to_entities(&models[..1].to_vec()).unwrap().len() , 1);
+    }
+}
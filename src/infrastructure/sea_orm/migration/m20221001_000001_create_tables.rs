@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+///
+/// 商品カテゴリ、商品、ユーザーテーブルを作成する
+/// 既存のデータベース(SQLスクリプトで作成済)にも適用できるよう、存在するテーブルは作成しない
+/// 既存のテーブルにない制約はm20221001_000002_add_legacy_constraintsで追加する
+///
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 商品カテゴリテーブル
+        manager.create_table(Table::create()
+            .table(ProductCategory::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(ProductCategory::Id).integer().not_null().auto_increment().primary_key())
+            .col(ColumnDef::new(ProductCategory::Name).string_len(20).not_null().unique_key())
+            .to_owned()).await?;
+        // 商品テーブル
+        manager.create_table(Table::create()
+            .table(Product::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(Product::Id).integer().not_null().auto_increment().primary_key())
+            .col(ColumnDef::new(Product::Name).string_len(30).not_null().unique_key())
+            .col(ColumnDef::new(Product::Price).integer().not_null())
+            .col(ColumnDef::new(Product::CategoryId).integer().not_null())
+            .foreign_key(ForeignKey::create()
+                .name("product_category_fk")
+                .from(Product::Table , Product::CategoryId)
+                .to(ProductCategory::Table , ProductCategory::Id)
+                .on_update(ForeignKeyAction::NoAction)
+                .on_delete(ForeignKeyAction::NoAction))
+            .to_owned()).await?;
+        // ユーザーテーブル
+        manager.create_table(Table::create()
+            .table(User::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(User::Id).integer().not_null().auto_increment().primary_key())
+            .col(ColumnDef::new(User::UserId).string_len(40).not_null().unique_key())
+            .col(ColumnDef::new(User::UserName).string_len(30).not_null().unique_key())
+            .col(ColumnDef::new(User::Password).string_len(130).not_null())
+            .col(ColumnDef::new(User::Mail).string_len(50).not_null().unique_key())
+            .to_owned()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 参照元から順に削除する
+        manager.drop_table(Table::drop().table(User::Table).if_exists().to_owned()).await?;
+        manager.drop_table(Table::drop().table(Product::Table).if_exists().to_owned()).await?;
+        manager.drop_table(Table::drop().table(ProductCategory::Table).if_exists().to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum ProductCategory {
+    Table ,
+    Id ,
+    Name
+}
+#[derive(Iden)]
+enum Product {
+    Table ,
+    Id ,
+    Name ,
+    Price ,
+    CategoryId
+}
+// 列名はテーブル定義に合わせる
+#[allow(clippy::enum_variant_names)]
+#[derive(Iden)]
+enum User {
+    Table ,
+    Id ,
+    UserId ,
+    UserName ,
+    Password ,
+    Mail
+}
@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend, Statement};
+
+/// 必須にする列(テーブル,列)
+const NOT_NULL_COLUMNS: [(&str , &str); 4] = [
+    ("product_category" , "name") , ("product" , "name") , ("product" , "price") , ("product" , "category_id")];
+/// 一意にする列(テーブル,列)
+/// インデックス名はm20221001_000001_create_tablesの一意制約にPostgreSQLが付ける名前と同じにする
+const UNIQUE_COLUMNS: [(&str , &str); 5] = [
+    ("product_category" , "name") , ("product" , "name") ,
+    ("user" , "user_id") , ("user" , "user_name") , ("user" , "mail")];
+/// 商品カテゴリの外部キー
+const CATEGORY_FK: &str = r#"DO $$ BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'product_category_fk') THEN
+        ALTER TABLE product ADD CONSTRAINT product_category_fk FOREIGN KEY (category_id) REFERENCES product_category (id);
+    END IF;
+END $$"#;
+
+///
+/// SQLスクリプトで作成済のデータベースに、m20221001_000001_create_tablesの制約を追加する
+/// 作成済のテーブルはm20221001_000001_create_tablesで作成されないため、必須、一意、外部キーの制約がない
+/// 制約に違反するデータがある場合は変更せずにエラーを返す
+/// マイグレーションで作成したデータベースでは何も変更しない、SQLiteも同様
+///
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DatabaseBackend::Sqlite {
+            return Ok(());
+        }
+        let violations = violations(manager).await?;
+        if !violations.is_empty() {
+            return Err(DbErr::Migration(format!("制約に違反するデータを修正してください。\n{}" , violations.join("\n"))));
+        }
+        for (table , column) in NOT_NULL_COLUMNS {
+            execute(manager , format!(r#"ALTER TABLE "{}" ALTER COLUMN "{}" SET NOT NULL"# , table , column)).await?;
+        }
+        for (table , column) in UNIQUE_COLUMNS {
+            execute(manager , format!(r#"CREATE UNIQUE INDEX IF NOT EXISTS "{}" ON "{}" ("{}")"# ,
+                unique_index(table , column) , table , column)).await?;
+        }
+        execute(manager , CATEGORY_FK.to_string()).await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // 追加した制約はm20221001_000001_create_tablesの定義と同じため削除しない
+        Ok(())
+    }
+}
+
+// 一意制約のインデックス名
+fn unique_index(table: &str , column: &str) -> String {
+    format!("{}_{}_key" , table , column)
+}
+// 制約に違反するデータの内容を返す
+async fn violations(manager: &SchemaManager<'_>) -> Result<Vec<String> , DbErr> {
+    let mut violations = Vec::new();
+    for (table , column) in NOT_NULL_COLUMNS {
+        let sql = format!(r#"SELECT CAST(id AS text) AS value FROM "{}" WHERE "{}" IS NULL ORDER BY id"# , table , column);
+        for id in query_values(manager , sql).await? {
+            violations.push(format!("{}.{}がありません: id={}" , table , column , id));
+        }
+    }
+    for (table , column) in UNIQUE_COLUMNS {
+        let sql = format!(r#"SELECT CAST("{1}" AS text) AS value FROM "{0}" GROUP BY "{1}" HAVING count(*) > 1 ORDER BY 1"# , table , column);
+        for value in query_values(manager , sql).await? {
+            violations.push(format!("{}.{}が重複しています: {}" , table , column , value));
+        }
+    }
+    let sql = String::from(r#"SELECT CAST(id AS text) AS value FROM product WHERE category_id IS NOT NULL
+        AND category_id NOT IN (SELECT id FROM product_category) ORDER BY id"#);
+    for id in query_values(manager , sql).await? {
+        violations.push(format!("product.category_idのカテゴリがありません: id={}" , id));
+    }
+    Ok(violations)
+}
+// 問合せ結果のvalue列を返す
+async fn query_values(manager: &SchemaManager<'_> , sql: String) -> Result<Vec<String> , DbErr> {
+    let rows = manager.get_connection().query_all(Statement::from_string(manager.get_database_backend() , sql)).await?;
+    rows.iter().map(|row| row.try_get::<String>("" , "value")).collect()
+}
+// SQLを実行する
+async fn execute(manager: &SchemaManager<'_> , sql: String) -> Result<(), DbErr> {
+    manager.get_connection().execute(Statement::from_string(manager.get_database_backend() , sql)).await?;
+    Ok(())
+}
+
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+///
+/// リフレッシュトークンと失効したアクセストークンのテーブルを作成する
+///
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // リフレッシュトークンテーブル(トークン本体ではなくハッシュ値を保存する)
+        manager.create_table(Table::create()
+            .table(RefreshToken::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(RefreshToken::Id).integer().not_null().auto_increment().primary_key())
+            .col(ColumnDef::new(RefreshToken::TokenHash).string_len(128).not_null().unique_key())
+            .col(ColumnDef::new(RefreshToken::FamilyId).string_len(40).not_null())
+            .col(ColumnDef::new(RefreshToken::UserId).string_len(40).not_null())
+            .col(ColumnDef::new(RefreshToken::ExpiresAt).big_integer().not_null())
+            .col(ColumnDef::new(RefreshToken::Revoked).boolean().not_null().default(false))
+            .col(ColumnDef::new(RefreshToken::ReplacedBy).string_len(128))
+            .to_owned()).await?;
+        // ファミリー単位の失効で利用する
+        manager.create_index(Index::create()
+            .name("refresh_token_family_idx")
+            .table(RefreshToken::Table)
+            .col(RefreshToken::FamilyId)
+            .if_not_exists()
+            .to_owned()).await?;
+        // 失効したアクセストークンテーブル
+        manager.create_table(Table::create()
+            .table(RevokedToken::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(RevokedToken::Jti).string_len(40).not_null().primary_key())
+            .col(ColumnDef::new(RevokedToken::ExpiresAt).big_integer().not_null())
+            .to_owned()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RevokedToken::Table).if_exists().to_owned()).await?;
+        manager.drop_table(Table::drop().table(RefreshToken::Table).if_exists().to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum RefreshToken {
+    Table ,
+    Id ,
+    TokenHash ,
+    FamilyId ,
+    UserId ,
+    ExpiresAt ,
+    Revoked ,
+    ReplacedBy
+}
+#[derive(Iden)]
+enum RevokedToken {
+    Table ,
+    Jti ,
+    ExpiresAt
+}
@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend, Statement};
+
+/// 単価の検査制約名
+const PRICE_CHECK: &str = "product_price_check";
+/// 適用時の値オブジェクトの最大文字数(カテゴリ名,商品名,ユーザー名,メールアドレス)
+/// 適用済のデータベースと同じスキーマになるよう、値オブジェクトの定義が変わってもこの値は変更しない
+const RULE_LENGTHS: [u32; 4] = [20 , 20 , 20 , 36];
+/// 適用時の単価の範囲
+const PRICE_RANGE: (i32 , i32) = (50 , 10000);
+
+///
+/// 列の長さと単価の範囲を適用時の値オブジェクトの制約に合わせる
+/// 以降の制約の変更は新しいマイグレーションで適用する
+/// SQLiteは列の変更ができず、列の長さも検査しないため何もしない
+///
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DatabaseBackend::Sqlite {
+            return Ok(());
+        }
+        for statement in alter_columns(RULE_LENGTHS) {
+            manager.alter_table(statement).await?;
+        }
+        let check = format!("ALTER TABLE product ADD CONSTRAINT {} CHECK (price BETWEEN {} AND {})" ,
+            PRICE_CHECK , PRICE_RANGE.0 , PRICE_RANGE.1);
+        manager.get_connection().execute(Statement::from_string(manager.get_database_backend() , check)).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DatabaseBackend::Sqlite {
+            return Ok(());
+        }
+        let check = format!("ALTER TABLE product DROP CONSTRAINT IF EXISTS {}" , PRICE_CHECK);
+        manager.get_connection().execute(Statement::from_string(manager.get_database_backend() , check)).await?;
+        // 変更前(m20221001_000001_create_tables)の長さに戻す
+        for statement in alter_columns([20 , 30 , 30 , 50]) {
+            manager.alter_table(statement).await?;
+        }
+        Ok(())
+    }
+}
+
+// 文字列の列の長さを変更する
+fn alter_columns(lengths: [u32; 4]) -> Vec<TableAlterStatement> {
+    let [category_name , product_name , user_name , mail] = lengths;
+    vec![
+        Table::alter().table(ProductCategory::Table)
+            .modify_column(ColumnDef::new(ProductCategory::Name).string_len(category_name).not_null())
+            .to_owned() ,
+        Table::alter().table(Product::Table)
+            .modify_column(ColumnDef::new(Product::Name).string_len(product_name).not_null())
+            .to_owned() ,
+        Table::alter().table(User::Table)
+            .modify_column(ColumnDef::new(User::UserName).string_len(user_name).not_null())
+            .to_owned() ,
+        Table::alter().table(User::Table)
+            .modify_column(ColumnDef::new(User::Mail).string_len(mail).not_null())
+            .to_owned()
+    ]
+}
+
+#[derive(Iden)]
+enum ProductCategory {
+    Table ,
+    Name
+}
+#[derive(Iden)]
+enum Product {
+    Table ,
+    Name
+}
+// 列名はテーブル定義に合わせる
+#[allow(clippy::enum_variant_names)]
+#[derive(Iden)]
+enum User {
+    Table ,
+    UserName ,
+    Mail
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 列の長さは適用時の値オブジェクトの最大文字数になる
+    #[test]
+    fn alter_columns_to_frozen_lengths() {
+        let statements: Vec<String> = alter_columns(RULE_LENGTHS).iter()
+            .map(|statement| statement.to_string(PostgresQueryBuilder)).collect();
+        for (statement , (column , length)) in statements.iter().zip([
+            ("name" , 20) , ("name" , 20) , ("user_name" , 20) , ("mail" , 36)]) {
+            assert!(statement.contains(&format!("ALTER COLUMN \"{}\" TYPE varchar({})" , column , length)) , "{}" , statement);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend, Statement};
+
+/// メールアドレスの長さ(RFC 5321のパスの上限)
+const MAIL_LENGTH: u32 = 254;
+/// 変更前(m20221020_000001_apply_value_rules)のメールアドレスの長さ
+const PREVIOUS_MAIL_LENGTH: u32 = 36;
+
+///
+/// メールアドレスの列をRFCの最大長にし、登録済みのドメインを正規化する
+/// ドメインは値オブジェクトと同じUTS #46の変換で小文字とPunycodeにする
+/// 正規化すると重複するメールアドレスがある場合は変更せずにエラーを返す
+/// SQLiteは列の変更ができず、列の長さも検査しないため何もしない
+///
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DatabaseBackend::Sqlite {
+            return Ok(());
+        }
+        let changes = normalized_changes(&select_mails(manager).await?)?;
+        manager.alter_table(alter_mail(MAIL_LENGTH)).await?;
+        for (id , mail) in changes {
+            manager.get_connection().execute(Statement::from_sql_and_values(manager.get_database_backend() ,
+                r#"UPDATE "user" SET mail = $1 WHERE id = $2"# , vec![mail.into() , id.into()])).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DatabaseBackend::Sqlite {
+            return Ok(());
+        }
+        // 正規化したドメインは元に戻さない、変更前の長さを超えるメールアドレスがある場合はエラーを返す
+        let too_long: Vec<String> = select_mails(manager).await?.into_iter()
+            .filter(|(_ , mail)| mail.chars().count() > PREVIOUS_MAIL_LENGTH as usize)
+            .map(|(id , mail)| format!("id={}: {}" , id , mail)).collect();
+        if !too_long.is_empty() {
+            return Err(DbErr::Migration(format!("{}文字を超えるメールアドレスを修正してください。\n{}" ,
+                PREVIOUS_MAIL_LENGTH , too_long.join("\n"))));
+        }
+        manager.alter_table(alter_mail(PREVIOUS_MAIL_LENGTH)).await?;
+        Ok(())
+    }
+}
+
+// 登録済みのユーザー番号とメールアドレス
+async fn select_mails(manager: &SchemaManager<'_>) -> Result<Vec<(i32 , String)> , DbErr> {
+    let rows = manager.get_connection().query_all(Statement::from_string(manager.get_database_backend() ,
+        String::from(r#"SELECT id , mail FROM "user" WHERE mail IS NOT NULL ORDER BY id"#))).await?;
+    rows.iter().map(|row| Ok((row.try_get::<i32>("" , "id")? , row.try_get::<String>("" , "mail")?))).collect()
+}
+// ドメインを正規化したメールアドレス、変換できないドメインはそのまま返す
+fn normalize(mail: &str) -> String {
+    match mail.rsplit_once('@') {
+        Some((local_part , domain)) => match idna::domain_to_ascii(domain) {
+            Ok(domain) => format!("{}@{}" , local_part , domain) ,
+            Err(_) => mail.to_string()
+        },
+        None => mail.to_string()
+    }
+}
+// 正規化で変わるユーザー番号とメールアドレスを返す、重複や長さの超過がある場合はエラーを返す
+fn normalized_changes(mails: &[(i32 , String)]) -> Result<Vec<(i32 , String)> , DbErr> {
+    let mut normalized: BTreeMap<String , Vec<i32>> = BTreeMap::new();
+    for (id , mail) in mails {
+        normalized.entry(normalize(mail)).or_default().push(*id);
+    }
+    let mut errors: Vec<String> = normalized.iter().filter(|(_ , ids)| ids.len() > 1)
+        .map(|(mail , ids)| format!("{}が重複します: id={:?}" , mail , ids)).collect();
+    errors.extend(normalized.keys().filter(|mail| mail.chars().count() > MAIL_LENGTH as usize)
+        .map(|mail| format!("{}文字を超えます: {}" , MAIL_LENGTH , mail)));
+    if !errors.is_empty() {
+        return Err(DbErr::Migration(format!("正規化したメールアドレスを登録できません。\n{}" , errors.join("\n"))));
+    }
+    Ok(mails.iter().filter_map(|(id , mail)| {
+        let normalized = normalize(mail);
+        (normalized != *mail).then_some((*id , normalized))
+    }).collect())
+}
+// メールアドレスの列の長さを変更する
+fn alter_mail(length: u32) -> TableAlterStatement {
+    Table::alter().table(User::Table)
+        .modify_column(ColumnDef::new(User::Mail).string_len(length).not_null())
+        .to_owned()
+}
+
+#[derive(Iden)]
+enum User {
+    Table ,
+    Mail
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::values::users::Mail;
+    use crate::domain::values::ValueInto;
+
+    // ドメインは値オブジェクトと同じ値に正規化する
+    #[test]
+    fn normalize_like_value_object() {
+        for mail in ["yamada@Sample.COM" , "user003@例え.jp" , "User.Name@EXAMPLE.co.jp"] {
+            assert_eq!(normalize(mail) , Mail::try_from(mail.to_string()).unwrap().value() , "{}" , mail);
+        }
+        assert_eq!(normalize("dummy") , "dummy");
+    }
+
+    // 正規化で重複するメールアドレスは変更しない
+    #[test]
+    fn changes_and_collisions() {
+        let mails = vec![(1 , String::from("yamada@sample.com")) , (2 , String::from("suzuki@Sample.com")) ,
+                         (3 , String::from("tanaka@例え.jp"))];
+        assert_eq!(normalized_changes(&mails).unwrap() ,
+                   vec![(2 , String::from("suzuki@sample.com")) , (3 , String::from("tanaka@xn--r8jz45g.jp"))]);
+        let mails = vec![(1 , String::from("yamada@sample.com")) , (2 , String::from("yamada@SAMPLE.com"))];
+        let error = normalized_changes(&mails).err().unwrap();
+        assert!(error.to_string().contains("yamada@sample.comが重複します: id=[1, 2]") , "{}" , error);
+    }
+}
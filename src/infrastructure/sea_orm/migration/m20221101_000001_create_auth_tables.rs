@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+/// 適用時のユーザー名の最大文字数、値オブジェクトの定義が変わってもこの値は変更しない
+const USER_NAME_LENGTH: u32 = 20;
+
+///
+/// 認証の失敗回数と認証イベントのテーブルを作成する
+///
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 認証の失敗回数テーブル(キーはuser:ユーザー名,client:クライアントの識別子)
+        manager.create_table(Table::create()
+            .table(LoginAttempt::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(LoginAttempt::Key).string_len(80).not_null().primary_key())
+            .col(ColumnDef::new(LoginAttempt::Failures).integer().not_null().default(0))
+            .col(ColumnDef::new(LoginAttempt::LastFailedAt).big_integer().not_null())
+            .col(ColumnDef::new(LoginAttempt::LockedUntil).big_integer())
+            .to_owned()).await?;
+        // 認証イベントテーブル
+        manager.create_table(Table::create()
+            .table(AuthEvent::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(AuthEvent::Id).integer().not_null().auto_increment().primary_key())
+            .col(ColumnDef::new(AuthEvent::UserName).string_len(USER_NAME_LENGTH).not_null())
+            .col(ColumnDef::new(AuthEvent::UserId).string_len(40))
+            .col(ColumnDef::new(AuthEvent::Client).string_len(64).not_null())
+            .col(ColumnDef::new(AuthEvent::Outcome).string_len(10).not_null())
+            .col(ColumnDef::new(AuthEvent::OccurredAt).big_integer().not_null())
+            .to_owned()).await?;
+        // ユーザー名ごとの履歴の参照で利用する
+        manager.create_index(Index::create()
+            .name("auth_event_user_name_idx")
+            .table(AuthEvent::Table)
+            .col(AuthEvent::UserName)
+            .col(AuthEvent::OccurredAt)
+            .if_not_exists()
+            .to_owned()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(AuthEvent::Table).if_exists().to_owned()).await?;
+        manager.drop_table(Table::drop().table(LoginAttempt::Table).if_exists().to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum LoginAttempt {
+    Table ,
+    Key ,
+    Failures ,
+    LastFailedAt ,
+    LockedUntil
+}
+#[derive(Iden)]
+enum AuthEvent {
+    Table ,
+    Id ,
+    UserName ,
+    UserId ,
+    Client ,
+    Outcome ,
+    OccurredAt
+}
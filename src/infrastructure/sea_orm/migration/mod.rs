@@ -0,0 +1,60 @@
+//! スキーマのマイグレーションとサンプルデータの登録
+use sea_orm_migration::prelude::*;
+
+mod m20221001_000001_create_tables;
+mod m20221001_000002_add_legacy_constraints;
+mod m20221015_000001_create_token_tables;
+mod m20221020_000001_apply_value_rules;
+mod m20221025_000001_normalize_mail;
+mod m20221101_000001_create_auth_tables;
+pub mod seed;
+
+///
+/// マイグレーションの実行順序を定義する
+///
+pub struct Migrator;
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        // 未適用のマイグレーションは適用済のものより前にあっても実行される
+        vec![Box::new(m20221001_000001_create_tables::Migration) ,
+             Box::new(m20221001_000002_add_legacy_constraints::Migration) ,
+             Box::new(m20221015_000001_create_token_tables::Migration) ,
+             Box::new(m20221020_000001_apply_value_rules::Migration) ,
+             Box::new(m20221025_000001_normalize_mail::Migration) ,
+             Box::new(m20221101_000001_create_auth_tables::Migration)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend, Statement};
+    use crate::config::AppConfig;
+    use crate::domain::values::categories::CategoryName;
+    use crate::domain::values::products::{ProductName, ProductPrice};
+    use crate::domain::values::users::{Mail, UserName};
+    use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
+
+    // マイグレーションを適用したスキーマは現在の値オブジェクトの制約と一致する
+    #[actix::test]
+    async fn schema_follows_value_rules() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        for (table , column , length) in [
+            ("product_category" , "name" , CategoryName::LENGTH.max) ,
+            ("product" , "name" , ProductName::LENGTH.max) ,
+            ("user" , "user_name" , UserName::LENGTH.max) ,
+            ("user" , "mail" , Mail::LENGTH.max) ,
+            ("auth_event" , "user_name" , UserName::LENGTH.max)] {
+            let row = conn.query_one(Statement::from_sql_and_values(DatabaseBackend::Postgres ,
+                "SELECT character_maximum_length AS length FROM information_schema.columns WHERE table_name = $1 AND column_name = $2" ,
+                vec![table.into() , column.into()])).await?.unwrap();
+            assert_eq!(row.try_get::<i32>("" , "length")? as usize , length , "{}.{}" , table , column);
+        }
+        let row = conn.query_one(Statement::from_string(DatabaseBackend::Postgres ,
+            String::from("SELECT pg_get_constraintdef(oid) AS definition FROM pg_constraint WHERE conname = 'product_price_check'"))).await?.unwrap();
+        assert_eq!(row.try_get::<String>("" , "definition")? ,
+                   format!("CHECK (((price >= {}) AND (price <= {})))" , ProductPrice::RANGE.min , ProductPrice::RANGE.max));
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, TransactionTrait};
+use sea_orm::ActiveValue::{NotSet, Set};
+use crate::Result;
+use crate::infrastructure::fixtures::{CATEGORIES, PRODUCTS, USERS};
+use crate::infrastructure::sea_orm::models::{product, product_category, user};
+use crate::infrastructure::sea_orm::models::prelude::{SeaOrmProduct, SeaOrmProductCategory, SeaOrmUser};
+
+///
+/// サンプルデータ(infrastructure::fixtures)を登録する
+/// 名称で登録済か判定するため、何度実行しても同じ結果になる
+///
+pub struct Seeder;
+impl Seeder {
+    /// サンプルデータを登録し、新たに登録した件数を返す
+    pub async fn run(db: &DatabaseConnection) -> Result<u64> {
+        let tran = db.begin().await?;
+        let mut count = 0;
+        // サンプルのカテゴリ番号と登録されたカテゴリ番号の対応
+        let mut category_ids = HashMap::new();
+        for (id , name) in CATEGORIES {
+            let category = SeaOrmProductCategory::find()
+                .filter(product_category::Column::Name.eq(name)).one(&tran).await?;
+            let category = match category {
+                Some(category) => category ,
+                None => {
+                    count += 1;
+                    product_category::ActiveModel{ id: NotSet , name: Set(Some(name.to_string())) }
+                        .insert(&tran).await?
+                }
+            };
+            category_ids.insert(id , category.id);
+        }
+        for (_ , name , price , category_id) in PRODUCTS {
+            if Self::exists_product(&tran , name).await? {
+                continue;
+            }
+            product::ActiveModel{
+                id: NotSet ,
+                name: Set(Some(name.to_string())) ,
+                price: Set(Some(price)) ,
+                category_id: Set(category_ids.get(&category_id).copied())
+            }.insert(&tran).await?;
+            count += 1;
+        }
+        for (user_id , user_name , password , mail) in USERS {
+            if Self::exists_user(&tran , user_name).await? {
+                continue;
+            }
+            let model = user::ActiveModel{
+                id: NotSet ,
+                user_id: Set(Some(user_id.to_string())) ,
+                user_name: Set(Some(user_name.to_string())) ,
+                password: Set(Some(password.to_string())) ,
+                mail: Set(Some(mail.to_string()))
+            };
+            SeaOrmUser::insert(model).exec(&tran).await?;
+            count += 1;
+        }
+        tran.commit().await?;
+        Ok(count)
+    }
+    // 同じ名称の商品が登録済か確認する
+    async fn exists_product(tran: &DatabaseTransaction , name: &str) -> Result<bool> {
+        let product = SeaOrmProduct::find()
+            .filter(product::Column::Name.eq(name)).one(tran).await?;
+        Ok(product.is_some())
+    }
+    // 同じ名前のユーザーが登録済か確認する
+    async fn exists_user(tran: &DatabaseTransaction , user_name: &str) -> Result<bool> {
+        let user = SeaOrmUser::find()
+            .filter(user::Column::UserName.eq(user_name)).one(tran).await?;
+        Ok(user.is_some())
+    }
+}
+
+#[cfg(all(test , feature = "sqlite"))]
+mod tests {
+    use sea_orm::TransactionTrait;
+    use crate::domain::entities::User;
+    use crate::domain::values::pages::PageRequest;
+    use crate::domain::values::products::{ProductName, ProductPrice, ProductSearchCriteria, ProductSortKey};
+    use crate::domain::values::users::{Mail, Password, UserName};
+    use crate::domain::values::ValueInto;
+    use crate::infrastructure::pool::PoolProvider;
+    use crate::infrastructure::sea_orm::pool_impl::SqliteMemoryPool;
+    use crate::infrastructure::sea_orm::repositories::category::CategoryRepositoryImpl;
+    use crate::infrastructure::sea_orm::repositories::product::ProductRepositoryImpl;
+    use crate::infrastructure::sea_orm::repositories::user::UserRepositoryImpl;
+    use super::*;
+
+    // 二回目の実行では何も登録しない
+    #[actix::test]
+    async fn run() -> anyhow::Result<()> {
+        let conn = SqliteMemoryPool::get(&Default::default()).await?;
+        assert_eq!(Seeder::run(&conn).await? , 0);
+        Ok(())
+    }
+
+    // PostgreSQLと同じRepositoryがSQLiteでも動作する
+    #[actix::test]
+    async fn repositories() -> anyhow::Result<()> {
+        let conn = SqliteMemoryPool::get(&Default::default()).await?;
+        let tran = conn.begin().await?;
+        let categories = CategoryRepositoryImpl::new().select_all(&tran).await?;
+        assert_eq!(categories.len() , 3);
+        let repository = ProductRepositoryImpl::new();
+        let products = repository.select_by_criteria(
+            &tran , &ProductSearchCriteria::keyword(&ProductName::try_from(String::from("ボールペン"))?) ,
+            &PageRequest::new(2 , 4)? , &ProductSortKey::PriceDesc).await?;
+        assert_eq!(products.total_count , 6);
+        assert_eq!(products.items.len() , 2);
+        assert_eq!(products.items[0].category.as_ref().unwrap().name.value() , "文房具");
+        let mut product = products.items[0].clone();
+        product.price = ProductPrice::try_from(150)?;
+        repository.update(&tran , &product).await?;
+        let repository = UserRepositoryImpl::new();
+        let user = User::new(UserName::try_from(String::from("user003"))? ,
+                             Password::try_from(String::from("pass003"))? ,
+                             Mail::try_from(String::from("user003@sample.com"))?)?;
+        repository.insert(&tran , &user).await?;
+        assert!(repository.exists_by_name(&tran , &user.user_name).await?);
+        tran.rollback().await?;
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 pub mod models;
 pub mod repositories;
 pub mod converter_impl;
-pub mod pool_impl;
\ No newline at end of file
+pub mod pool_impl;
+pub mod transaction_impl;
+pub mod migration;
\ No newline at end of file
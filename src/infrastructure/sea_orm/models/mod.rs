@@ -1,6 +1,10 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.9.1
 pub mod prelude;
+pub mod auth_event;
+pub mod login_attempt;
 pub mod product;
 pub mod product_category;
+pub mod refresh_token;
+pub mod revoked_token;
 pub mod user;
 
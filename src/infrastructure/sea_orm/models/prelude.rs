@@ -1,5 +1,9 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.9.1
 
+pub use super::auth_event::Entity as SeaOrmAuthEvent;
+pub use super::login_attempt::Entity as SeaOrmLoginAttempt;
 pub use super::product::Entity as SeaOrmProduct;
 pub use super::product_category::Entity as SeaOrmProductCategory;
+pub use super::refresh_token::Entity as SeaOrmRefreshToken;
+pub use super::revoked_token::Entity as SeaOrmRevokedToken;
 pub use super::user::Entity as SeaOrmUser;
@@ -1,25 +1,97 @@
-use std::env;
 use std::sync::Arc;
-use std::time::Duration;
-use dotenv::dotenv;
 use async_trait::async_trait;
 use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use tokio::sync::OnceCell;
+use crate::{AppError, Result};
+use crate::config::DatabaseConfig;
 use crate::infrastructure::pool::PoolProvider;
+#[cfg(feature = "sqlite")]
+use sea_orm_migration::MigratorTrait;
+#[cfg(feature = "sqlite")]
+use crate::infrastructure::sea_orm::migration::{Migrator, seed::Seeder};
 
+// プロセス内で共有するコネクションプール
+static POOL: OnceCell<Arc<DatabaseConnection>> = OnceCell::const_new();
+
+///
+/// SeaORMのコネクションプール
+/// getは初回呼出し時に指定された設定で接続し、以降は同じプールを返す
+///
 pub struct SeaOrmPool;
+impl SeaOrmPool {
+    /// 指定された設定で新しいコネクションプールを生成する
+    pub async fn connect(config: &DatabaseConfig) -> Result<DatabaseConnection> {
+        let errors = config.errors();
+        if !errors.is_empty() {
+            return Err(AppError::from(anyhow::anyhow!(errors.join("\n"))));
+        }
+        let mut opt = ConnectOptions::new(config.url.clone());
+        opt.max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .connect_timeout(config.connect_timeout())
+            .idle_timeout(config.idle_timeout())
+            .max_lifetime(config.max_lifetime())
+            .sqlx_logging(config.sqlx_logging != "off")
+            .sqlx_logging_level(config.logging_level());
+        let mut connection = match Database::connect(opt).await {
+            Ok(connection) => connection ,
+            Err(error) => return Err(AppError::from(error))
+        };
+        // 実行に時間がかかったSQLを警告ログに出力する
+        if let Some(threshold) = config.slow_statement_threshold() {
+            connection.set_metric_callback(move |info| {
+                if info.elapsed >= threshold {
+                    log::warn!("slow statement: {:?} elapsed: {:?}" , info.statement , info.elapsed);
+                }
+            });
+        }
+        Ok(connection)
+    }
+}
 #[async_trait]
 impl PoolProvider<Arc<DatabaseConnection>> for SeaOrmPool{
-    async fn get() -> Arc<DatabaseConnection> {
-        dotenv().ok();
-        let database_url = env::var("DATABASE_URL").unwrap();
-        let mut opt = ConnectOptions::new(database_url);
-        opt.max_connections(100)
-            .min_connections(5)
-            .connect_timeout(Duration::from_secs(8))
-            .idle_timeout(Duration::from_secs(8))
-            .max_lifetime(Duration::from_secs(8))
-            .sqlx_logging(true);
-        let connection = Database::connect(opt).await.unwrap();
-        Arc::new(connection)
+    async fn get(config: &DatabaseConfig) -> Result<Arc<DatabaseConnection>> {
+        let pool = POOL.get_or_try_init(|| async {
+            Ok::<_ , AppError>(Arc::new(Self::connect(config).await?))
+        }).await?;
+        Ok(pool.clone())
+    }
+}
+
+///
+/// サンプルデータを登録したSQLiteのインメモリデータベース
+/// 接続ごとに別のデータベースになるため、接続数は1に固定する
+/// テスト用のため、getは呼出しごとに新しいデータベースを返す
+///
+#[cfg(feature = "sqlite")]
+pub struct SqliteMemoryPool;
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl PoolProvider<Arc<DatabaseConnection>> for SqliteMemoryPool{
+    async fn get(_: &DatabaseConfig) -> Result<Arc<DatabaseConnection>> {
+        let mut opt = ConnectOptions::new(String::from("sqlite::memory:"));
+        opt.max_connections(1)
+            .min_connections(1)
+            .sqlx_logging(false);
+        let connection = Database::connect(opt).await?;
+        Migrator::up(&connection , None).await?;
+        Seeder::run(&connection).await?;
+        Ok(Arc::new(connection))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    // 2回目以降は同じプールを返す
+    #[actix::test]
+    async fn get() -> anyhow::Result<()> {
+        let config = AppConfig::load()?;
+        let pool1 = SeaOrmPool::get(&config.database).await?;
+        let pool2 = SeaOrmPool::get(&config.database).await?;
+        assert!(Arc::ptr_eq(&pool1 , &pool2));
+        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -17,6 +17,7 @@ use crate::infrastructure::sea_orm::models::prelude::SeaOrmProductCategory;
 pub struct CategoryRepositoryImpl;
 impl CategoryRepositoryImpl {
     //  Repositoryの生成
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> Arc<dyn CategoryRepository<Transaction=DatabaseTransaction>> {
         Arc::new(Self{})
     }
@@ -36,7 +37,7 @@ impl CategoryRepository for CategoryRepositoryImpl{
         match SeaOrmProductCategory::find_by_id(id.value()).one(tran).await{
             Ok(option_model) => {
                 match option_model{
-                    Some(model) => Ok(CategoryConverter::model_to_entity(&model).ok()) ,
+                    Some(model) => Ok(Some(CategoryConverter::model_to_entity(&model)?)) ,
                     None => Ok(None)
                 }
             },
@@ -47,14 +48,14 @@ impl CategoryRepository for CategoryRepositoryImpl{
 
 #[cfg(test)]
 mod tests {
-    use crate::infrastructure::pool::PoolProvider;
+    use crate::config::AppConfig;
     use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
     use sea_orm::TransactionTrait;
     use super::*;
 
     #[actix::test]
     async fn select_all() -> Result<()> {
-        let conn = SeaOrmPool::get().await;
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
         let tran = conn.begin().await.unwrap();
 
         let repository = CategoryRepositoryImpl::new();
@@ -67,14 +68,15 @@ mod tests {
 
     #[actix::test]
     async fn select_by_id() -> Result<()> {
-        let conn = SeaOrmPool::get().await;
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
         let tran = conn.begin().await.unwrap();
 
         let repository = CategoryRepositoryImpl::new();
-        let category = repository.select_by_id(&tran , CategoryId::try_from(1)?).await.unwrap();
+        let category = repository.select_by_id(&tran , &CategoryId::try_from(1)?).await?;
         println!("{:?}" , category);
-        let err = repository.select_by_id(&tran , CategoryId::try_from(10)?).await.err().unwrap();
-        println!("{:?}" , err);
+        assert!(category.is_some());
+        let category = repository.select_by_id(&tran , &CategoryId::try_from(10)?).await?;
+        assert!(category.is_none());
         Ok(())
     }
 }
\ No newline at end of file
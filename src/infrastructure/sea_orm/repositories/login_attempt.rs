@@ -0,0 +1,167 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DatabaseTransaction, EntityTrait, QuerySelect, QueryTrait};
+use sea_orm::sea_query::OnConflict;
+use crate::{AppError, Result};
+use crate::domain::entities::{AuthEvent, LoginAttempt};
+use crate::domain::repositories::{AuthEventRepository, LoginAttemptRepository};
+use crate::infrastructure::converter::{ActiveModelGenerator, ModelAndEntity};
+use crate::infrastructure::sea_orm::converter_impl::{AuthEventConverter, LoginAttemptConverter};
+use crate::infrastructure::sea_orm::models::login_attempt;
+use crate::infrastructure::sea_orm::models::prelude::{SeaOrmAuthEvent, SeaOrmLoginAttempt};
+
+///
+/// 認証の失敗回数リポジトリの実装
+///
+pub struct LoginAttemptRepositoryImpl;
+impl LoginAttemptRepositoryImpl {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn LoginAttemptRepository<Transaction=DatabaseTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl LoginAttemptRepository for LoginAttemptRepositoryImpl {
+    type Transaction = DatabaseTransaction;
+    /// キーで失敗回数を取得する
+    async fn select_by_key(&self, tran: &Self::Transaction, key: &str) -> Result<Option<LoginAttempt>> {
+        match SeaOrmLoginAttempt::find_by_id(key.to_string()).one(tran).await {
+            Ok(Some(model)) => Ok(Some(LoginAttemptConverter::model_to_entity(&model)?)) ,
+            Ok(None) => Ok(None) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// キーで失敗回数を排他ロックして取得する、ない場合は0回で登録する
+    async fn select_for_update(&self, tran: &Self::Transaction, key: &str) -> Result<LoginAttempt> {
+        // 同時に登録しても一意制約違反にならないよう、登録済の場合は何もしない
+        // (sea-ormのexecは結果行のないRETURNINGを扱えないため文を直接実行する)
+        let insert = SeaOrmLoginAttempt::insert(LoginAttemptConverter::active_model(&LoginAttempt::new(key.to_string())))
+            .on_conflict(OnConflict::column(login_attempt::Column::Key).do_nothing().to_owned())
+            .build(tran.get_database_backend());
+        if let Err(error) = tran.execute(insert).await {
+            return Err(AppError::from(error));
+        }
+        // 他のトランザクションの更新はコミットまで待ち、コミット後の値を取得する
+        match SeaOrmLoginAttempt::find_by_id(key.to_string()).lock_exclusive().one(tran).await {
+            Ok(Some(model)) => LoginAttemptConverter::model_to_entity(&model) ,
+            Ok(None) => Err(AppError::from("認証の失敗回数を登録できませんでした。")) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 失敗回数を永続化する、登録済の場合は更新する
+    async fn save(&self, tran: &Self::Transaction, attempt: &LoginAttempt) -> Result<()> {
+        let upsert = SeaOrmLoginAttempt::insert(LoginAttemptConverter::active_model(attempt))
+            .on_conflict(OnConflict::column(login_attempt::Column::Key)
+                .update_columns([login_attempt::Column::Failures , login_attempt::Column::LastFailedAt ,
+                                 login_attempt::Column::LockedUntil]).to_owned())
+            .build(tran.get_database_backend());
+        match tran.execute(upsert).await {
+            Ok(_) => Ok(()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 失敗回数を削除する
+    async fn delete(&self, tran: &Self::Transaction, key: &str) -> Result<()> {
+        match SeaOrmLoginAttempt::delete_by_id(key.to_string()).exec(tran).await {
+            Ok(_) => Ok(()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+}
+
+///
+/// 認証イベントリポジトリの実装
+///
+pub struct AuthEventRepositoryImpl;
+impl AuthEventRepositoryImpl {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn AuthEventRepository<Transaction=DatabaseTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl AuthEventRepository for AuthEventRepositoryImpl {
+    type Transaction = DatabaseTransaction;
+    /// 認証イベントを永続化する
+    async fn insert(&self, tran: &Self::Transaction, event: &AuthEvent) -> Result<()> {
+        match SeaOrmAuthEvent::insert(AuthEventConverter::active_model(event)).exec(tran).await {
+            Ok(_) => Ok(()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use sea_orm::{ColumnTrait, QueryFilter, TransactionTrait};
+    use crate::config::AppConfig;
+    use crate::domain::entities::AuthOutcome;
+    use crate::domain::lockout_policy::LockoutPolicy;
+    use crate::domain::values::users::UserName;
+    use crate::infrastructure::sea_orm::models::auth_event;
+    use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
+
+    #[actix::test]
+    async fn save_and_delete() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await?;
+        let repository = LoginAttemptRepositoryImpl::new();
+        let mut attempt = LoginAttempt::new(String::from("client:repository-test"));
+        attempt.fail(1000 , 1 , &LockoutPolicy::default());
+        repository.save(&tran , &attempt).await?;
+        attempt.fail(1010 , 1 , &LockoutPolicy::default());
+        repository.save(&tran , &attempt).await?;
+        assert_eq!(repository.select_by_key(&tran , &attempt.key).await? , Some(attempt.clone()));
+        repository.delete(&tran , &attempt.key).await?;
+        assert!(repository.select_by_key(&tran , &attempt.key).await?.is_none());
+        tran.rollback().await?;
+        Ok(())
+    }
+
+    // 同時に失敗を数えても回数を取りこぼさず、登録も重複しない
+    #[actix::test]
+    async fn concurrent_failures() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let key = format!("client:concurrent-{}" , uuid::Uuid::new_v4());
+        let mut handles = Vec::new();
+        for _ in 0..8 {
+            let (conn , key) = (conn.clone() , key.clone());
+            handles.push(actix::spawn(async move {
+                let tran = conn.begin().await?;
+                let repository = LoginAttemptRepositoryImpl::new();
+                let mut attempt = repository.select_for_update(&tran , &key).await?;
+                attempt.fail(1000 , 100 , &LockoutPolicy::default());
+                repository.save(&tran , &attempt).await?;
+                tran.commit().await?;
+                anyhow::Ok(())
+            }));
+        }
+        for handle in handles {
+            handle.await??;
+        }
+        let tran = conn.begin().await?;
+        let repository = LoginAttemptRepositoryImpl::new();
+        let failures = repository.select_by_key(&tran , &key).await?.map(|attempt| attempt.failures);
+        repository.delete(&tran , &key).await?;
+        tran.commit().await?;
+        assert_eq!(failures , Some(8));
+        Ok(())
+    }
+
+    #[actix::test]
+    async fn insert_event() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await?;
+        let event = AuthEvent::new(&UserName::try_from(String::from("repository"))? , None ,
+                                   "192.0.2.1" , AuthOutcome::Failure , 1000);
+        AuthEventRepositoryImpl::new().insert(&tran , &event).await?;
+        let models = SeaOrmAuthEvent::find()
+            .filter(auth_event::Column::UserName.eq("repository")).all(&tran).await?;
+        assert_eq!(models.len() , 1);
+        assert_eq!(models[0].outcome , "failure");
+        tran.rollback().await?;
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 use async_trait::async_trait;
-use sea_orm::{ DatabaseTransaction, EntityTrait, QueryFilter, ColumnTrait, QueryOrder };
+use sea_orm::{ Condition, DatabaseTransaction, DbErr, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, Order, PaginatorTrait };
+use sea_orm::ActiveValue::Set;
 use crate::{AppError, Result};
+use crate::i18n::Message;
 use crate::domain::entities::{Characteristic, Product};
 use crate::domain::repositories::ProductRepository;
-use crate::domain::values::products::{ProductId, ProductName};
+use crate::domain::values::pages::{Page, PageRequest};
+use crate::domain::values::products::{KeywordMatch, ProductId, ProductName, ProductSearchCriteria, ProductSortKey};
 use crate::domain::values::ValueInto;
 use crate::infrastructure::converter::{ActiveModelGenerator, VecModelToVecEntity};
 use crate::infrastructure::sea_orm::converter_impl::ProductConverter;
@@ -18,23 +21,80 @@ use crate::infrastructure::sea_orm::models::prelude::SeaOrmProductCategory;
 pub struct ProductRepositoryImpl;
 impl ProductRepositoryImpl{
     // インスタンスをProductRepository型に変換して返す
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> Arc<dyn ProductRepository<Transaction=DatabaseTransaction>> {
         // インスタンスをスレッドセーフな参照カウンタArcにラップして返す
         Arc::new(Self{})
     }
+    // 検索条件をSeaOrmの条件に変換する
+    fn condition(criteria: &ProductSearchCriteria) -> Condition {
+        let mut condition = Condition::all();
+        // 商品名のキーワード AND/OR
+        if !criteria.keywords().is_empty() {
+            let mut keywords = match criteria.keyword_match() {
+                KeywordMatch::All => Condition::all() ,
+                KeywordMatch::Any => Condition::any()
+            };
+            for keyword in criteria.keywords() {
+                keywords = keywords.add(product::Column::Name.contains(keyword.as_str()));
+            }
+            condition = condition.add(keywords);
+        }
+        // 単価の範囲
+        if let Some(min_price) = criteria.min_price() {
+            condition = condition.add(product::Column::Price.gte(min_price));
+        }
+        if let Some(max_price) = criteria.max_price() {
+            condition = condition.add(product::Column::Price.lte(max_price));
+        }
+        // カテゴリ
+        if !criteria.category_ids().is_empty() {
+            condition = condition.add(product::Column::CategoryId.is_in(
+                criteria.category_ids().iter().map(|id| id.value())));
+        }
+        condition
+    }
+    // 並び順をソートする列と方向に変換する
+    fn order_by(sort: &ProductSortKey) -> (product::Column , Order) {
+        match sort {
+            ProductSortKey::IdAsc => (product::Column::Id , Order::Asc) ,
+            ProductSortKey::IdDesc => (product::Column::Id , Order::Desc) ,
+            ProductSortKey::NameAsc => (product::Column::Name , Order::Asc) ,
+            ProductSortKey::NameDesc => (product::Column::Name , Order::Desc) ,
+            ProductSortKey::PriceAsc => (product::Column::Price , Order::Asc) ,
+            ProductSortKey::PriceDesc => (product::Column::Price , Order::Desc)
+        }
+    }
 }
 #[async_trait]
 impl ProductRepository for ProductRepositoryImpl{
     type Transaction = sea_orm::DatabaseTransaction;
-    /// キーワード検索
-    async fn select_by_name_like(&self, tran: &Self::Transaction, keyword: &ProductName) -> Result<Vec<Product>> {
-        // 指定されたキーワードで問合せし、商品番号でソートした結果を取得する
-        match SeaOrmProduct::find().filter(product::Column::Name.contains(keyword.value().as_str()))
+    /// 検索条件による検索
+    async fn select_by_criteria(&self, tran: &Self::Transaction, criteria: &ProductSearchCriteria ,
+                                page: &PageRequest , sort: &ProductSortKey) -> Result<Page<Product>> {
+        // 指定された検索条件で問合せし、指定された並び順でソートする
+        let (column , order) = Self::order_by(sort);
+        let paginator = SeaOrmProduct::find().filter(Self::condition(criteria))
             .find_also_related(SeaOrmProductCategory)
+            .order_by(column , order)
+            // 同じ値の商品は商品番号順に並べる
             .order_by_asc(product::Column::Id)
-            .all(tran).await{
+            .paginate(tran , page.page_size() as usize);
+        // 全件数と全ページ数を取得する
+        let counts = match paginator.num_items_and_pages().await {
+            Ok(counts) => counts ,
+            Err(error) => return Err(AppError::from(error))
+        };
+        // 指定ページの商品を取得する(SeaOrmのページ番号は0から始まる)
+        match paginator.fetch_page((page.page() - 1) as usize).await{
             Ok(models) => // 結合で取得したモデルをEntityに変換して返す
-                ProductConverter::join_model_to_entities(&models) ,
+                Ok(Page{
+                    items: ProductConverter::join_model_to_entities(&models)? ,
+                    total_count: counts.number_of_items as u64 ,
+                    total_pages: counts.number_of_pages as u64 ,
+                    page: page.page() ,
+                    page_size: page.page_size()
+                }) ,
             Err(error) => // SeaOrmからのエラーをAppErrorにラップして返す
                 Err(AppError::from(error))
         }
@@ -49,7 +109,7 @@ impl ProductRepository for ProductRepositoryImpl{
                 // Entityのクローンを取得する
                 let mut new_product = product.clone();
                 // 返されたIdをProductIdに格納する
-                let product_id = ProductId::try_from(new_id.last_insert_id as i32)?;
+                let product_id = ProductId::try_from(new_id.last_insert_id)?;
                 // ProductIdを変更する
                 new_product.change(&product_id)?;
                 // 永続化結果を返す
@@ -66,27 +126,60 @@ impl ProductRepository for ProductRepositoryImpl{
             Err(error) => Err(AppError::from(error))
         }
     }
+    /// 商品番号で商品を取得する
+    async fn select_by_id(&self, tran: &Self::Transaction, id: &ProductId) -> Result<Option<Product>> {
+        match SeaOrmProduct::find_by_id(id.value())
+            .find_also_related(SeaOrmProductCategory)
+            .all(tran).await{
+            Ok(models) => // 結合で取得したモデルをEntityに変換して返す
+                Ok(ProductConverter::join_model_to_entities(&models)?.into_iter().next()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 商品の変更
+    async fn update(&self, tran: &Self::Transaction, product: &Product) -> Result<Product> {
+        // 渡されたEntityをModelに変換し、変更対象の商品番号を設定する
+        let mut update_product = ProductConverter::active_model(product);
+        update_product.id = Set(product.get().value());
+        match SeaOrmProduct::update(update_product).exec(tran).await{
+            Ok(_) => Ok(product.clone()) ,
+            Err(DbErr::RecordNotFound(_)) =>
+                Err(AppError::NotFound(Message::new("product.not_found").with_param("id" , product.get().value()))) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 商品の削除
+    async fn delete(&self, tran: &Self::Transaction, id: &ProductId) -> Result<bool> {
+        match SeaOrmProduct::delete_by_id(id.value()).exec(tran).await{
+            Ok(result) => Ok(result.rows_affected > 0) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
 }
 #[cfg(test)]
 mod tests{
-    use crate::infrastructure::pool::PoolProvider;
+    use crate::config::AppConfig;
     use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
     use sea_orm::TransactionTrait;
+    use crate::domain::values::categories::CategoryId;
+    use crate::domain::values::products::ProductPrice;
     use super::*;
 
     #[actix::test]
     async fn select_by_name() -> Result<()> {
-        let conn = SeaOrmPool::get().await;
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
         let tran = conn.begin().await.unwrap();
         let repository = ProductRepositoryImpl::new();
-        let products = repository.select_by_name_like(
-            &tran , ProductName::try_from(String::from("マウス"))?).await?;
-        for product in products{
+        let products = repository.select_by_criteria(
+            &tran , &ProductSearchCriteria::keyword(&ProductName::try_from(String::from("ゲーミングマウス"))?) ,
+            &PageRequest::default() , &ProductSortKey::default()).await?;
+        for product in products.items{
             println!("{:?}" , product);
         }
-        let products = repository.select_by_name_like(
-            &tran , ProductName::try_from(String::from("xxxx"))?).await?;
-        if products.is_empty(){
+        let products = repository.select_by_criteria(
+            &tran , &ProductSearchCriteria::keyword(&ProductName::try_from(String::from("xxxx"))?) ,
+            &PageRequest::default() , &ProductSortKey::default()).await?;
+        if products.items.is_empty(){
             println!("Empty!!");
         }else{
             println!("Not Empty!!");
@@ -95,16 +188,108 @@ mod tests{
         Ok(())
     }
     #[actix::test]
+    async fn select_by_name_page() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await.unwrap();
+        let repository = ProductRepositoryImpl::new();
+        // ボールペンは6件、1ページ4件で2ページ目を単価の降順で取得する
+        let products = repository.select_by_criteria(
+            &tran , &ProductSearchCriteria::keyword(&ProductName::try_from(String::from("ボールペン"))?) ,
+            &PageRequest::new(2 , 4)? , &ProductSortKey::PriceDesc).await?;
+        assert_eq!(products.total_count , 6);
+        assert_eq!(products.total_pages , 2);
+        assert_eq!(products.items.len() , 2);
+        assert!(products.items.iter().all(|product| product.price.value() == 100));
+        // 単価が同じ商品は商品番号順に並ぶ
+        assert!(products.items[0].get().value() < products.items[1].get().value());
+        // 上限のページ番号は空のページになる
+        let page = PageRequest::new(PageRequest::max_page(100) , 100)?;
+        let products = repository.select_by_criteria(
+            &tran , &ProductSearchCriteria::keyword(&ProductName::try_from(String::from("ボールペン"))?) ,
+            &page , &ProductSortKey::PriceDesc).await?;
+        assert!(products.items.is_empty());
+        Ok(())
+    }
+    #[actix::test]
+    async fn select_by_criteria() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await.unwrap();
+        let repository = ProductRepositoryImpl::new();
+        let keywords = vec![String::from("ボールペン") , String::from("(黒)")];
+        // すべてのキーワードを含む
+        let criteria = ProductSearchCriteria::new(keywords.clone() , KeywordMatch::All ,
+                                                  None , None , vec![])?;
+        let products = repository.select_by_criteria(&tran , &criteria ,
+                                                     &PageRequest::default() , &ProductSortKey::default()).await?;
+        assert_eq!(products.total_count , 2);
+        // いずれかのキーワードを含む
+        let criteria = ProductSearchCriteria::new(keywords , KeywordMatch::Any ,
+                                                  None , None , vec![])?;
+        let products = repository.select_by_criteria(&tran , &criteria ,
+                                                     &PageRequest::default() , &ProductSortKey::default()).await?;
+        assert_eq!(products.total_count , 7);
+        // 単価の範囲とカテゴリ
+        let criteria = ProductSearchCriteria::new(vec![] , KeywordMatch::All ,
+                                                  Some(500) , Some(1000) ,
+                                                  vec![CategoryId::try_from(2)? , CategoryId::try_from(3)?])?;
+        let products = repository.select_by_criteria(&tran , &criteria ,
+                                                     &PageRequest::default() , &ProductSortKey::default()).await?;
+        assert_eq!(products.total_count , 7);
+        assert!(products.items.iter().all(|product|
+            (500..=1000).contains(&product.price.value()) &&
+                product.category.as_ref().unwrap().get().value() != 1));
+        Ok(())
+    }
+    #[actix::test]
     async fn exists() -> Result<()>{
-        let conn = SeaOrmPool::get().await;
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
         let tran = conn.begin().await.unwrap();
         let repository = ProductRepositoryImpl::new();
         let result  = repository.exists(&tran ,
-                                        ProductName::try_from(String::from("水性ボールペン(黒)"))?).await.unwrap();
+                                        &ProductName::try_from(String::from("水性ボールペン(黒)"))?).await.unwrap();
         println!("result = {:?}" , result);
         let result  = repository.exists(&tran ,
-                                        ProductName::try_from(String::from("水性ボールペン"))?).await.unwrap();
+                                        &ProductName::try_from(String::from("水性ボールペン"))?).await.unwrap();
         println!("result = {:?}" , result);
         Ok(())
     }
+    #[actix::test]
+    async fn select_by_id() -> Result<()>{
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await.unwrap();
+        let repository = ProductRepositoryImpl::new();
+        let product = repository.select_by_id(&tran , &ProductId::try_from(1)?).await?;
+        assert!(product.unwrap().category.is_some());
+        let product = repository.select_by_id(&tran , &ProductId::try_from(9999)?).await?;
+        assert!(product.is_none());
+        Ok(())
+    }
+    #[actix::test]
+    async fn update() -> Result<()>{
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await.unwrap();
+        let repository = ProductRepositoryImpl::new();
+        let mut product = repository.select_by_id(&tran , &ProductId::try_from(1)?).await?.unwrap();
+        product.name = ProductName::try_from(String::from("水性ボールペン(紫)"))?;
+        product.price = ProductPrice::try_from(150)?;
+        repository.update(&tran , &product).await?;
+        let result = repository.select_by_id(&tran , &ProductId::try_from(1)?).await?.unwrap();
+        assert_eq!(result.name , product.name);
+        assert_eq!(result.price , product.price);
+        product.change(&ProductId::try_from(9999)?)?;
+        assert!(repository.update(&tran , &product).await.is_err());
+        tran.rollback().await?;
+        Ok(())
+    }
+    #[actix::test]
+    async fn delete() -> Result<()>{
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await.unwrap();
+        let repository = ProductRepositoryImpl::new();
+        assert!(repository.delete(&tran , &ProductId::try_from(1)?).await?);
+        assert!(repository.select_by_id(&tran , &ProductId::try_from(1)?).await?.is_none());
+        assert!(!repository.delete(&tran , &ProductId::try_from(9999)?).await?);
+        tran.rollback().await?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseTransaction, EntityTrait, QueryFilter, QueryTrait};
+use sea_orm::ActiveValue::Set;
+use sea_orm::sea_query::{Expr, OnConflict};
+use crate::{AppError, Result};
+use crate::domain::entities::RefreshToken;
+use crate::domain::repositories::{RefreshTokenRepository, RevokedTokenRepository};
+use crate::infrastructure::converter::{ActiveModelGenerator, ModelAndEntity};
+use crate::infrastructure::sea_orm::converter_impl::RefreshTokenConverter;
+use crate::infrastructure::sea_orm::models::prelude::{SeaOrmRefreshToken, SeaOrmRevokedToken};
+use crate::infrastructure::sea_orm::models::{refresh_token, revoked_token};
+
+///
+/// リフレッシュトークンリポジトリの実装
+///
+pub struct RefreshTokenRepositoryImpl;
+impl RefreshTokenRepositoryImpl {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn RefreshTokenRepository<Transaction=DatabaseTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
+    type Transaction = DatabaseTransaction;
+    /// 新しいトークンを永続化する
+    async fn insert(&self, tran: &Self::Transaction, token: &RefreshToken) -> Result<()> {
+        match SeaOrmRefreshToken::insert(RefreshTokenConverter::active_model(token)).exec(tran).await {
+            Ok(_) => Ok(()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// ハッシュ値でトークンを取得する
+    async fn select_by_hash(&self, tran: &Self::Transaction, token_hash: &str) -> Result<Option<RefreshToken>> {
+        match SeaOrmRefreshToken::find()
+            .filter(refresh_token::Column::TokenHash.eq(token_hash)).one(tran).await {
+            Ok(Some(model)) => Ok(Some(RefreshTokenConverter::model_to_entity(&model)?)) ,
+            Ok(None) => Ok(None) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 未使用のトークンをローテーション済にする
+    /// 条件付きの更新にして、同時に使用された場合も1件だけ成功させる
+    async fn replace(&self, tran: &Self::Transaction, token_hash: &str, replaced_by: &str) -> Result<bool> {
+        match SeaOrmRefreshToken::update_many()
+            .col_expr(refresh_token::Column::ReplacedBy , Expr::value(replaced_by))
+            .filter(refresh_token::Column::TokenHash.eq(token_hash))
+            .filter(refresh_token::Column::ReplacedBy.is_null())
+            .filter(refresh_token::Column::Revoked.eq(false))
+            .exec(tran).await {
+            Ok(result) => Ok(result.rows_affected == 1) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// ファミリーのトークンをすべて失効させる
+    async fn revoke_family(&self, tran: &Self::Transaction, family_id: &str) -> Result<u64> {
+        match SeaOrmRefreshToken::update_many()
+            .col_expr(refresh_token::Column::Revoked , Expr::value(true))
+            .filter(refresh_token::Column::FamilyId.eq(family_id))
+            .filter(refresh_token::Column::Revoked.eq(false))
+            .exec(tran).await {
+            Ok(result) => Ok(result.rows_affected) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+}
+
+///
+/// 失効したアクセストークンリポジトリの実装
+///
+pub struct RevokedTokenRepositoryImpl;
+impl RevokedTokenRepositoryImpl {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn RevokedTokenRepository<Transaction=DatabaseTransaction>> {
+        Arc::new(Self{})
+    }
+}
+#[async_trait]
+impl RevokedTokenRepository for RevokedTokenRepositoryImpl {
+    type Transaction = DatabaseTransaction;
+    /// 失効したトークンの識別子を永続化する、登録済の場合は何もしない
+    async fn insert(&self, tran: &Self::Transaction, jti: &str, expires_at: i64) -> Result<()> {
+        // 同じトークンを同時に失効させても一意制約違反にならないよう、登録済の場合は何もしない
+        // (sea-ormのexecは結果行のないRETURNINGを扱えないため文を直接実行する)
+        let model = revoked_token::ActiveModel{ jti: Set(jti.to_string()) , expires_at: Set(expires_at) };
+        let insert = SeaOrmRevokedToken::insert(model)
+            .on_conflict(OnConflict::column(revoked_token::Column::Jti).do_nothing().to_owned())
+            .build(tran.get_database_backend());
+        match tran.execute(insert).await {
+            Ok(_) => Ok(()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 有効期限内の失効したトークンを取得する
+    async fn select_active(&self, tran: &Self::Transaction, now: i64) -> Result<Vec<(String , i64)>> {
+        match SeaOrmRevokedToken::find()
+            .filter(revoked_token::Column::ExpiresAt.gt(now)).all(tran).await {
+            Ok(models) => Ok(models.into_iter().map(|model| (model.jti , model.expires_at)).collect()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 有効期限を過ぎたトークンを削除する
+    async fn delete_expired(&self, tran: &Self::Transaction, now: i64) -> Result<u64> {
+        match SeaOrmRevokedToken::delete_many()
+            .filter(revoked_token::Column::ExpiresAt.lte(now)).exec(tran).await {
+            Ok(result) => Ok(result.rows_affected) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use sea_orm::TransactionTrait;
+    use crate::config::AppConfig;
+    use crate::domain::values::users::UserId;
+    use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
+
+    #[actix::test]
+    async fn rotate_and_revoke() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await?;
+        let repository = RefreshTokenRepositoryImpl::new();
+        let token = RefreshToken::new(String::from("hash-001") ,
+            UserId::try_from(String::from("5772a800-fef1-40bf-888b-68fddd29d881"))? , 4102444800);
+        repository.insert(&tran , &token).await?;
+        let next = token.rotate(String::from("hash-002") , 4102444800);
+        repository.insert(&tran , &next).await?;
+        // ローテーションは1回だけ成功する
+        assert!(repository.replace(&tran , "hash-001" , "hash-002").await?);
+        assert!(!repository.replace(&tran , "hash-001" , "hash-003").await?);
+        let stored = repository.select_by_hash(&tran , "hash-001").await?.unwrap();
+        assert_eq!(stored.replaced_by , Some(String::from("hash-002")));
+        assert_eq!(repository.revoke_family(&tran , &token.family_id).await? , 2);
+        assert!(repository.select_by_hash(&tran , "hash-002").await?.unwrap().is_used());
+        tran.rollback().await?;
+        Ok(())
+    }
+
+    #[actix::test]
+    async fn revoked_tokens() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await?;
+        let repository = RevokedTokenRepositoryImpl::new();
+        repository.insert(&tran , "jti-001" , 100).await?;
+        repository.insert(&tran , "jti-002" , 300).await?;
+        repository.insert(&tran , "jti-002" , 300).await?;
+        let active = repository.select_active(&tran , 200).await?;
+        assert!(active.contains(&(String::from("jti-002") , 300)));
+        assert!(!active.iter().any(|(jti , _)| jti == "jti-001"));
+        assert!(repository.delete_expired(&tran , 200).await? >= 1);
+        tran.rollback().await?;
+        Ok(())
+    }
+
+    // 未確定の同じ識別子の登録を待ってから何もせずに終わり、一意制約違反にならない
+    #[actix::test]
+    async fn concurrent_revoke() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let jti = uuid::Uuid::new_v4().to_string();
+        let repository = RevokedTokenRepositoryImpl::new();
+        let first = conn.begin().await?;
+        repository.insert(&first , &jti , 4102444800).await?;
+        let handle = {
+            let (conn , jti) = (conn.clone() , jti.clone());
+            actix::spawn(async move {
+                let tran = conn.begin().await?;
+                RevokedTokenRepositoryImpl::new().insert(&tran , &jti , 4102444800).await?;
+                tran.commit().await?;
+                anyhow::Ok(())
+            })
+        };
+        actix::clock::sleep(std::time::Duration::from_millis(200)).await;
+        first.commit().await?;
+        let result = handle.await?;
+        SeaOrmRevokedToken::delete_by_id(jti).exec(&conn).await?;
+        result
+    }
+}
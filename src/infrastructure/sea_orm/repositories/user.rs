@@ -1,10 +1,12 @@
 use std::sync::Arc;
 use async_trait::async_trait;
 use sea_orm::{DatabaseTransaction , EntityTrait , ColumnTrait , QueryFilter};
+use sea_orm::sea_query::Expr;
 use crate::{AppError, Result};
-use crate::domain::entities::User;
+use crate::i18n::Message;
+use crate::domain::entities::{Characteristic, User};
 use crate::domain::repositories::UserRepository;
-use crate::domain::values::users::UserName;
+use crate::domain::values::users::{Mail, UserId, UserName};
 use crate::domain::values::ValueInto;
 use crate::infrastructure::converter::{ActiveModelGenerator, ModelAndEntity};
 use crate::infrastructure::sea_orm::converter_impl::UserConverter;
@@ -16,6 +18,7 @@ use crate::infrastructure::sea_orm::models::user;
 ///
 pub struct UserRepositoryImpl;
 impl UserRepositoryImpl{
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> Arc<dyn UserRepository<Transaction=DatabaseTransaction>> {
         Arc::new(Self{})
     }
@@ -28,13 +31,41 @@ impl UserRepository for UserRepositoryImpl{
         match SeaOrmUser::find().filter(user::Column::UserName.eq(user_name.value().as_str())).one(tran).await {
             Ok(option_model) => {
                 match option_model {
-                    Some(model) => Ok(UserConverter::model_to_entity(&model).ok()),
+                    Some(model) => Ok(Some(UserConverter::model_to_entity(&model)?)),
                     None => Ok(None)
                 }
             },
             Err(error) => Err(AppError::from(error))
         }
     }
+    /// 指定されたユーザーIDで問合せする
+    async fn select_by_id(&self, tran: &Self::Transaction, user_id: &UserId) -> Result<Option<User>> {
+        match SeaOrmUser::find().filter(user::Column::UserId.eq(user_id.value().as_str())).one(tran).await {
+            Ok(option_model) => {
+                match option_model {
+                    Some(model) => Ok(Some(UserConverter::model_to_entity(&model)?)),
+                    None => Ok(None)
+                }
+            },
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 指定されたユーザー名のユーザーが存在するか確認する
+    async fn exists_by_name(&self, tran: &Self::Transaction, user_name: &UserName) -> Result<bool> {
+        match SeaOrmUser::find()
+            .filter(user::Column::UserName.eq(user_name.value())).one(tran).await{
+            Ok(result) => Ok(result.is_some()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    /// 指定されたメールアドレスのユーザーが存在するか確認する
+    async fn exists_by_mail(&self, tran: &Self::Transaction, mail: &Mail) -> Result<bool> {
+        match SeaOrmUser::find()
+            .filter(user::Column::Mail.eq(mail.value())).one(tran).await{
+            Ok(result) => Ok(result.is_some()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
     /// 新しいユーザーを永続化する
     async fn insert(&self, tran: &Self::Transaction, user: &User) -> Result<User> {
         let new_user = UserConverter::active_model(user);
@@ -43,6 +74,18 @@ impl UserRepository for UserRepositoryImpl{
             Err(error) => Err(AppError::from(error))
         }
     }
+    /// ユーザーのパスワードを変更する
+    async fn update_password(&self, tran: &Self::Transaction, user: &User) -> Result<User> {
+        match SeaOrmUser::update_many()
+            .col_expr(user::Column::Password , Expr::value(user.password.value()))
+            .filter(user::Column::UserId.eq(user.get().value()))
+            .exec(tran).await{
+            Ok(result) if result.rows_affected == 0 =>
+                Err(AppError::NotFound(Message::new("user.not_found").with_param("id" , user.get().value()))) ,
+            Ok(_) => Ok(user.clone()),
+            Err(error) => Err(AppError::from(error))
+        }
+    }
 }
 #[cfg(test)]
 mod tests {
@@ -50,21 +93,41 @@ mod tests {
     use anyhow::Result;
     use sea_orm::TransactionTrait;
     use crate::domain::values::users::{Mail, Password, UserName};
-    use crate::infrastructure::pool::PoolProvider;
+    use crate::config::AppConfig;
     use crate::infrastructure::sea_orm::pool_impl::SeaOrmPool;
 
     #[actix::test]
     async fn select_by_user_name() -> Result<()> {
-        let conn = SeaOrmPool::get().await;
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
         let tran = conn.begin().await.unwrap();
         let repository = UserRepositoryImpl::new();
         let user = repository.select_by_name(&tran,
-        UserName::try_from(String::from("user001"))?).await?;
-        println!("{:?}", user);
+        &UserName::try_from(String::from("user001"))?).await?;
+        assert_eq!(user.map(|user| user.user_name.value()) , Some(String::from("user001")));
         let user = repository.select_by_name(&tran,
-        UserName::try_from(String::from("abcd"))?).await?;
-        println!("{:?}", user);
+        &UserName::try_from(String::from("user999"))?).await?;
+        assert!(user.is_none());
+
+        Ok(())
+    }
 
+    #[actix::test]
+    async fn exists() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await.unwrap();
+        let repository = UserRepositoryImpl::new();
+        let result = repository.exists_by_name(&tran ,
+            &UserName::try_from(String::from("user001"))?).await?;
+        assert!(result);
+        let result = repository.exists_by_name(&tran ,
+            &UserName::try_from(String::from("user999"))?).await?;
+        assert!(!result);
+        let result = repository.exists_by_mail(&tran ,
+            &Mail::try_from(String::from("yamada@sample.com"))?).await?;
+        assert!(result);
+        let result = repository.exists_by_mail(&tran ,
+            &Mail::try_from(String::from("abcd@sample.com"))?).await?;
+        assert!(!result);
         Ok(())
     }
 
@@ -72,15 +135,31 @@ mod tests {
     async fn insert() -> Result<()> {
         let password = "j2hcn6sU".to_string();
         let user = User::new(
-            UserName::try_from("user002".to_string()) ? ,
+            UserName::try_from("user901".to_string()) ? ,
             Password::try_from(password) ? ,
-            Mail::try_from("user001@sample.com".to_string()) ?).unwrap();
+            Mail::try_from("user901@sample.com".to_string()) ?).unwrap();
 
-        let conn = SeaOrmPool::get().await;
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
         let tran = conn.begin().await.unwrap();
         let repository = UserRepositoryImpl::new();
         let user = repository.insert(&tran , &user).await?;
-        println!("{:?}" ,user);
+        assert_eq!(user.mail.value() , "user901@sample.com");
+        tran.rollback().await?;
+        Ok(())
+    }
+
+    #[actix::test]
+    async fn update_password() -> Result<()> {
+        let conn = SeaOrmPool::connect(&AppConfig::load()?.database).await?;
+        let tran = conn.begin().await.unwrap();
+        let repository = UserRepositoryImpl::new();
+        let mut user = repository.select_by_name(&tran,
+            &UserName::try_from(String::from("user001"))?).await?.unwrap();
+        user.password = Password::try_from(String::from("changed"))?;
+        repository.update_password(&tran , &user).await?;
+        let user = repository.select_by_name(&tran,
+            &UserName::try_from(String::from("user001"))?).await?.unwrap();
+        assert_eq!(user.password.value() , "changed");
         tran.rollback().await?;
         Ok(())
     }
@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use crate::{AppError, Result};
+use crate::infrastructure::transaction::{Transaction, TransactionManager};
+
+// SeaOrmのコネクションからトランザクションを開始する
+#[async_trait]
+impl TransactionManager for DatabaseConnection {
+    type Transaction = DatabaseTransaction;
+    async fn begin(&self) -> Result<Self::Transaction> {
+        match TransactionTrait::begin(self).await {
+            Ok(tran) => Ok(tran) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+}
+// SeaOrmのトランザクションをコミット、ロールバックする
+#[async_trait]
+impl Transaction for DatabaseTransaction {
+    async fn commit(self) -> Result<()> {
+        match DatabaseTransaction::commit(self).await {
+            Ok(_) => Ok(()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+    async fn rollback(self) -> Result<()> {
+        match DatabaseTransaction::rollback(self).await {
+            Ok(_) => Ok(()) ,
+            Err(error) => Err(AppError::from(error))
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use crate::Result;
+
+///
+/// トランザクションを開始するデータベース
+///
+#[async_trait]
+pub trait TransactionManager : Send + Sync + 'static {
+    type Transaction: Transaction;
+    /// トランザクションを開始する
+    async fn begin(&self) -> Result<Self::Transaction>;
+}
+///
+/// トランザクション
+///
+#[async_trait]
+pub trait Transaction : Send + Sync + 'static {
+    /// トランザクションをコミットする
+    async fn commit(self) -> Result<()>;
+    /// トランザクションをロールバックする
+    async fn rollback(self) -> Result<()>;
+}
+///
+/// ユースケース単位のトランザクション(Unit of Work)
+/// 複数のサービス、リポジトリに同じトランザクションを渡し、処理結果に応じてまとめて確定または取り消す
+/// completeを呼ばずに破棄した場合、トランザクションはロールバックされる
+///
+pub struct UnitOfWork<T: Transaction> {
+    tran: T
+}
+impl<T: Transaction> UnitOfWork<T> {
+    /// トランザクションを開始する
+    pub async fn begin<D>(db: &D) -> Result<Self> where D: TransactionManager<Transaction=T> {
+        Ok(Self{ tran: db.begin().await? })
+    }
+    /// サービス、リポジトリに渡すトランザクションを返す
+    pub fn transaction(&self) -> &T {
+        &self.tran
+    }
+    /// 処理結果がOkならコミット、Errならロールバックして処理結果を返す
+    pub async fn complete<R>(self , result: Result<R>) -> Result<R> {
+        match result {
+            Ok(value) => {
+                self.tran.commit().await?;
+                Ok(value)
+            },
+            Err(error) => {
+                self.tran.rollback().await?;
+                Err(error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppError;
+    use crate::i18n::Message;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+
+    #[actix::test]
+    async fn complete() -> anyhow::Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        // 処理結果がErrの場合は途中の変更も取り消される
+        let uow = UnitOfWork::begin(&database).await?;
+        uow.transaction().store()?.products.remove(&1);
+        let result: Result<()> = Err(AppError::Conflict(Message::from("error")));
+        assert!(uow.complete(result).await.is_err());
+        assert_eq!(database.snapshot()?.products.len() , 28);
+        // 処理結果がOkの場合は確定する
+        let uow = UnitOfWork::begin(&database).await?;
+        uow.transaction().store()?.products.remove(&1);
+        assert_eq!(uow.complete(Ok(1)).await? , 1);
+        assert_eq!(database.snapshot()?.products.len() , 27);
+        Ok(())
+    }
+}
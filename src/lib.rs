@@ -4,6 +4,8 @@ pub mod infrastructure;
 pub mod application;
 pub mod presentation;
 pub mod error;
+pub mod config;
+pub mod i18n;
 
 ///
 /// Resultエリアス
@@ -0,0 +1,201 @@
+use std::future::{ready, Future, Ready};
+use std::ops::Deref;
+use std::pin::Pin;
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, web};
+use actix_web::dev::Payload;
+use serde::de::DeserializeOwned;
+use crate::AppError;
+use crate::i18n::Locale;
+use crate::presentation::actix::request_locale;
+use crate::presentation::claims::UserPrincipal;
+use crate::presentation::decoder::JwtDecodeError;
+use crate::presentation::validate::AppValidator;
+
+// 本文を読込む抽出器の結果
+type BoxedFuture<T> = Pin<Box<dyn Future<Output = Result<T , Error>>>>;
+
+///
+/// JSONの本文を変換して入力値検証したフォーム
+///
+#[derive(Debug)]
+pub struct ValidatedJson<T>(pub T);
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T: DeserializeOwned + AppValidator + 'static> FromRequest for ValidatedJson<T> {
+    type Error = Error;
+    type Future = BoxedFuture<Self>;
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<T>::from_request(request , payload);
+        Box::pin(async move {
+            let form = json.await?.into_inner();
+            validate(form).map(Self)
+        })
+    }
+}
+
+///
+/// URLエンコードされた本文を変換して入力値検証したフォーム
+///
+#[derive(Debug)]
+pub struct ValidatedForm<T>(pub T);
+impl<T> ValidatedForm<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+impl<T> Deref for ValidatedForm<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T: DeserializeOwned + AppValidator + 'static> FromRequest for ValidatedForm<T> {
+    type Error = Error;
+    type Future = BoxedFuture<Self>;
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let form = web::Form::<T>::from_request(request , payload);
+        Box::pin(async move {
+            let form = form.await?.into_inner();
+            validate(form).map(Self)
+        })
+    }
+}
+
+///
+/// クエリ文字列を変換して入力値検証したフォーム
+///
+#[derive(Debug)]
+pub struct ValidatedQuery<T>(pub T);
+impl<T> ValidatedQuery<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+impl<T> Deref for ValidatedQuery<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T: DeserializeOwned + AppValidator> FromRequest for ValidatedQuery<T> {
+    type Error = Error;
+    type Future = Ready<Result<Self , Error>>;
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let result = web::Query::<T>::from_query(request.query_string())
+            .map_err(Error::from)
+            .and_then(|query| validate(query.into_inner()))
+            .map(Self);
+        ready(result)
+    }
+}
+
+// JwtAuthミドルウェアで認証したユーザーを取り出す
+impl FromRequest for UserPrincipal {
+    type Error = Error;
+    type Future = Ready<Result<Self , Error>>;
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let result = match request.extensions().get::<UserPrincipal>() {
+            Some(principal) => Ok(principal.clone()) ,
+            None => Err(Error::from(JwtDecodeError::Missing))
+        };
+        ready(result)
+    }
+}
+
+// リクエストのロケールを取り出す
+impl FromRequest for Locale {
+    type Error = Error;
+    type Future = Ready<Result<Self , Error>>;
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(request_locale(request)))
+    }
+}
+
+// フォームの入力値を検証する
+fn validate<T: AppValidator>(form: T) -> Result<T , Error> {
+    match form.validate_value() {
+        Ok(_) => Ok(form) ,
+        Err(error) => Err(Error::from(AppError::from(error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App, HttpResponse};
+    use actix_web::http::StatusCode;
+    use crate::domain::values::products::ProductSearchCriteria;
+    use crate::domain::values::ValueInto;
+    use crate::presentation::forms::{FormToDomain, LoginForm, ProductSearchForm};
+
+    async fn login(form: ValidatedJson<LoginForm>) -> HttpResponse {
+        HttpResponse::Ok().body(form.name.clone().unwrap_or_default())
+    }
+    async fn search(form: ValidatedQuery<ProductSearchForm>) -> HttpResponse {
+        HttpResponse::Ok().body(form.into_inner().keyword.unwrap_or_default())
+    }
+    async fn criteria(form: ValidatedQuery<ProductSearchForm>) -> HttpResponse {
+        match FormToDomain::<ProductSearchCriteria>::convert(&form.into_inner()) {
+            Ok(criteria) => HttpResponse::Ok().body(format!("{:?}" , criteria.category_ids().iter().map(|id| id.value()).collect::<Vec<_>>())) ,
+            Err(error) => HttpResponse::InternalServerError().body(error.to_string())
+        }
+    }
+
+    #[actix::test]
+    async fn validated_json() {
+        let app = test::init_service(App::new().route("/login" , web::post().to(login))).await;
+        let request = test::TestRequest::post().uri("/login")
+            .set_json(serde_json::json!({"name": "user001" , "password": "pass001"})).to_request();
+        let response = test::call_service(&app , request).await;
+        assert_eq!(response.status() , StatusCode::OK);
+        assert_eq!(test::read_body(response).await , "user001");
+        // 入力値検証エラーは項目ごとのメッセージを返す
+        let request = test::TestRequest::post().uri("/login")
+            .set_json(serde_json::json!({"name": "user" , "password": "pass001"})).to_request();
+        let response = test::call_service(&app , request).await;
+        assert_eq!(response.status() , StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        assert_eq!(body["errors"]["name"][0]["code"] , "length");
+        assert!(body["errors"].get("password").is_none());
+        // ユーザー名のない本文は検証エラーになり、ハンドラを呼び出さない
+        let request = test::TestRequest::post().uri("/login")
+            .set_json(serde_json::json!({"password": "x"})).to_request();
+        let response = test::call_service(&app , request).await;
+        assert_eq!(response.status() , StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        assert_eq!(body["errors"]["name"][0]["code"] , "required");
+    }
+
+    #[actix::test]
+    async fn validated_query() {
+        let app = test::init_service(App::new().route("/search" , web::get().to(search))).await;
+        let request = test::TestRequest::get().uri("/search?keyword=%E3%83%9A%E3%83%B3").to_request();
+        let response = test::call_service(&app , request).await;
+        assert_eq!(response.status() , StatusCode::OK);
+        assert_eq!(test::read_body(response).await , "ペン");
+    }
+
+    // 複数のカテゴリ番号はカンマ区切りで指定する
+    #[actix::test]
+    async fn validated_query_category_ids() {
+        let app = test::init_service(App::new().route("/search" , web::get().to(criteria))).await;
+        let request = test::TestRequest::get().uri("/search?category_ids=1%2C2,3").to_request();
+        let response = test::call_service(&app , request).await;
+        assert_eq!(response.status() , StatusCode::OK);
+        assert_eq!(test::read_body(response).await , "[1, 2, 3]");
+        let request = test::TestRequest::get().uri("/search?category_ids=1,0").to_request();
+        let response = test::call_service(&app , request).await;
+        assert_eq!(response.status() , StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(response).await;
+        assert_eq!(body["errors"]["category_ids[1]"][0]["code"] , "range");
+    }
+}
@@ -0,0 +1,220 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use actix_web::{Error, HttpMessage, web};
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use crate::AppError;
+use crate::application::sea_orm::provider_impl::AppServiceProvider;
+use crate::presentation::actix::request_locale;
+use crate::presentation::actix::response::{localized_response, LocalizedError};
+use crate::presentation::claims::{UserClaims, UserPrincipal};
+use crate::presentation::decoder::{decode_request, HeaderOrCookieDecoder, JwtDecodeError};
+use crate::presentation::jwt::JwtDecoder;
+
+///
+/// JWT認証ミドルウェア
+/// トークンを検証し、認証したユーザー(UserPrincipal)をリクエストに格納する
+/// 検証鍵はアプリケーションデータのAppServiceProviderから取得する
+///
+pub struct JwtAuth<D>{
+    decoder: Rc<D>
+}
+impl<D> JwtAuth<D> {
+    /// 指定されたDecoderでトークンを取り出すミドルウェアを生成する
+    pub fn new(decoder: D) -> Self {
+        Self{ decoder: Rc::new(decoder) }
+    }
+}
+// 既定はヘッダーを優先し、なければCookieからトークンを取り出す
+impl Default for JwtAuth<HeaderOrCookieDecoder<UserClaims>> {
+    fn default() -> Self {
+        Self::new(HeaderOrCookieDecoder::default())
+    }
+}
+impl<S , B , D> Transform<S , ServiceRequest> for JwtAuth<D>
+    where S: Service<ServiceRequest , Response = ServiceResponse<B> , Error = Error> + 'static ,
+          B: 'static ,
+          D: JwtDecoder<UserClaims , JwtDecodeError , ServiceRequest> + 'static {
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = JwtAuthMiddleware<S , D>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform , Self::InitError>>;
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(JwtAuthMiddleware{ service , decoder: self.decoder.clone() }))
+    }
+}
+
+///
+/// JwtAuthが生成するサービス
+///
+pub struct JwtAuthMiddleware<S , D>{
+    service: S ,
+    decoder: Rc<D>
+}
+impl<S , B , D> Service<ServiceRequest> for JwtAuthMiddleware<S , D>
+    where S: Service<ServiceRequest , Response = ServiceResponse<B> , Error = Error> + 'static ,
+          B: 'static ,
+          D: JwtDecoder<UserClaims , JwtDecodeError , ServiceRequest> + 'static {
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response , Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        match authenticate(self.decoder.as_ref() , &request) {
+            Ok(principal) => {
+                request.extensions_mut().insert(principal);
+                Box::pin(self.service.call(request))
+            },
+            Err(error) => Box::pin(async move { Err(error) })
+        }
+    }
+}
+// トークンを検証して認証したユーザーを返す
+fn authenticate<D>(decoder: &D , request: &ServiceRequest) -> Result<UserPrincipal , Error>
+    where D: JwtDecoder<UserClaims , JwtDecodeError , ServiceRequest> {
+    let provider = match request.app_data::<web::Data<AppServiceProvider>>() {
+        Some(provider) => provider ,
+        None => return Err(Error::from(AppError::from("AppServiceProviderが登録されていません。")))
+    };
+    match decode_request(decoder , &provider.jwt_keys , request) {
+        Ok(data) => Ok(UserPrincipal::from(data.claims)) ,
+        Err(error) => Err(Error::from(error))
+    }
+}
+
+///
+/// ロケール選択ミドルウェア
+/// Accept-Languageヘッダーからロケールを選んでリクエストに格納し、
+/// エラーレスポンス(AppError,ValidationError,JwtDecodeError)のメッセージをロケールに合わせる
+/// 利用例: App::new().wrap(Localize) 内側のJwtAuthのエラーも変換するため外側に登録する
+///
+pub struct Localize;
+impl<S , B> Transform<S , ServiceRequest> for Localize
+    where S: Service<ServiceRequest , Response = ServiceResponse<B> , Error = Error> + 'static ,
+          B: 'static {
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = LocalizeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform , Self::InitError>>;
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LocalizeMiddleware{ service }))
+    }
+}
+
+///
+/// Localizeが生成するサービス
+///
+pub struct LocalizeMiddleware<S>{
+    service: S
+}
+impl<S , B> Service<ServiceRequest> for LocalizeMiddleware<S>
+    where S: Service<ServiceRequest , Response = ServiceResponse<B> , Error = Error> + 'static ,
+          B: 'static {
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response , Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let locale = request_locale(request.request());
+        request.extensions_mut().insert(locale);
+        let future = self.service.call(request);
+        Box::pin(async move {
+            match future.await {
+                Ok(response) => {
+                    match response.response().error().and_then(|error| localized_response(error , locale)) {
+                        Some(localized) => Ok(response.into_response(localized).map_into_right_body()) ,
+                        None => Ok(response.map_into_left_body())
+                    }
+                },
+                // 内側のミドルウェアのエラーはレスポンスの生成時に変換する
+                Err(error) => Err(LocalizedError::wrap(error , locale))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App, HttpResponse};
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::{ACCEPT_LANGUAGE, AUTHORIZATION, WWW_AUTHENTICATE};
+    use crate::application::transfers::UserDto;
+    use crate::config::{AppConfig, Profile};
+    use actix_web::body::to_bytes;
+    use crate::i18n::{Locale, Message};
+    use crate::presentation::actix::configure;
+    use crate::presentation::claims::AccessToken;
+
+    async fn me(principal: UserPrincipal) -> HttpResponse {
+        HttpResponse::Ok().body(principal.user_name)
+    }
+
+    #[actix::test]
+    async fn authenticate() -> anyhow::Result<()> {
+        let config = AppConfig::for_profile(Profile::Test);
+        let provider = AppServiceProvider::new(std::sync::Arc::new(config.clone()))?;
+        let user = UserDto{ user_id: String::from("5772a800-fef1-40bf-888b-68fddd29d881") ,
+            user_name: String::from("user001") , password: String::new() , mail: String::new() };
+        let token = AccessToken::issue(&provider.jwt_keys , &config.jwt , &user)?.access_token;
+        let app = test::init_service(App::new()
+            .configure(configure(provider.clone()))
+            .service(web::resource("/me").wrap(JwtAuth::default()).route(web::get().to(me)))).await;
+        let request = test::TestRequest::get().uri("/me")
+            .insert_header((AUTHORIZATION , format!("Bearer {}" , token))).to_request();
+        let response = test::call_service(&app , request).await;
+        assert_eq!(response.status() , StatusCode::OK);
+        assert_eq!(test::read_body(response).await , "user001");
+        // トークンがない、不正なリクエストは401を返す
+        let request = test::TestRequest::get().uri("/me").to_request();
+        let error = test::try_call_service(&app , request).await.err().unwrap();
+        assert_eq!(error.as_response_error().status_code() , StatusCode::UNAUTHORIZED);
+        let request = test::TestRequest::get().uri("/me")
+            .insert_header((AUTHORIZATION , String::from("Bearer abc"))).to_request();
+        let error = test::try_call_service(&app , request).await.err().unwrap();
+        assert_eq!(error.as_error::<JwtDecodeError>() , Some(&JwtDecodeError::Malformed));
+        Ok(())
+    }
+
+    #[actix::test]
+    async fn localize() -> anyhow::Result<()> {
+        let config = AppConfig::for_profile(Profile::Test);
+        let provider = AppServiceProvider::new(std::sync::Arc::new(config))?;
+        let app = test::init_service(App::new()
+            .configure(configure(provider))
+            .wrap(Localize)
+            .service(web::resource("/me").wrap(JwtAuth::default()).route(web::get().to(me)))
+            .route("/locale" , web::get().to(|locale: Locale| async move { HttpResponse::Ok().body(locale.as_str()) }))
+            .route("/missing" , web::get().to(|| async {
+                Err::<HttpResponse , AppError>(AppError::NotFound(Message::new("product.not_found").with_param("id" , 9)))
+            }))).await;
+        // 認証ミドルウェアのエラーもロケールのメッセージで返す
+        let request = test::TestRequest::get().uri("/me")
+            .insert_header((ACCEPT_LANGUAGE , "en-US,en;q=0.9,ja;q=0.8")).to_request();
+        let error = test::try_call_service(&app , request).await.err().unwrap();
+        assert_eq!(error.as_response_error().status_code() , StatusCode::UNAUTHORIZED);
+        let response = error.error_response();
+        assert!(response.headers().contains_key(WWW_AUTHENTICATE));
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body()).await.unwrap())?;
+        assert_eq!(body["title"] , "Authentication is required.");
+        assert_eq!(body["detail"] , "No token was provided.");
+        let request = test::TestRequest::get().uri("/me").to_request();
+        let response = test::try_call_service(&app , request).await.err().unwrap().error_response();
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body()).await.unwrap())?;
+        assert_eq!(body["detail"] , "トークンがありません。");
+        // ハンドラーのエラーもロケールのメッセージで返す
+        let request = test::TestRequest::get().uri("/missing").insert_header((ACCEPT_LANGUAGE , "en")).to_request();
+        let body: serde_json::Value = serde_json::from_slice(&test::read_body(test::call_service(&app , request).await).await)?;
+        assert_eq!(body["detail"] , "No product found with ID 9.");
+        let request = test::TestRequest::get().uri("/locale").insert_header((ACCEPT_LANGUAGE , "en")).to_request();
+        assert_eq!(test::read_body(test::call_service(&app , request).await).await , "en");
+        Ok(())
+    }
+}
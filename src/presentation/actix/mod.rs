@@ -0,0 +1,56 @@
+//! actix-webとの連携(actix-webフィーチャー)
+//! フォームの抽出器、JWT認証ミドルウェア、ロケールの選択、エラーレスポンスを提供する
+use std::sync::Arc;
+use actix_web::{HttpMessage, HttpRequest};
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::ACCEPT_LANGUAGE;
+use actix_web::web::{Data, ServiceConfig};
+use crate::application::sea_orm::provider_impl::AppServiceProvider;
+use crate::i18n::Locale;
+use crate::presentation::decoder::JwtRequest;
+
+pub mod extract;
+pub mod middleware;
+pub mod response;
+
+///
+/// AppServiceProviderをアプリケーションデータとして登録する
+/// 利用例: App::new().configure(configure(provider.clone()))
+///
+pub fn configure(provider: Arc<AppServiceProvider>) -> impl FnOnce(&mut ServiceConfig) {
+    move |config| {
+        config.app_data(Data::from(provider));
+    }
+}
+
+///
+/// リクエストのロケール
+/// Localizeミドルウェアが選んだロケール、なければAccept-Languageヘッダーから選ぶ
+///
+pub fn request_locale(request: &HttpRequest) -> Locale {
+    if let Some(locale) = request.extensions().get::<Locale>() {
+        return *locale;
+    }
+    match request.headers().get(ACCEPT_LANGUAGE).and_then(|value| value.to_str().ok()) {
+        Some(accept_language) => Locale::negotiate(accept_language) ,
+        None => Locale::default()
+    }
+}
+
+// リクエストのヘッダーとCookieからトークンを取り出す
+impl JwtRequest for HttpRequest {
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers().get(name).and_then(|value| value.to_str().ok()).map(String::from)
+    }
+    fn cookie(&self, name: &str) -> Option<String> {
+        HttpRequest::cookie(self , name).map(|cookie| cookie.value().to_string())
+    }
+}
+impl JwtRequest for ServiceRequest {
+    fn header(&self, name: &str) -> Option<String> {
+        JwtRequest::header(self.request() , name)
+    }
+    fn cookie(&self, name: &str) -> Option<String> {
+        JwtRequest::cookie(self.request() , name)
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt::{Display, Formatter};
+use actix_web::{Error, HttpResponse, ResponseError};
+use actix_web::http::StatusCode;
+use actix_web::http::header::{CONTENT_TYPE, WWW_AUTHENTICATE};
+use crate::AppError;
+use crate::error::ProblemDetails;
+use crate::i18n::Locale;
+use crate::presentation::decoder::JwtDecodeError;
+use crate::presentation::validate::ValidationError;
+
+// Problem Details(application/problem+json)のレスポンスを生成する
+fn problem_response(problem: &ProblemDetails) -> HttpResponse {
+    let status = StatusCode::from_u16(problem.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    HttpResponse::build(status)
+        .insert_header((CONTENT_TYPE , ProblemDetails::CONTENT_TYPE))
+        .body(serde_json::to_string(problem).unwrap_or_default())
+}
+
+// アプリケーションのエラーをHTTPステータスに対応付ける
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+    fn error_response(&self) -> HttpResponse {
+        // 内部エラーの詳細はログにのみ出力する
+        if self.status() >= 500 {
+            log::error!("{:?}" , self);
+        }
+        problem_response(&self.problem())
+    }
+}
+///
+/// ロケールに合わせてレスポンスを生成するエラー
+/// Localizeミドルウェアが内側のサービスが返したエラーを包む
+///
+#[derive(Debug)]
+pub struct LocalizedError {
+    error:  Error ,     // 内側のサービスが返したエラー
+    locale: Locale      // レスポンスのロケール
+}
+impl LocalizedError {
+    /// このクレートのエラーであれば包む、それ以外はそのまま返す
+    pub fn wrap(error: Error , locale: Locale) -> Error {
+        if localized_response(&error , locale).is_some() {
+            Error::from(Self{ error , locale })
+        }else{
+            error
+        }
+    }
+}
+impl Display for LocalizedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f , "{}" , self.error)
+    }
+}
+impl ResponseError for LocalizedError {
+    fn status_code(&self) -> StatusCode {
+        self.error.as_response_error().status_code()
+    }
+    fn error_response(&self) -> HttpResponse {
+        localized_response(&self.error , self.locale).unwrap_or_else(|| self.error.error_response())
+    }
+}
+// エラーをロケールに合わせたレスポンスにする、このクレートのエラー以外はNoneを返す
+// 利用者向けのレスポンスのためログは出力しない(元のレスポンス生成時に出力済)
+pub fn localized_response(error: &Error , locale: Locale) -> Option<HttpResponse> {
+    if let Some(error) = error.as_error::<AppError>() {
+        Some(problem_response(&error.problem_in(locale)))
+    }else if let Some(error) = error.as_error::<ValidationError>() {
+        Some(problem_response(&AppError::from(error.clone()).problem_in(locale)))
+    }else{
+        error.as_error::<JwtDecodeError>().map(|error| jwt_error_response(error , locale))
+    }
+}
+// 入力値検証エラーは項目ごとのメッセージを返す
+impl ResponseError for ValidationError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+    fn error_response(&self) -> HttpResponse {
+        problem_response(&AppError::from(self.clone()).problem())
+    }
+}
+// トークンのエラーはRFC 6750のWWW-Authenticateヘッダーを付けて返す
+impl ResponseError for JwtDecodeError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+    fn error_response(&self) -> HttpResponse {
+        jwt_error_response(self , Locale::default())
+    }
+}
+fn jwt_error_response(error: &JwtDecodeError , locale: Locale) -> HttpResponse {
+    let challenge = match error {
+        JwtDecodeError::Missing => String::from("Bearer") ,
+        _ => format!("Bearer error=\"invalid_token\", error_description=\"{:?}\"" , error)
+    };
+    let mut response = problem_response(&AppError::Unauthorized(error.message()).problem_in(locale));
+    if let Ok(value) = challenge.parse() {
+        response.headers_mut().insert(WWW_AUTHENTICATE , value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::i18n::Message;
+    use actix_web::body::to_bytes;
+    use anyhow::anyhow;
+
+    #[actix::test]
+    async fn status_codes() -> anyhow::Result<()> {
+        assert_eq!(AppError::NotFound(Message::from("なし")).status_code() , StatusCode::NOT_FOUND);
+        assert_eq!(AppError::Conflict(Message::from("登録済")).status_code() , StatusCode::CONFLICT);
+        assert_eq!(AppError::Unauthorized(Message::from("認証")).status_code() , StatusCode::UNAUTHORIZED);
+        assert_eq!(AppError::Forbidden(Message::from("権限")).status_code() , StatusCode::FORBIDDEN);
+        // 内部エラーのメッセージは返さない
+        let response = AppError::from(anyhow!("接続文字列:secret")).error_response();
+        assert_eq!(response.status() , StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers().get(CONTENT_TYPE).unwrap() , ProblemDetails::CONTENT_TYPE);
+        let body = to_bytes(response.into_body()).await.map_err(|_| anyhow!("本文を読込めません。"))?;
+        assert!(!String::from_utf8(body.to_vec())?.contains("secret"));
+        // 検証エラーは項目ごとのメッセージを返す
+        let mut errors = HashMap::new();
+        errors.insert(String::from("name") , String::from("入力して下さい。"));
+        let response = AppError::from(ValidationError::from(errors)).error_response();
+        assert_eq!(response.status() , StatusCode::BAD_REQUEST);
+        let body = to_bytes(response.into_body()).await.map_err(|_| anyhow!("本文を読込めません。"))?;
+        let body: serde_json::Value = serde_json::from_slice(&body)?;
+        assert_eq!(body["errors"]["name"][0]["message"] , "入力して下さい。");
+        assert_eq!(body["code"] , "VALIDATION");
+        // トークンのエラーは認証方式を返す
+        let response = JwtDecodeError::Expired.error_response();
+        assert!(response.headers().get(WWW_AUTHENTICATE).unwrap().to_str()?.contains("invalid_token"));
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::Result;
+use crate::application::transfers::UserDto;
+use crate::config::JwtConfig;
+use crate::presentation::jwt::{ClaimsGenerator, JwtKeys};
+
+///
+/// 認証済ユーザーのクレーム
+/// sub(ユーザーID)とname,rolesに加えて登録済クレーム(iat,exp,nbf,jti,iss,aud)を持つ
+///
+#[derive(Serialize , Deserialize , Debug , Clone , PartialEq)]
+pub struct UserClaims {
+    pub sub:    String ,        // ユーザーID
+    pub name:   String ,        // ユーザー名
+    pub roles:  Vec<String> ,   // ロール
+    pub iat:    u64 ,           // 発行日時
+    pub exp:    u64 ,           // 有効期限
+    pub nbf:    u64 ,           // 有効開始日時
+    pub jti:    String ,        // トークンの識別子
+    #[serde(default , skip_serializing_if = "Option::is_none")]
+    pub iss:    Option<String> ,// 発行者
+    #[serde(default , skip_serializing_if = "Option::is_none")]
+    pub aud:    Option<String>  // 対象者
+}
+// 認証結果のユーザーからクレームを生成する
+impl ClaimsGenerator<UserDto> for UserClaims {
+    fn generate(user: &UserDto , config: &JwtConfig) -> Self {
+        let now = jsonwebtoken::get_current_timestamp();
+        Self{
+            sub: user.user_id.clone() ,
+            name: user.user_name.clone() ,
+            roles: config.roles.clone() ,
+            iat: now ,
+            exp: now + config.expires_in ,
+            nbf: now ,
+            jti: Uuid::new_v4().to_string() ,
+            iss: config.issuer.clone() ,
+            aud: config.audience.clone()
+        }
+    }
+}
+
+///
+/// 発行したアクセストークン
+///
+#[derive(Serialize , Deserialize , Debug , Clone)]
+pub struct AccessToken {
+    pub access_token: String ,  // トークン
+    pub token_type:   String ,  // トークンの種類(Bearer)
+    pub expires_in:   u64 ,     // 有効期間(秒)
+    #[serde(default , skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String> // リフレッシュトークン
+}
+impl AccessToken {
+    /// 認証結果のユーザーにアクセストークンを発行する
+    pub fn issue(keys: &JwtKeys , config: &JwtConfig , user: &UserDto) -> Result<Self> {
+        let claims = UserClaims::generate(user , config);
+        Ok(Self{
+            access_token: keys.encode(&claims)? ,
+            token_type: String::from("Bearer") ,
+            expires_in: config.expires_in ,
+            refresh_token: None
+        })
+    }
+    /// リフレッシュトークンを付与する
+    pub fn with_refresh_token(mut self , refresh_token: String) -> Self {
+        self.refresh_token = Some(refresh_token);
+        self
+    }
+}
+
+///
+/// トークンから復元した認証済ユーザー
+///
+#[derive(Serialize , Deserialize , Debug , Clone , PartialEq)]
+pub struct UserPrincipal {
+    pub user_id:    String ,        // ユーザーID
+    pub user_name:  String ,        // ユーザー名
+    pub roles:      Vec<String> ,   // ロール
+    pub token_id:   String ,        // トークンの識別子(jti)
+    pub expires_at: u64             // 有効期限
+}
+impl UserPrincipal {
+    /// トークンを検証して認証済ユーザーを復元する
+    pub fn from_token(keys: &JwtKeys , token: &str) -> std::result::Result<Self , jsonwebtoken::errors::Error> {
+        let data = keys.decode::<UserClaims>(token)?;
+        Ok(Self::from(data.claims))
+    }
+    /// 指定されたロールを持つか判定する
+    pub fn has_role(&self , role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+impl From<UserClaims> for UserPrincipal {
+    fn from(claims: UserClaims) -> Self {
+        Self{
+            user_id: claims.sub ,
+            user_name: claims.name ,
+            roles: claims.roles ,
+            token_id: claims.jti ,
+            expires_at: claims.exp
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user() -> UserDto {
+        UserDto{
+            user_id: String::from("5772a800-fef1-40bf-888b-68fddd29d881") ,
+            user_name: String::from("user001") ,
+            password: String::from("$argon2id$") ,
+            mail: String::from("yamada@sample.com")
+        }
+    }
+
+    #[test]
+    fn generate() {
+        let config = JwtConfig{ issuer: Some(String::from("app_commons")) , expires_in: 600 , ..Default::default() };
+        let claims = UserClaims::generate(&user() , &config);
+        assert_eq!(claims.sub , "5772a800-fef1-40bf-888b-68fddd29d881");
+        assert_eq!(claims.roles , vec![String::from("user")]);
+        assert_eq!(claims.exp - claims.iat , 600);
+        assert_eq!(claims.iss , Some(String::from("app_commons")));
+        assert_eq!(claims.aud , None);
+        // jtiはトークンごとに異なる
+        assert_ne!(claims.jti , UserClaims::generate(&user() , &config).jti);
+    }
+
+    #[test]
+    fn issue_and_from_token() -> anyhow::Result<()> {
+        let config = JwtConfig{ issuer: Some(String::from("app_commons")) ,
+                                audience: Some(String::from("sample")) , ..Default::default() };
+        let keys = JwtKeys::from_config(&config)?;
+        let token = AccessToken::issue(&keys , &config , &user())?;
+        assert_eq!(token.token_type , "Bearer");
+        let principal = UserPrincipal::from_token(&keys , &token.access_token)?;
+        assert_eq!(principal.user_id , "5772a800-fef1-40bf-888b-68fddd29d881");
+        assert_eq!(principal.user_name , "user001");
+        assert!(principal.has_role("user"));
+        assert!(!principal.has_role("admin"));
+        // 改ざんしたトークンは復元できない
+        assert!(UserPrincipal::from_token(&keys , &format!("{}x" , token.access_token)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn denied_token() -> anyhow::Result<()> {
+        let config = JwtConfig::default();
+        let keys = JwtKeys::from_config(&config)?;
+        let token = AccessToken::issue(&keys , &config , &user())?;
+        assert!(serde_json::to_string(&token)?.find("refresh_token").is_none());
+        let principal = UserPrincipal::from_token(&keys , &token.access_token)?;
+        // 失効したjtiのトークンは復元できない
+        keys.denylist().deny(&principal.token_id , principal.expires_at);
+        let error = UserPrincipal::from_token(&keys , &token.access_token).err().unwrap();
+        assert_eq!(error.kind() , &jsonwebtoken::errors::ErrorKind::InvalidToken);
+        // 別のトークンは影響を受けない
+        let other = AccessToken::issue(&keys , &config , &user())?.with_refresh_token(String::from("refresh"));
+        assert!(UserPrincipal::from_token(&keys , &other.access_token).is_ok());
+        assert_eq!(other.refresh_token , Some(String::from("refresh")));
+        Ok(())
+    }
+}
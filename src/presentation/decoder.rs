@@ -0,0 +1,302 @@
+//! リクエストからトークンを取り出して検証するJwtDecoderの実装
+//! Webフレームワークに依存しないよう、リクエストはJwtRequestを通して参照する
+use std::marker::PhantomData;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::TokenData;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use crate::i18n::Message;
+use crate::presentation::jwt::{JwtDecoder, JwtKeys, JWT_COOKIE_KEY, JWT_HEADER_KEY};
+
+/// Authorizationヘッダーの認証方式
+pub const BEARER_SCHEME: &str = "Bearer";
+
+///
+/// トークンを取り出すリクエスト
+///
+pub trait JwtRequest {
+    /// ヘッダーの値、ヘッダー名の大文字小文字は区別しない
+    fn header(&self , name: &str) -> Option<String>;
+    /// Cookieの値
+    fn cookie(&self , name: &str) -> Option<String>;
+}
+
+///
+/// トークンの取り出しと検証のエラー
+///
+#[derive(Debug , Clone , PartialEq , Eq , Error)]
+pub enum JwtDecodeError {
+    #[error("トークンがありません。")]
+    Missing ,               // ヘッダー、Cookieにトークンがない
+    #[error("トークンの形式が不正です。")]
+    Malformed ,             // Bearer形式でない、JWTとして解析できない
+    #[error("トークンの有効期限が切れています。")]
+    Expired ,               // 有効期限切れ
+    #[error("トークンの署名が不正です。")]
+    InvalidSignature ,      // 署名の検証に失敗
+    #[error("トークンが無効です。({0:?})")]
+    Invalid(ErrorKind)      // 失効済、未知の鍵、発行者や対象者の不一致など
+}
+impl JwtDecodeError {
+    /// 利用者に返すメッセージ
+    pub fn message(&self) -> Message {
+        Message::new(match self {
+            JwtDecodeError::Missing => "token.missing" ,
+            JwtDecodeError::Malformed => "token.malformed" ,
+            JwtDecodeError::Expired => "token.expired" ,
+            JwtDecodeError::InvalidSignature => "token.invalid_signature" ,
+            JwtDecodeError::Invalid(_) => "token.invalid"
+        })
+    }
+}
+impl From<jsonwebtoken::errors::Error> for JwtDecodeError {
+    fn from(error: jsonwebtoken::errors::Error) -> Self {
+        match error.into_kind() {
+            ErrorKind::ExpiredSignature => JwtDecodeError::Expired ,
+            ErrorKind::InvalidSignature => JwtDecodeError::InvalidSignature ,
+            ErrorKind::Base64(_) | ErrorKind::Json(_) | ErrorKind::Utf8(_) => JwtDecodeError::Malformed ,
+            kind => JwtDecodeError::Invalid(kind)
+        }
+    }
+}
+
+///
+/// トークンの取り出し元の優先順位
+///
+#[derive(Debug , Clone , Copy , PartialEq , Eq , Default)]
+pub enum TokenPrecedence {
+    #[default]
+    HeaderFirst ,   // ヘッダーを優先する
+    CookieFirst     // Cookieを優先する
+}
+
+///
+/// Authorization: Bearer ヘッダーからトークンを取り出す
+///
+pub struct BearerDecoder<T>(PhantomData<fn() -> T>);
+impl<T> BearerDecoder<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+impl<T> Default for BearerDecoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: DeserializeOwned , R: JwtRequest> JwtDecoder<T , JwtDecodeError , R> for BearerDecoder<T> {
+    fn parse_header(&self, request: &R) -> Result<String , JwtDecodeError> {
+        header_token(request)
+    }
+}
+
+///
+/// Authorization Cookieからトークンを取り出す
+///
+pub struct CookieDecoder<T>(PhantomData<fn() -> T>);
+impl<T> CookieDecoder<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+impl<T> Default for CookieDecoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: DeserializeOwned , R: JwtRequest> JwtDecoder<T , JwtDecodeError , R> for CookieDecoder<T> {
+    fn parse_header(&self, request: &R) -> Result<String , JwtDecodeError> {
+        cookie_token(request)
+    }
+}
+
+///
+/// ヘッダーとCookieの両方からトークンを取り出す
+/// 優先する取り出し元にトークンがない場合のみ、もう一方を参照する
+///
+pub struct HeaderOrCookieDecoder<T>{
+    precedence: TokenPrecedence ,
+    phantom:    PhantomData<fn() -> T>
+}
+impl<T> HeaderOrCookieDecoder<T> {
+    pub fn new(precedence: TokenPrecedence) -> Self {
+        Self{ precedence , phantom: PhantomData }
+    }
+}
+impl<T> Default for HeaderOrCookieDecoder<T> {
+    fn default() -> Self {
+        Self::new(TokenPrecedence::default())
+    }
+}
+impl<T: DeserializeOwned , R: JwtRequest> JwtDecoder<T , JwtDecodeError , R> for HeaderOrCookieDecoder<T> {
+    fn parse_header(&self, request: &R) -> Result<String , JwtDecodeError> {
+        let (first , second) = match self.precedence {
+            TokenPrecedence::HeaderFirst => (header_token(request) , cookie_token(request)) ,
+            TokenPrecedence::CookieFirst => (cookie_token(request) , header_token(request))
+        };
+        match first {
+            Err(JwtDecodeError::Missing) => second ,
+            result => result
+        }
+    }
+}
+
+///
+/// リクエストからトークンを取り出して検証する
+///
+pub fn decode_request<T , R , D>(decoder: &D , keys: &JwtKeys , request: &R) -> Result<TokenData<T> , JwtDecodeError>
+    where T: DeserializeOwned , R: JwtRequest , D: JwtDecoder<T , JwtDecodeError , R> {
+    let token = decoder.parse_header(request)?;
+    Ok(decoder.decode(keys , &token)?)
+}
+
+// Authorizationヘッダーからトークンを取り出す
+fn header_token<R: JwtRequest>(request: &R) -> Result<String , JwtDecodeError> {
+    match request.header(JWT_HEADER_KEY) {
+        Some(value) => bearer_token(&value) ,
+        None => Err(JwtDecodeError::Missing)
+    }
+}
+// Authorization Cookieからトークンを取り出す、Bearer形式で保存された値も受け付ける
+fn cookie_token<R: JwtRequest>(request: &R) -> Result<String , JwtDecodeError> {
+    let value = match request.cookie(JWT_COOKIE_KEY) {
+        Some(value) => value ,
+        None => return Err(JwtDecodeError::Missing)
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(JwtDecodeError::Missing);
+    }
+    match value.split_once(' ') {
+        Some(_) => bearer_token(value) ,
+        None => compact_token(value)
+    }
+}
+// Bearer形式のヘッダー値からトークンを取り出す、認証方式は大文字小文字を区別しない
+fn bearer_token(value: &str) -> Result<String , JwtDecodeError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(JwtDecodeError::Missing);
+    }
+    match value.split_once(' ') {
+        Some((scheme , token)) if scheme.eq_ignore_ascii_case(BEARER_SCHEME) => compact_token(token.trim()) ,
+        _ => Err(JwtDecodeError::Malformed)
+    }
+}
+// JWTのコンパクト形式(3つの部分を.で区切った文字列)か検証する
+fn compact_token(token: &str) -> Result<String , JwtDecodeError> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() == 3 && parts.iter().all(|part| !part.is_empty() &&
+        part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')) {
+        Ok(token.to_string())
+    } else {
+        Err(JwtDecodeError::Malformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::application::transfers::UserDto;
+    use crate::config::{JwtConfig, JwtKeyConfig};
+    use crate::presentation::claims::{AccessToken, UserClaims};
+    use crate::presentation::jwt::ClaimsGenerator;
+
+    #[derive(Default)]
+    struct Request {
+        headers: HashMap<String , String> ,
+        cookies: HashMap<String , String>
+    }
+    impl JwtRequest for Request {
+        fn header(&self, name: &str) -> Option<String> {
+            self.headers.iter().find(|(key , _)| key.eq_ignore_ascii_case(name)).map(|(_ , value)| value.clone())
+        }
+        fn cookie(&self, name: &str) -> Option<String> {
+            self.cookies.get(name).cloned()
+        }
+    }
+    impl Request {
+        fn header(value: &str) -> Self {
+            let mut request = Self::default();
+            request.headers.insert(String::from("authorization") , value.to_string());
+            request
+        }
+        fn cookie(mut self , value: &str) -> Self {
+            self.cookies.insert(String::from(JWT_COOKIE_KEY) , value.to_string());
+            self
+        }
+    }
+
+    fn user() -> UserDto {
+        UserDto{ user_id: String::from("5772a800-fef1-40bf-888b-68fddd29d881") ,
+            user_name: String::from("user001") , password: String::new() , mail: String::new() }
+    }
+    fn token(config: &JwtConfig) -> anyhow::Result<String> {
+        Ok(AccessToken::issue(&JwtKeys::from_config(config)? , config , &user())?.access_token)
+    }
+
+    #[test]
+    fn bearer_and_cookie() -> anyhow::Result<()> {
+        let config = JwtConfig::default();
+        let keys = JwtKeys::from_config(&config)?;
+        let token = token(&config)?;
+        let bearer = BearerDecoder::<UserClaims>::new();
+        let data = decode_request(&bearer , &keys , &Request::header(&format!("bearer {}" , token)))?;
+        assert_eq!(data.claims.name , "user001");
+        assert_eq!(decode_request(&bearer , &keys , &Request::default()).err() , Some(JwtDecodeError::Missing));
+        assert_eq!(decode_request(&bearer , &keys , &Request::header(&format!("Basic {}" , token))).err() ,
+                   Some(JwtDecodeError::Malformed));
+        assert_eq!(decode_request(&bearer , &keys , &Request::header("Bearer abc")).err() ,
+                   Some(JwtDecodeError::Malformed));
+        let cookie = CookieDecoder::<UserClaims>::new();
+        assert!(decode_request(&cookie , &keys , &Request::default().cookie(&token)).is_ok());
+        assert!(decode_request(&cookie , &keys , &Request::default().cookie(&format!("Bearer {}" , token))).is_ok());
+        assert_eq!(decode_request(&cookie , &keys , &Request::header(&format!("Bearer {}" , token))).err() ,
+                   Some(JwtDecodeError::Missing));
+        Ok(())
+    }
+
+    #[test]
+    fn precedence() -> anyhow::Result<()> {
+        let config = JwtConfig::default();
+        let keys = JwtKeys::from_config(&config)?;
+        let token = token(&config)?;
+        // 優先する取り出し元の不正なトークンはもう一方で補わない
+        let request = Request::header(&format!("Bearer {}" , token)).cookie("broken");
+        let header_first = HeaderOrCookieDecoder::<UserClaims>::new(TokenPrecedence::HeaderFirst);
+        let cookie_first = HeaderOrCookieDecoder::<UserClaims>::new(TokenPrecedence::CookieFirst);
+        assert!(decode_request(&header_first , &keys , &request).is_ok());
+        assert_eq!(decode_request(&cookie_first , &keys , &request).err() , Some(JwtDecodeError::Malformed));
+        // 優先する取り出し元にトークンがなければもう一方を使う
+        assert!(decode_request(&cookie_first , &keys , &Request::header(&format!("Bearer {}" , token))).is_ok());
+        assert!(decode_request(&header_first , &keys , &Request::default().cookie(&token)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> anyhow::Result<()> {
+        let config = JwtConfig::default();
+        let keys = JwtKeys::from_config(&config)?;
+        let decoder = BearerDecoder::<UserClaims>::new();
+        // 許容誤差を超えて期限切れのトークン
+        let mut claims = UserClaims::generate(&user() , &config);
+        claims.exp = jsonwebtoken::get_current_timestamp() - 120;
+        let expired = keys.encode(&claims)?;
+        assert_eq!(decode_request(&decoder , &keys , &Request::header(&format!("Bearer {}" , expired))).err() ,
+                   Some(JwtDecodeError::Expired));
+        // 別の鍵で署名されたトークン
+        let other = JwtConfig{ key: JwtKeyConfig{ secret: String::from("other-secret") , ..Default::default() } ,
+                               ..Default::default() };
+        let forged = token(&other)?;
+        assert_eq!(decode_request(&decoder , &keys , &Request::header(&format!("Bearer {}" , forged))).err() ,
+                   Some(JwtDecodeError::InvalidSignature));
+        // 失効したトークン
+        let token = token(&config)?;
+        let data = decode_request(&decoder , &keys , &Request::header(&format!("Bearer {}" , token)))?;
+        keys.denylist().deny(&data.claims.jti , data.claims.exp);
+        assert_eq!(decode_request(&decoder , &keys , &Request::header(&format!("Bearer {}" , token))).err() ,
+                   Some(JwtDecodeError::Invalid(ErrorKind::InvalidToken)));
+        Ok(())
+    }
+}
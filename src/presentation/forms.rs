@@ -1,14 +1,15 @@
-use std::collections::HashMap;
-use std::ops::Deref;
 use anyhow::Result;
 use serde::{de, Deserialize, Serialize};
-use validator::{validate_length, validate_required, validate_range, Validate, ValidationErrors};
+use validator::{validate_required, validate_range, Validate};
 use crate::domain::entities::{Category, Product, User};
 use crate::domain::values::categories::{CategoryId, CategoryName};
-use crate::domain::values::products::{ProductId, ProductName, ProductPrice};
-use crate::domain::values::users::{Mail, Password, UserName};
+use crate::domain::values::pages::{PageRequest, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::domain::values::products::{KeywordMatch, ProductId, ProductName, ProductPrice, ProductSearchCriteria, ProductSortKey, MAX_SEARCH_KEYWORDS};
+use crate::domain::values::rules::{LengthRule, RangeRule};
+use crate::domain::values::users::{Credentials, Mail, Password, PasswordChange, UserName};
 use crate::error::AppError;
-use crate::presentation::validate::{AppValidator, ValidationError};
+use crate::i18n::Message;
+use crate::presentation::validate::{AppValidator, FieldError, ValidationError};
 
 
 ///
@@ -26,30 +27,172 @@ pub fn empty_string_as_none<'de, T, D>(deserializer: D) -> Result<Option<T>, D::
     Ok(T::deserialize(deserializer).ok())
 }
 
+// 文字数の制約違反のエラー、制約は値オブジェクトの定義に従う
+fn length_error(label: &str , rule: LengthRule) -> FieldError {
+    FieldError::new("length" , rule.message("validation" , label))
+        .with_param("min" , rule.min).with_param("max" , rule.max)
+}
+// 範囲の制約違反のエラー、制約は値オブジェクトの定義に従う
+fn range_error(label: &str , rule: RangeRule) -> FieldError {
+    FieldError::new("range" , rule.message("validation" , label))
+        .with_param("min" , rule.min).with_param("max" , rule.max)
+}
+// 未入力のエラー
+fn required_error(label: &str) -> FieldError {
+    FieldError::new("required" , Message::new("validation.required").with_label("label" , label))
+}
+// 下限を下回るエラー
+fn min_error<T: Serialize + Copy>(label: &str , min: T) -> FieldError {
+    FieldError::new("range" , Message::new("validation.min").with_label("label" , label).with_param("min" , min))
+        .with_param("min" , min)
+}
+// 選択肢にない値のエラー
+fn choice_error(code: &str , label: &str) -> FieldError {
+    FieldError::new(code , Message::new("validation.invalid_choice").with_label("label" , label))
+}
+// 変換する項目の値を取り出す、未入力の場合は検証エラーを返す
+fn required_value<'a , T>(value: &'a Option<T> , field: &str , label: &str) -> Result<&'a T , AppError> {
+    value.as_ref().ok_or_else(|| field_error(field , required_error(label)))
+}
+// 1つの項目の検証エラー
+fn field_error(field: &str , error: FieldError) -> AppError {
+    let mut errors = ValidationError::new();
+    errors.add(field , error);
+    AppError::from(errors)
+}
+
 // 商品検索
-#[derive(Deserialize , Debug)]
+#[derive(Deserialize , Debug , Default)]
 pub struct ProductSearchForm {
-    pub keyword: Option<String>
+    pub keyword: Option<String> ,       // 空白区切りで複数指定できるキーワード
+    #[serde(default)]
+    pub keyword_match: Option<String> , // キーワードの結合方法(and,or 未指定はand)
+    #[serde(default , deserialize_with = "empty_string_as_none")]
+    pub min_price:  Option<i32> ,       // 単価の下限
+    #[serde(default , deserialize_with = "empty_string_as_none")]
+    pub max_price:  Option<i32> ,       // 単価の上限
+    #[serde(default)]
+    pub category_ids: Option<String> ,  // カンマ区切りのカテゴリ番号(いずれかに該当)
+    #[serde(default , deserialize_with = "empty_string_as_none")]
+    pub page:       Option<u64> ,       // ページ番号(未指定は1ページ目)
+    #[serde(default , deserialize_with = "empty_string_as_none")]
+    pub page_size:  Option<u64> ,       // 1ページの件数(未指定は既定値)
+    #[serde(default)]
+    pub sort:       Option<String>      // 並び順(id_asc,id_desc,name_asc,name_desc,price_asc,price_desc)
+}
+impl ProductSearchForm {
+    // 空白(全角を含む)で区切ったキーワードを返す
+    fn keywords(&self) -> Vec<String> {
+        match self.keyword.as_ref() {
+            Some(keyword) => keyword.split_whitespace().map(String::from).collect() ,
+            None => Vec::new()
+        }
+    }
+    // カンマで区切ったカテゴリ番号を返す、数値に変換できない要素はNoneにする
+    fn category_ids(&self) -> Vec<Option<i32>> {
+        match self.category_ids.as_ref() {
+            Some(ids) => ids.split(',').map(str::trim).filter(|id| !id.is_empty())
+                .map(|id| id.parse().ok()).collect() ,
+            None => Vec::new()
+        }
+    }
 }
 /// 入力値検証
 impl AppValidator for ProductSearchForm{
     fn validate_value(&self) -> Result<(), ValidationError> {
-        let mut errors:HashMap<String,String> = HashMap::new();
-        // 未入力と範囲チェック
-        if self.keyword.is_none() || self.keyword.as_ref().unwrap().is_empty() {
-            errors.insert(String::from("keyword"),String::from("キーワードは入力必須です。"));
+        let mut errors = ValidationError::new();
+        // keywordフィールドの検証　キーワード数チェック
+        if self.keywords().len() > MAX_SEARCH_KEYWORDS {
+            errors.add("keyword" , FieldError::new("count" , Message::new("validation.max_count")
+                .with_label("label" , "label.search.keyword").with_param("max" , MAX_SEARCH_KEYWORDS)).with_param("max" , MAX_SEARCH_KEYWORDS));
+        }
+        // keyword_matchフィールドの検証　結合方法の種類チェック
+        if let Some(keyword_match) = self.keyword_match.as_ref() {
+            if KeywordMatch::try_from(keyword_match.clone()).is_err() {
+                errors.add("keyword_match" , choice_error("invalid" , "label.search.keyword_match"));
+            }
+        }
+        // min_price,max_priceフィールドの検証　範囲チェック
+        if self.min_price.is_some_and(|price| ! validate_range(price , Some(0) , None)) {
+            errors.add("min_price" , min_error("label.search.min_price" , 0));
+        }
+        if self.max_price.is_some_and(|price| ! validate_range(price , Some(0) , None)) {
+            errors.add("max_price" , min_error("label.search.max_price" , 0));
+        }
+        if let (Some(min_price) , Some(max_price)) = (self.min_price , self.max_price) {
+            if min_price > max_price {
+                errors.add("max_price" , FieldError::new("range" , Message::new("validation.price_order")).with_param("min" , min_price));
+            }
         }
-        if errors.is_empty(){
-            Ok(())
-        }else{
-            Err(ValidationError::from(errors))
+        // category_idsフィールドの検証　範囲チェック、不正な要素は添字付きの項目名で返す
+        for (index , id) in self.category_ids().into_iter().enumerate() {
+            match id {
+                Some(id) if validate_range(id , Some(1) , None) => {} ,
+                Some(_) => errors.add(&format!("category_ids[{}]" , index) ,
+                                      choice_error("range" , "label.product.category").with_param("min" , 1)) ,
+                None => errors.add(&format!("category_ids[{}]" , index) , choice_error("invalid" , "label.product.category"))
+            }
+        }
+        // pageフィールドの検証　範囲チェック、上限は1ページの件数から求める
+        if let Some(page) = self.page {
+            let max_page = PageRequest::max_page(self.page_size.unwrap_or(DEFAULT_PAGE_SIZE));
+            if ! validate_range(page , Some(1) , None) {
+                errors.add("page" , min_error("label.search.page" , 1));
+            }else if page > max_page {
+                errors.add("page" , FieldError::new("range" , Message::new("validation.range")
+                    .with_label("label" , "label.search.page").with_param("min" , 1).with_param("max" , max_page))
+                    .with_param("min" , 1).with_param("max" , max_page));
+            }
+        }
+        // page_sizeフィールドの検証　範囲チェック
+        if let Some(page_size) = self.page_size {
+            if ! validate_range(page_size , Some(1) , Some(MAX_PAGE_SIZE)) {
+                errors.add("page_size" , FieldError::new("range" , Message::new("validation.range")
+                    .with_label("label" , "label.search.page_size").with_param("min" , 1).with_param("max" , MAX_PAGE_SIZE))
+                    .with_param("min" , 1).with_param("max" , MAX_PAGE_SIZE));
+            }
         }
+        // sortフィールドの検証　並び順の種類チェック
+        if let Some(sort) = self.sort.as_ref() {
+            if ProductSortKey::try_from(sort.clone()).is_err() {
+                errors.add("sort" , choice_error("invalid" , "label.search.sort"));
+            }
+        }
+        errors.into_result()
     }
 }
-// FormをProductNameに変換する
-impl FormToDomain<ProductName> for ProductSearchForm{
-    fn convert(&self) -> Result<ProductName, AppError> {
-        Ok(ProductName::try_from(self.keyword.as_ref().unwrap().clone())?)
+// FormをProductSearchCriteriaに変換する
+impl FormToDomain<ProductSearchCriteria> for ProductSearchForm{
+    fn convert(&self) -> Result<ProductSearchCriteria, AppError> {
+        let keyword_match = match self.keyword_match.as_ref() {
+            Some(keyword_match) => KeywordMatch::try_from(keyword_match.clone())? ,
+            None => KeywordMatch::default()
+        };
+        let mut category_ids:Vec<CategoryId> = Vec::new();
+        for (index , id) in self.category_ids().into_iter().enumerate() {
+            match id {
+                Some(id) => category_ids.push(CategoryId::try_from(id)?) ,
+                None => return Err(field_error(&format!("category_ids[{}]" , index) ,
+                                               choice_error("invalid" , "label.product.category")))
+            }
+        }
+        ProductSearchCriteria::new(self.keywords() , keyword_match ,
+                                   self.min_price , self.max_price , category_ids)
+    }
+}
+// FormをPageRequestに変換する
+impl FormToDomain<PageRequest> for ProductSearchForm{
+    fn convert(&self) -> Result<PageRequest, AppError> {
+        PageRequest::new(self.page.unwrap_or(1) , self.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
+    }
+}
+// FormをProductSortKeyに変換する
+impl FormToDomain<ProductSortKey> for ProductSearchForm{
+    fn convert(&self) -> Result<ProductSortKey, AppError> {
+        match self.sort.as_ref() {
+            Some(sort) => ProductSortKey::try_from(sort.clone()) ,
+            None => Ok(ProductSortKey::default())
+        }
     }
 }
 // 商品登録
@@ -66,130 +209,463 @@ impl FormToDomain<Product> for ProductRegisterForm {
     fn convert(&self) -> Result<Product, AppError> {
         //  Categoryを生成する
         let category = Category::new(
-            CategoryId::try_from(self.category_id.unwrap().clone())?,
+            CategoryId::try_from(*required_value(&self.category_id , "category_id" , "label.product.category")?)?,
             CategoryName::try_from(String::from("dummy"))?);
         //　Productを生成して返す
         Ok(Product::new(
             ProductId::try_from(0)?,
-            ProductName::try_from(self.name.as_ref().unwrap().clone())?,
-            ProductPrice::try_from(self.price.unwrap().clone())?,
+            ProductName::try_from(required_value(&self.name , "name" , "label.product.name")?.clone())?,
+            ProductPrice::try_from(*required_value(&self.price , "price" , "label.product.price")?)?,
             Some(category)))
     }
 }
 /// 入力値検証
 impl AppValidator for ProductRegisterForm{
     fn validate_value(&self) -> Result<(), ValidationError> {
-        let mut errors:HashMap<String,String> = HashMap::new();
+        let mut errors = ValidationError::new();
         // nameフィールドの検証 未入力と文字数チェック
-        if ! validate_length(self.name.as_ref().unwrap(), Some(4), Some(20), None) {
-            errors.insert(String::from("name"),String::from("商品名は４文字以上20文字以内で入力して下さい。"));
+        if ! self.name.as_ref().is_some_and(|name| ProductName::LENGTH.contains(name)) {
+            errors.add("name" , length_error("label.product.name" , ProductName::LENGTH));
         }
         // priceフィールドの検証　未入力と範囲チェック
         if ! validate_required(&self.price) {
-            errors.insert(String::from("price"),String::from("単価は入力必須です。"));
-        }else{
-            if ! validate_range(self.price.unwrap(), Some(50), Some(100000) ){
-                errors.insert(String::from("price") , String::from("単価は50～100000までで入力して下さい。"));
-            }
+            errors.add("price" , required_error("label.product.price"));
+        }else if ! ProductPrice::RANGE.contains(self.price.unwrap()) {
+            errors.add("price" , range_error("label.product.price" , ProductPrice::RANGE));
         }
         // category_idフィールドの検証　未入力と範囲チェック
         if ! validate_required(&self.category_id) {
-            errors.insert(String::from("category_id"),String::from("カテゴリは入力必須です。"));
-        }else{
-            if ! validate_range(self.category_id.unwrap(), Some(1), Some(3) ){
-                errors.insert(String::from("category_id"),String::from("不正なカテゴリが選択されました。"));
-            }
+            errors.add("category_id" , required_error("label.product.category"));
+        }else if ! validate_range(self.category_id.unwrap(), Some(1), None ){
+            errors.add("category_id" , choice_error("range" , "label.product.category").with_param("min" , 1));
+        }
+        errors.into_result()
+    }
+}
+
+// 商品変更
+#[derive(Deserialize , Serialize , Debug , Clone)]
+pub struct ProductUpdateForm {
+    #[serde(deserialize_with = "empty_string_as_none")]
+    pub id:             Option<i32> ,
+    pub name:           Option<String> ,
+    #[serde(deserialize_with = "empty_string_as_none")]
+    pub price:          Option<i32> ,
+    #[serde(deserialize_with = "empty_string_as_none")]
+    pub category_id:    Option<i32>
+}
+/// FormをProductに変換する
+impl FormToDomain<Product> for ProductUpdateForm {
+    fn convert(&self) -> Result<Product, AppError> {
+        //  Categoryを生成する
+        let category = Category::new(
+            CategoryId::try_from(*required_value(&self.category_id , "category_id" , "label.product.category")?)?,
+            CategoryName::try_from(String::from("dummy"))?);
+        //　Productを生成して返す
+        Ok(Product::new(
+            ProductId::try_from(*required_value(&self.id , "id" , "label.product.id")?)?,
+            ProductName::try_from(required_value(&self.name , "name" , "label.product.name")?.clone())?,
+            ProductPrice::try_from(*required_value(&self.price , "price" , "label.product.price")?)?,
+            Some(category)))
+    }
+}
+/// 入力値検証
+impl AppValidator for ProductUpdateForm{
+    fn validate_value(&self) -> Result<(), ValidationError> {
+        // 商品番号以外は商品登録と同じ規則で検証する
+        let register = ProductRegisterForm {
+            name: self.name.clone() ,
+            price: self.price ,
+            category_id: self.category_id
+        };
+        let mut errors = match register.validate_value() {
+            Ok(_) => ValidationError::new() ,
+            Err(errors) => errors
+        };
+        // idフィールドの検証　未入力と範囲チェック
+        if ! validate_required(&self.id) {
+            errors.add("id" , required_error("label.product.id"));
+        }else if ! validate_range(self.id.unwrap(), Some(1), None ){
+            errors.add("id" , FieldError::new("range" , Message::new("validation.invalid_id").with_label("label" , "label.product.id")).with_param("min" , 1));
         }
-        if errors.is_empty(){
-            Ok(())
-        }else{
-            Err(ValidationError::from(errors))
+        errors.into_result()
+    }
+}
+
+// 商品削除
+#[derive(Deserialize , Serialize , Debug , Clone)]
+pub struct ProductDeleteForm {
+    #[serde(deserialize_with = "empty_string_as_none")]
+    pub id:             Option<i32>
+}
+/// FormをProductIdに変換する
+impl FormToDomain<ProductId> for ProductDeleteForm {
+    fn convert(&self) -> Result<ProductId, AppError> {
+        ProductId::try_from(*required_value(&self.id , "id" , "label.product.id")?)
+    }
+}
+/// 入力値検証
+impl AppValidator for ProductDeleteForm{
+    fn validate_value(&self) -> Result<(), ValidationError> {
+        let mut errors = ValidationError::new();
+        // idフィールドの検証　未入力と範囲チェック
+        if ! validate_required(&self.id) {
+            errors.add("id" , required_error("label.product.id"));
+        }else if ! validate_range(self.id.unwrap(), Some(1), None ){
+            errors.add("id" , FieldError::new("range" , Message::new("validation.invalid_id").with_label("label" , "label.product.id")).with_param("min" , 1));
         }
+        errors.into_result()
     }
 }
 
 // 認証
 #[derive(Debug , Clone , Deserialize , Serialize , Validate)]
 pub struct LoginForm {
-    #[validate(length(min = 6 , max = 20, message="ユーザー名は6文字以上20文字以内で入力して下さい。"))]
+    #[validate(required(message="validation.user.name_required"))]
     pub name:       Option<String> , //  ユーザー名
-    #[validate(length(min = 6 , max = 20, message="パスワードは6文字以上20文字以内で入力して下さい。"))]
+    // パスワードポリシーは登録と変更で適用する、認証では登録済のパスワードを受け付ける
+    #[validate(required(message="validation.user.password_required") ,
+        length(min = 1 , message="validation.user.password_required"))]
     pub password:   Option<String>   //  パスワード
 }
+/// FormをCredentialsに変換する
+impl FormToDomain<Credentials> for LoginForm{
+    fn convert(&self) -> Result<Credentials, AppError> {
+        Ok(Credentials::new(UserName::try_from(required_value(&self.name , "name" , "label.user.name")?.clone())?,
+                            Password::try_from(required_value(&self.password , "password" , "label.user.password")?.clone())?))
+    }
+}
+/// 入力値検証
+impl AppValidator for LoginForm {
+    fn validate_value(&self) -> Result<(), ValidationError> {
+        // 項目ごとのすべてのエラーを取得する
+        let mut errors = match self.validate() {
+            Ok(_) => ValidationError::new() ,
+            Err(validation_errors) => ValidationError::from(validation_errors)
+        };
+        // nameフィールドの検証　文字数チェック
+        if self.name.as_ref().is_some_and(|name| ! UserName::LENGTH.contains(name)) {
+            errors.add("name" , length_error("label.user.name" , UserName::LENGTH));
+        }
+        errors.into_result()
+    }
+}
+
+// ユーザー登録
+#[derive(Debug , Clone , Deserialize , Serialize , Validate)]
+pub struct UserRegisterForm {
+    #[validate(required(message="validation.user.name_required"))]
+    pub name:           Option<String> , //  ユーザー名
+    // 文字数と文字種はユーザー名、メールアドレスと合わせてパスワードポリシーで検証する
+    #[validate(required(message="validation.user.password_required"))]
+    pub password:       Option<String> , //  パスワード
+    #[validate(required(message="validation.user.confirmation_required"))]
+    pub confirmation:   Option<String> , //  確認用パスワード
+    #[validate(required(message="validation.user.mail_required"))]
+    pub mail:           Option<String>   //  メールアドレス
+}
 /// FormをUserに変換する
-impl FormToDomain<User> for LoginForm{
+impl FormToDomain<User> for UserRegisterForm {
     fn convert(&self) -> Result<User, AppError> {
-        User::new(UserName::try_from(self.name.as_ref().unwrap().clone())?,
-                  Password::try_from(self.password.as_ref().unwrap().clone())?,
-                  Mail::try_from(String::from("dummy"))?)
+        User::new(UserName::try_from(required_value(&self.name , "name" , "label.user.name")?.clone())?,
+                  Password::try_from(required_value(&self.password , "password" , "label.user.password")?.clone())?,
+                  Mail::try_from(required_value(&self.mail , "mail" , "label.user.mail")?.clone())?)
     }
 }
 /// 入力値検証
-impl AppValidator for LoginForm {
+impl AppValidator for UserRegisterForm {
     fn validate_value(&self) -> Result<(), ValidationError> {
-        // エラーメッセージを格納するHashMap
-        let mut error_messages:HashMap<String,String> = HashMap::new();
-        // フィールド毎のエラーを取得するクロージャ
-        let mut get_errors = |validation_errors: ValidationErrors, field: &str| {
-            match validation_errors.field_errors().get(field){
-                Some(errors) =>{
-                    // &&Vec<ValidationErrors>からエラーメッセージを取得する
-                    for error in errors.deref() {
-                        error_messages.insert(String::from(field),
-                                              error.message.as_ref().unwrap().to_string());
-                    }
-                }, None => ()
-            };
+        let mut errors = match self.validate() {
+            Ok(_) => ValidationError::new() ,
+            Err(validation_errors) => ValidationError::from(validation_errors)
         };
-        match self.validate() { // 検証メソッドの実行
-            Ok(_) => Ok(()) ,
-            Err(validation_errors) => {
-                // nameフィールドのエラーを取得する
-                get_errors(validation_errors.clone(), "name");
-                // passwordフィールドのエラーを取得する
-                get_errors(validation_errors.clone() , "password");
-                Err(ValidationError::from(error_messages)) // 検証エラーを返す
+        // nameフィールドの検証　文字数チェック
+        if self.name.as_ref().is_some_and(|name| ! UserName::LENGTH.contains(name)) {
+            errors.add("name" , length_error("label.user.name" , UserName::LENGTH));
+        }
+        // mailフィールドの検証　形式チェック、文字数は正規化した値で数える
+        if let Some(mail) = self.mail.as_ref() {
+            let normalized = Mail::normalize(mail);
+            if normalized.is_none() {
+                errors.add("mail" , FieldError::new("email" , Message::new("validation.user.mail_format")));
             }
+            if ! Mail::LENGTH.contains(normalized.as_deref().unwrap_or(mail.trim())) {
+                errors.add("mail" , length_error("label.user.mail" , Mail::LENGTH));
+            }
+        }
+        // パスワードと確認用パスワードの一致チェック
+        if self.confirmation.is_some() && self.password != self.confirmation {
+            errors.add("confirmation" , FieldError::new("must_match" , Message::new("validation.user.confirmation_mismatch"))
+                .with_param("other" , "password"));
+        }
+        errors.into_result()
+    }
+}
+
+// パスワード変更
+#[derive(Debug , Clone , Deserialize , Serialize , Validate)]
+pub struct PasswordChangeForm {
+    #[validate(required(message="validation.user.current_password_required"))]
+    pub current:        Option<String> , //  現在のパスワード
+    // 文字数と文字種はパスワードポリシーで検証する
+    #[validate(required(message="validation.user.password_required"))]
+    pub password:       Option<String> , //  新しいパスワード
+    #[validate(required(message="validation.user.confirmation_required"))]
+    pub confirmation:   Option<String>   //  確認用パスワード
+}
+/// FormをPasswordChangeに変換する
+impl FormToDomain<PasswordChange> for PasswordChangeForm {
+    fn convert(&self) -> Result<PasswordChange, AppError> {
+        Ok(PasswordChange::new(Password::try_from(required_value(&self.current , "current" , "label.user.current_password")?.clone())?,
+                               Password::try_from(required_value(&self.password , "password" , "label.user.password")?.clone())?))
+    }
+}
+/// 入力値検証
+impl AppValidator for PasswordChangeForm {
+    fn validate_value(&self) -> Result<(), ValidationError> {
+        let mut errors = match self.validate() {
+            Ok(_) => ValidationError::new() ,
+            Err(validation_errors) => ValidationError::from(validation_errors)
+        };
+        // パスワードと確認用パスワードの一致チェック
+        if self.confirmation.is_some() && self.password != self.confirmation {
+            errors.add("confirmation" , FieldError::new("must_match" , Message::new("validation.user.confirmation_mismatch"))
+                .with_param("other" , "password"));
         }
-           /*
-                match self.validate() { // 検証メソッドの実行
-                    Ok(_) => Ok(()) ,
-                    Err(validate_errors) => {
-                        // フィールド毎のエラーを取得する
-                        let field_errors = validate_errors.field_errors();
-                        // nameフィールドのエラーを取得してHashMapに格納する
-                        match field_errors.get("name"){
-                            Some(name_errors) =>{
-                                for name_error in *name_errors {
-                                    errors.insert(String::from("name"), name_error.message.as_ref().unwrap().to_string());
-                                }
-                            }, None => ()
-                        };
-                        // passwordフィールドのエラーを取得してHashMapに格納する
-                        match field_errors.get("password"){
-                            Some(password_errors)=>{
-                                for password_error in *password_errors {
-                                    errors.insert("password".to_string(), password_error.message.as_ref().unwrap().to_string());
-                                }
-                            },None => ()
-                        };
-                        Err(ValidationError::from(validate_errors)) // 検証エラーを返す
-                    }
-                }
-                 */
+        errors.into_result()
     }
 }
 
 #[cfg(test)]
 mod tests{
     use super::*;
+    use crate::i18n::Locale;
     use anyhow::Result;
 
     #[test]
     fn search_form_validate() -> Result<()>{
-        let form = ProductSearchForm{keyword: Some(String::from(""))};
-        let result = form.validate_value();
-        println!("{:?}" , result);
+        let form = ProductSearchForm{keyword: Some(String::from("")) , ..Default::default()};
+        assert!(form.validate_value().is_ok());
+        let form = ProductSearchForm{
+            keyword: Some(String::from("ペン")) ,
+            page: Some(0) ,
+            page_size: Some(MAX_PAGE_SIZE + 1) ,
+            sort: Some(String::from("stock_asc")) ,
+            ..Default::default()};
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.errors["page"][0].code , "range");
+        assert_eq!(result.errors["page_size"][0].code , "range");
+        assert_eq!(result.errors["sort"][0].code , "invalid");
+        let form = ProductSearchForm{
+            keyword: Some(String::from("ペン")) ,
+            sort: Some(String::from("price_desc")) ,
+            ..Default::default()};
+        assert!(form.validate_value().is_ok());
+        let page:PageRequest = form.convert()?;
+        assert_eq!(page , PageRequest::default());
+        let sort:ProductSortKey = form.convert()?;
+        assert_eq!(sort , ProductSortKey::PriceDesc);
+        Ok(())
+    }
+
+    // 読み飛ばす件数が桁あふれするページ番号は受け付けない
+    #[test]
+    fn search_form_huge_page() -> Result<()>{
+        let form = ProductSearchForm{ page: Some(u64::MAX) , page_size: Some(MAX_PAGE_SIZE) , ..Default::default() };
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.errors["page"][0].params["max"] , PageRequest::max_page(MAX_PAGE_SIZE));
+        assert!(FormToDomain::<PageRequest>::convert(&form).is_err());
+        // 上限のページ番号は受け付け、読み飛ばす件数はi64に収まる
+        let form = ProductSearchForm{ page: Some(PageRequest::max_page(MAX_PAGE_SIZE)) , page_size: Some(MAX_PAGE_SIZE) , ..Default::default() };
+        assert!(form.validate_value().is_ok());
+        let page:PageRequest = form.convert()?;
+        assert!(i64::try_from(page.offset()).is_ok());
+        assert!(PageRequest::new(PageRequest::max_page(1) + 1 , 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn search_form_criteria() -> Result<()>{
+        let form = ProductSearchForm{
+            keyword: Some(String::from("ボールペン　(黒) ")) ,
+            keyword_match: Some(String::from("or")) ,
+            min_price: Some(100) ,
+            max_price: Some(500) ,
+            category_ids: Some(String::from("1, 2")) ,
+            ..Default::default()};
+        assert!(form.validate_value().is_ok());
+        let criteria:ProductSearchCriteria = form.convert()?;
+        assert_eq!((criteria.min_price() , criteria.max_price()) , (Some(100) , Some(500)));
+        assert_eq!(criteria.keywords() , &vec![String::from("ボールペン") , String::from("(黒)")]);
+        assert_eq!(criteria.keyword_match() , KeywordMatch::Any);
+        assert_eq!(criteria.category_ids().len() , 2);
+        // 20文字を超えるキーワードも指定できる
+        let form = ProductSearchForm{
+            keyword: Some(String::from("とても長い商品名のキーワードを指定して検索する")) ,
+            ..Default::default()};
+        assert!(form.validate_value().is_ok());
+        let form = ProductSearchForm{
+            keyword_match: Some(String::from("xor")) ,
+            min_price: Some(500) ,
+            max_price: Some(100) ,
+            category_ids: Some(String::from("0,x")) ,
+            ..Default::default()};
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.errors["keyword_match"][0].code , "invalid");
+        assert_eq!(result.errors["max_price"][0].code , "range");
+        assert_eq!(result.errors["category_ids[0]"][0].code , "range");
+        assert_eq!(result.errors["category_ids[1]"][0].code , "invalid");
+        // 変換でも数値でないカテゴリ番号は検証エラーにする
+        let form = ProductSearchForm{ category_ids: Some(String::from("1,x")) , ..Default::default() };
+        let error = FormToDomain::<ProductSearchCriteria>::convert(&form).err().unwrap();
+        assert!(matches!(error , AppError::Validation(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn register_form_validate() -> Result<()>{
+        // カテゴリ番号の上限はなく、存在確認は登録時に行う
+        let form = ProductRegisterForm{
+            name: Some(String::from("油性ボールペン(緑)")) ,
+            price: Some(100) ,
+            category_id: Some(4) };
+        assert!(form.validate_value().is_ok());
+        let form = ProductRegisterForm{
+            name: Some(String::from("油性ボールペン(緑)")) ,
+            price: Some(100) ,
+            category_id: Some(0) };
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.errors["category_id"][0].code , "range");
+        Ok(())
+    }
+
+    // 入力値検証と値オブジェクトは同じ境界値で受け入れと拒否が切り替わる
+    #[test]
+    fn register_form_follows_domain_rules() {
+        let form = |name: String , price: i32| ProductRegisterForm{
+            name: Some(name) , price: Some(price) , category_id: Some(1) };
+        let rule = ProductName::LENGTH;
+        for length in [rule.min - 1 , rule.min , rule.max , rule.max + 1] {
+            let name = "a".repeat(length);
+            assert_eq!(form(name.clone() , 100).validate_value().is_ok() ,
+                       ProductName::try_from(name).is_ok() , "{}" , length);
+        }
+        let rule = ProductPrice::RANGE;
+        for price in [rule.min - 1 , rule.min , rule.max , rule.max + 1] {
+            let result = form(String::from("油性ボールペン(緑)") , price).validate_value();
+            assert_eq!(result.is_ok() , ProductPrice::try_from(price).is_ok() , "{}" , price);
+            // 検証を通過した値は値オブジェクトに変換できる
+            if result.is_ok() {
+                assert!(FormToDomain::<Product>::convert(&form(String::from("油性ボールペン(緑)") , price)).is_ok());
+            }
+        }
+        let result = form(String::from("油性ボールペン(緑)") , rule.max + 1).validate_value().err().unwrap();
+        assert_eq!(result.errors["price"][0].params["max"] , rule.max);
+    }
+
+    #[test]
+    fn user_form_follows_domain_rules() {
+        let form = |name: String , mail: String| UserRegisterForm{
+            name: Some(name) , password: Some(String::from("pass003")) ,
+            confirmation: Some(String::from("pass003")) , mail: Some(mail) };
+        let rule = UserName::LENGTH;
+        for length in [rule.min - 1 , rule.min , rule.max , rule.max + 1] {
+            let name = "a".repeat(length);
+            assert_eq!(form(name.clone() , String::from("user003@sample.com")).validate_value().is_ok() ,
+                       UserName::try_from(name.clone()).is_ok() , "{}" , length);
+            assert_eq!(LoginForm{ name: Some(name.clone()) , password: Some(String::from("pass003")) }.validate_value().is_ok() ,
+                       UserName::try_from(name).is_ok() , "{}" , length);
+        }
+        let rule = Mail::LENGTH;
+        for length in [rule.max , rule.max + 1] {
+            let mail = format!("{}@{}.{}.{}.com" , "a".repeat(length - 196) , "b".repeat(63) , "c".repeat(63) , "d".repeat(63));
+            assert_eq!(form(String::from("user003") , mail.clone()).validate_value().is_ok() ,
+                       Mail::try_from(mail).is_ok() , "{}" , length);
+        }
+        for mail in ["dummy" , "user003@Sample.COM" , "user003@例え.jp" , "user..003@sample.com"] {
+            assert_eq!(form(String::from("user003") , mail.to_string()).validate_value().is_ok() ,
+                       Mail::try_from(mail.to_string()).is_ok() , "{}" , mail);
+        }
+    }
+
+    // 変換時の値オブジェクトのルール違反は入力値の誤りとして扱い、内部エラーにしない
+    #[test]
+    fn convert_rule_violation() {
+        let form = UserRegisterForm{ name: Some(String::from("user003")) , password: Some(String::from("pass003")) ,
+            confirmation: Some(String::from("pass003")) , mail: Some(String::from("user003")) };
+        let error = FormToDomain::<User>::convert(&form).err().unwrap();
+        assert!(matches!(error , AppError::Rule(_)) , "{:?}" , error);
+        assert_eq!(error.status() , 400);
+        let form = ProductSearchForm{ min_price: Some(500) , max_price: Some(100) , ..Default::default() };
+        let error = FormToDomain::<ProductSearchCriteria>::convert(&form).err().unwrap();
+        assert_eq!(error.status() , 400);
+        assert_eq!(error.problem().detail , Some(error.message()));
+    }
+
+    // 検証しなかった未入力の項目は変換で検証エラーになる
+    #[test]
+    fn product_forms_required() {
+        let form = ProductRegisterForm{ name: None , price: Some(100) , category_id: Some(1) };
+        match FormToDomain::<Product>::convert(&form) {
+            Err(AppError::Validation(errors)) => assert_eq!(errors.errors["name"][0].code , "required") ,
+            result => panic!("{:?}" , result)
+        }
+        let form = ProductUpdateForm{ id: Some(1) , name: Some(String::from("水性ボールペン(紫)")) , price: None , category_id: Some(1) };
+        match FormToDomain::<Product>::convert(&form) {
+            Err(AppError::Validation(errors)) => assert_eq!(errors.errors["price"][0].code , "required") ,
+            result => panic!("{:?}" , result)
+        }
+        match FormToDomain::<ProductId>::convert(&ProductDeleteForm{ id: None }) {
+            Err(AppError::Validation(errors)) => assert_eq!(errors.errors["id"][0].code , "required") ,
+            result => panic!("{:?}" , result)
+        }
+    }
+
+    // 未入力の項目は検証でも変換でもエラーになる
+    #[test]
+    fn login_form_required() {
+        let form = LoginForm{ name: None , password: Some(String::from("x")) };
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.errors["name"][0].code , "required");
+        let error = FormToDomain::<Credentials>::convert(&form).err().unwrap();
+        assert!(matches!(error , AppError::Validation(_)));
+        let form = UserRegisterForm{ name: Some(String::from("user003")) , password: Some(String::from("pass003")) ,
+            confirmation: Some(String::from("pass003")) , mail: None };
+        match FormToDomain::<User>::convert(&form) {
+            Err(AppError::Validation(errors)) => assert_eq!(errors.errors["mail"][0].code , "required") ,
+            result => panic!("{:?}" , result)
+        }
+        let form = PasswordChangeForm{ current: None , password: Some(String::from("sakura2022")) , confirmation: None };
+        match FormToDomain::<PasswordChange>::convert(&form) {
+            Err(AppError::Validation(errors)) => assert_eq!(errors.errors["current"][0].code , "required") ,
+            result => panic!("{:?}" , result)
+        }
+    }
+
+    #[test]
+    fn update_form_validate() -> Result<()>{
+        let form = ProductUpdateForm{
+            id: Some(1) ,
+            name: Some(String::from("水性ボールペン(紫)")) ,
+            price: Some(150) ,
+            category_id: Some(1) };
+        assert!(form.validate_value().is_ok());
+        let form = ProductUpdateForm{
+            id: None ,
+            name: Some(String::from("水性ボールペン(紫)")) ,
+            price: Some(10) ,
+            category_id: Some(1) };
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.errors["id"][0].code , "required");
+        assert_eq!(result.errors["price"][0].code , "range");
+        Ok(())
+    }
+
+    #[test]
+    fn delete_form_validate() -> Result<()>{
+        assert!(ProductDeleteForm{ id: Some(1) }.validate_value().is_ok());
+        assert!(ProductDeleteForm{ id: None }.validate_value().is_err());
+        assert!(ProductDeleteForm{ id: Some(0) }.validate_value().is_err());
         Ok(())
     }
 
@@ -198,9 +674,60 @@ mod tests{
         let form = LoginForm {
             name: Some(String::from("")) ,
             password: Some(String::from("")) };
-        let result = form.validate_value();
-        println!("{:?}" , result);
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.messages("name") , vec!["ユーザー名は6文字以上20文字以内で入力して下さい。"]);
+        assert_eq!(result.localize(Locale::En).messages("name") , vec!["User name must be 6 to 20 characters long."]);
+        assert_eq!(result.localize(Locale::En).messages("password") , vec!["Password is required."]);
+        // 登録済のパスワードはポリシーを満たさなくても認証に使える
+        assert!(LoginForm{ name: Some(String::from("user001")) , password: Some(String::from("p")) }.validate_value().is_ok());
         Ok(())
     }
 
+    #[test]
+    fn user_register_form_validate() -> Result<()>{
+        let form = UserRegisterForm {
+            name: Some(String::from("user003")) ,
+            password: Some(String::from("pass003")) ,
+            confirmation: Some(String::from("pass003")) ,
+            mail: Some(String::from("user003@sample.com")) };
+        assert!(form.validate_value().is_ok());
+        let form = UserRegisterForm {
+            name: Some(String::from("usr")) ,
+            password: Some(String::from("pass003")) ,
+            confirmation: Some(String::from("pass004")) ,
+            mail: Some(String::from("user003")) };
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.errors["name"][0].code , "length");
+        assert_eq!(result.errors["mail"][0].code , "email");
+        assert!(!result.errors.contains_key("password"));
+        assert_eq!(result.errors["confirmation"][0].code , "must_match");
+        // 1つの項目の複数のエラーをすべて返す
+        let form = UserRegisterForm {
+            name: Some(String::from("user003")) ,
+            password: Some(String::from("pass003")) ,
+            confirmation: Some(String::from("pass003")) ,
+            mail: Some("a_very_long_mail_address_without_at_mark".repeat(7)) };
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.messages("mail").len() , 2);
+        Ok(())
+    }
+
+    #[test]
+    fn password_change_form_validate() -> Result<()>{
+        let form = PasswordChangeForm {
+            current: Some(String::from("pass001")) ,
+            password: Some(String::from("sakura2022")) ,
+            confirmation: Some(String::from("sakura2022")) };
+        assert!(form.validate_value().is_ok());
+        let (current , password) = (Password::try_from(String::from("pass001"))? , Password::try_from(String::from("sakura2022"))?);
+        assert_eq!(form.convert()? , PasswordChange::new(current , password));
+        let form = PasswordChangeForm {
+            current: None ,
+            password: Some(String::from("sakura2022")) ,
+            confirmation: Some(String::from("sakura2023")) };
+        let result = form.validate_value().err().unwrap();
+        assert_eq!(result.messages("current") , vec!["現在のパスワードは入力必須です。"]);
+        assert_eq!(result.errors["confirmation"][0].code , "must_match");
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -1,31 +1,152 @@
-use jsonwebtoken::{DecodingKey, EncodingKey, TokenData, Validation};
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use anyhow::{anyhow, Context};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use jsonwebtoken::errors::ErrorKind;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::Value;
+use crate::{AppError, Result};
+use crate::config::{JwtConfig, JwtKeyConfig};
 
-pub const JWT_SECRET_KEY:  &str = "app-secret";  // シークレットキー
 pub const JWT_HEADER_KEY:  &str = "Authorization";   // ヘッダーキー
 pub const JWT_COOKIE_KEY:  &str = "Authorization";   // Cookieキー
 
+///
+/// 失効したアクセストークンの識別子(jti)の一覧
+/// 検証のたびに参照するため、永続化した一覧をメモリ上に読込んで利用する
+/// 他のインスタンスで失効したトークンは定期的な読込み(AppServiceProvider::spawn_denylist_reload)で反映する
+///
+pub trait JwtDenylist : Send + Sync + 'static {
+    /// トークンを失効させる、有効期限を過ぎた識別子は破棄してよい
+    fn deny(&self , jti: &str , expires_at: u64);
+    /// 失効したトークンか判定する
+    fn is_denied(&self , jti: &str) -> bool;
+}
+///
+/// メモリ上の失効したトークンの一覧
+///
+#[derive(Default)]
+pub struct MemoryDenylist {
+    entries: RwLock<HashMap<String , u64>>  // jtiと有効期限
+}
+impl MemoryDenylist {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn JwtDenylist> {
+        Arc::new(Self::default())
+    }
+}
+impl JwtDenylist for MemoryDenylist {
+    fn deny(&self , jti: &str , expires_at: u64) {
+        if let Ok(mut entries) = self.entries.write() {
+            let now = jsonwebtoken::get_current_timestamp();
+            entries.retain(|_ , expires_at| *expires_at > now);
+            entries.insert(jti.to_string() , expires_at);
+        }
+    }
+    fn is_denied(&self , jti: &str) -> bool {
+        match self.entries.read() {
+            Ok(entries) => entries.contains_key(jti) ,
+            // 判定できない場合は失効として扱う
+            Err(_) => true
+        }
+    }
+}
+
+///
+/// 設定から生成した署名鍵と検証鍵
+/// 署名は現在の鍵で行い、検証はトークンのkidに対応する鍵で行う
+///
+pub struct JwtKeys {
+    header:     Header ,                                  // 署名に使うヘッダー
+    encoding:   EncodingKey ,                             // 署名鍵
+    decoding:   HashMap<String , (Algorithm , DecodingKey)> , // kidごとの検証鍵
+    current_kid: String ,                                 // 現在の鍵のkid
+    validation: Validation ,                              // 検証条件
+    denylist:   Arc<dyn JwtDenylist>                      // 失効したトークン
+}
+impl JwtKeys {
+    /// JWTの設定から鍵を生成する、PEMファイルはこの時点で読込む
+    pub fn from_config(config: &JwtConfig) -> Result<Self> {
+        let algorithm = algorithm(&config.key)?;
+        let header = Header{
+            typ: Some(String::from("JWT")) ,    // typeの設定
+            alg: algorithm ,                    // アルゴリズムの設定
+            kid: if config.key.kid.is_empty() { None } else { Some(config.key.kid.clone()) } ,
+            ..Default::default()
+        };
+        let encoding = encoding_key(&config.key)?;
+        let mut decoding = HashMap::new();
+        decoding.insert(config.key.kid.clone() , (algorithm , decoding_key(&config.key)?));
+        for key in config.keys.iter() {
+            decoding.insert(key.kid.clone() , (self::algorithm(key)? , decoding_key(key)?));
+        }
+        // 有効期限、発行者、対象者を検証する
+        let mut validation = Validation::new(algorithm);
+        validation.leeway = config.leeway;
+        if let Some(issuer) = config.issuer.as_ref() {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = config.audience.as_ref() {
+            validation.set_audience(&[audience]);
+        }
+        validation.validate_nbf = true;
+        Ok(Self{ header , encoding , decoding , current_kid: config.key.kid.clone() , validation , denylist: MemoryDenylist::new() })
+    }
+    /// 失効したトークンの一覧を置き換える
+    pub fn with_denylist(mut self , denylist: Arc<dyn JwtDenylist>) -> Self {
+        self.denylist = denylist;
+        self
+    }
+    /// 失効したトークンの一覧
+    pub fn denylist(&self) -> &Arc<dyn JwtDenylist> {
+        &self.denylist
+    }
+    /// クレームに署名してトークンを生成する
+    pub fn encode<T:Serialize>(&self , claims: &T) -> Result<String> {
+        // Headerとクレームでトークンを生成
+        match jsonwebtoken::encode(&self.header , claims , &self.encoding) {
+            Ok(token) => Ok(token) ,
+            Err(error) => Err(AppError::from(anyhow::Error::new(error).context("トークンを生成できません。")))
+        }
+    }
+    /// トークンを検証してクレームを取り出す
+    pub fn decode<T:DeserializeOwned>(&self , token: &str) -> std::result::Result<TokenData<T> , jsonwebtoken::errors::Error> {
+        // kidに対応する検証鍵を選ぶ、kidのないトークンは現在の鍵で検証する
+        let kid = jsonwebtoken::decode_header(token)?.kid.unwrap_or_else(|| self.current_kid.clone());
+        let (algorithm , key) = match self.decoding.get(&kid) {
+            Some(key) => key ,
+            None => return Err(ErrorKind::InvalidToken.into())
+        };
+        // 鍵のアルゴリズム以外で署名されたトークンは受け付けない
+        let mut validation = self.validation.clone();
+        validation.algorithms = vec![*algorithm];
+        let data = jsonwebtoken::decode::<Value>(token , key , &validation)?;
+        // 失効したトークンは受け付けない
+        if let Some(jti) = data.claims.get("jti").and_then(Value::as_str) {
+            if self.denylist.is_denied(jti) {
+                return Err(ErrorKind::InvalidToken.into());
+            }
+        }
+        Ok(TokenData{ header: data.header , claims: serde_json::from_value(data.claims)? })
+    }
+}
+
 ///
 /// Claimsの生成
 ///
 pub trait ClaimsGenerator<T>{
-    fn generate(_: &T) -> Self;
+    fn generate(_: &T , config: &JwtConfig) -> Self;
 }
 ///
 /// JWTトークンエンコード
 ///
 pub trait JwtEncoder {
     // JWTトークン生成
-    fn encode<T:Serialize>(claims: &T) -> String {
-        // Headerの生成
-        let mut header = jsonwebtoken::Header::default();
-        header.typ = Some(String::from("JWT")); // typeの設定
-        header.alg = jsonwebtoken::Algorithm::HS256; // アルゴリズムの設定
-        // Headerとクレームでトークンを生成
-        jsonwebtoken::encode(&header , &claims ,
-                             // シークレットキーでエンコード
-                             &EncodingKey::from_secret(JWT_SECRET_KEY.as_ref())).unwrap()
+    fn encode<T:Serialize>(keys: &JwtKeys , claims: &T) -> Result<String> {
+        keys.encode(claims)
     }
 }
 ///
@@ -33,16 +154,153 @@ pub trait JwtEncoder {
 ///
 pub trait JwtDecoder<T:DeserializeOwned , E , R> {
     // ヘッダーの解析
-    fn parse_header(&self , request: &R) -> Result<String , E>;
-    // トークンの検証とデコード
-    fn decode(&self , token: &str) -> Result<TokenData<T> , jsonwebtoken::errors::Error> {
-        match jsonwebtoken::decode::<T>(
-            //　シークレットキーでデコード
-            token , &DecodingKey::from_secret(JWT_SECRET_KEY.as_ref()),
-            // トークンの検証
-            &Validation::default()) {
-            Ok(token) => Ok(token),
-            Err(error) => Err(error)
-        }
-    }
-}
\ No newline at end of file
+    fn parse_header(&self , request: &R) -> std::result::Result<String , E>;
+    // トークンの検証とデコード、失効したトークンはInvalidTokenになる
+    fn decode(&self , keys: &JwtKeys , token: &str) -> std::result::Result<TokenData<T> , jsonwebtoken::errors::Error> {
+        keys.decode(token)
+    }
+}
+
+// 鍵の署名アルゴリズム
+fn algorithm(key: &JwtKeyConfig) -> Result<Algorithm> {
+    match Algorithm::from_str(&key.algorithm) {
+        Ok(algorithm @ (Algorithm::HS256 | Algorithm::RS256 | Algorithm::ES256)) => Ok(algorithm) ,
+        _ => Err(AppError::from(anyhow!("署名アルゴリズム:{}は未対応です。" , key.algorithm)))
+    }
+}
+// PEM文字列またはファイルの内容を返す
+fn pem(value: &Option<String> , file: &Option<String> , kid: &str) -> Result<Vec<u8>> {
+    match (value , file) {
+        (Some(pem) , _) => Ok(pem.as_bytes().to_vec()) ,
+        (None , Some(file)) => Ok(fs::read(file)
+            .with_context(|| format!("鍵ファイル:{}を読込めません。" , file))?) ,
+        (None , None) => Err(AppError::from(anyhow!("鍵(kid={})のPEMが設定されていません。" , kid)))
+    }
+}
+// 署名鍵を生成する
+fn encoding_key(key: &JwtKeyConfig) -> Result<EncodingKey> {
+    let result = match algorithm(key)? {
+        Algorithm::HS256 => return Ok(EncodingKey::from_secret(key.secret.as_ref())) ,
+        Algorithm::RS256 => EncodingKey::from_rsa_pem(&pem(&key.private_key , &key.private_key_file , &key.kid)?) ,
+        _ => EncodingKey::from_ec_pem(&pem(&key.private_key , &key.private_key_file , &key.kid)?)
+    };
+    result.with_context(|| format!("鍵(kid={})の秘密鍵が不正です。" , key.kid)).map_err(AppError::from)
+}
+// 検証鍵を生成する
+fn decoding_key(key: &JwtKeyConfig) -> Result<DecodingKey> {
+    let result = match algorithm(key)? {
+        Algorithm::HS256 => return Ok(DecodingKey::from_secret(key.secret.as_ref())) ,
+        Algorithm::RS256 => DecodingKey::from_rsa_pem(&pem(&key.public_key , &key.public_key_file , &key.kid)?) ,
+        _ => DecodingKey::from_ec_pem(&pem(&key.public_key , &key.public_key_file , &key.kid)?)
+    };
+    result.with_context(|| format!("鍵(kid={})の公開鍵が不正です。" , key.kid)).map_err(AppError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    // テスト専用の鍵(src/presentation/testdata)
+    const TESTDATA: &str = concat!(env!("CARGO_MANIFEST_DIR") , "/src/presentation/testdata/");
+
+    #[derive(Debug , Serialize , Deserialize , PartialEq)]
+    struct Claims {
+        sub: String ,
+        exp: u64 ,
+        iss: String ,
+        aud: String
+    }
+    impl Claims {
+        fn new(iss: &str , aud: &str) -> Self {
+            Self{ sub: String::from("user001") , exp: jsonwebtoken::get_current_timestamp() + 60 ,
+                  iss: iss.to_string() , aud: aud.to_string() }
+        }
+    }
+    struct Encoder;
+    impl JwtEncoder for Encoder {}
+    struct Decoder;
+    impl JwtDecoder<Claims , (), ()> for Decoder {
+        fn parse_header(&self, _: &()) -> std::result::Result<String , ()> {
+            Err(())
+        }
+    }
+
+    fn config(kid: &str , algorithm: &str) -> JwtConfig {
+        let mut config = JwtConfig{ issuer: Some(String::from("app_commons")) ,
+                                    audience: Some(String::from("sample")) , ..Default::default() };
+        config.key.kid = kid.to_string();
+        config.key.algorithm = algorithm.to_string();
+        let name = algorithm.to_lowercase();
+        config.key.private_key_file = Some(format!("{}{}_private.pem" , TESTDATA , name));
+        config.key.public_key_file = Some(format!("{}{}_public.pem" , TESTDATA , name));
+        config
+    }
+
+    #[test]
+    fn encode_and_decode() -> anyhow::Result<()> {
+        for algorithm in ["HS256" , "RS256" , "ES256"] {
+            let keys = JwtKeys::from_config(&config("2022-10" , algorithm))?;
+            let claims = Claims::new("app_commons" , "sample");
+            let token = Encoder::encode(&keys , &claims)?;
+            let data = Decoder.decode(&keys , &token)?;
+            assert_eq!(data.header.kid , Some(String::from("2022-10")));
+            assert_eq!(data.header.alg , Algorithm::from_str(algorithm)?);
+            assert_eq!(data.claims , claims);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn rotation() -> anyhow::Result<()> {
+        // ローテーション前の鍵で署名されたトークン
+        let old = JwtKeys::from_config(&config("2022-09" , "HS256"))?;
+        let token = Encoder::encode(&old , &Claims::new("app_commons" , "sample"))?;
+        // 現在の鍵をRS256に変更し、旧鍵を検証用に残す
+        let mut current = config("2022-10" , "RS256");
+        current.keys.push(config("2022-09" , "HS256").key);
+        let keys = JwtKeys::from_config(&current)?;
+        assert!(Decoder.decode(&keys , &token).is_ok());
+        // 旧鍵を外すと検証できない
+        let keys = JwtKeys::from_config(&config("2022-10" , "RS256"))?;
+        let error = Decoder.decode(&keys , &token).err().unwrap();
+        assert_eq!(error.kind() , &ErrorKind::InvalidToken);
+        Ok(())
+    }
+
+    #[test]
+    fn rotation_without_kid() -> anyhow::Result<()> {
+        // kidを設定する前に発行したトークン
+        let old = JwtKeys::from_config(&config("" , "HS256"))?;
+        let token = Encoder::encode(&old , &Claims::new("app_commons" , "sample"))?;
+        assert_eq!(jsonwebtoken::decode_header(&token)?.kid , None);
+        // 同じ鍵にkidを設定しても現在の鍵で検証できる
+        let mut current = config("2022-10" , "HS256");
+        current.keys.push(config("2022-09" , "RS256").key);
+        let keys = JwtKeys::from_config(&current)?;
+        assert!(Decoder.decode(&keys , &token).is_ok());
+        // 現在の鍵を変更した後は検証できない
+        let keys = JwtKeys::from_config(&config("2022-10" , "RS256"))?;
+        assert!(Decoder.decode(&keys , &token).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validation() -> anyhow::Result<()> {
+        let keys = JwtKeys::from_config(&config("" , "HS256"))?;
+        let token = Encoder::encode(&keys , &Claims::new("other" , "sample"))?;
+        assert_eq!(Decoder.decode(&keys , &token).err().unwrap().kind() , &ErrorKind::InvalidIssuer);
+        let token = Encoder::encode(&keys , &Claims::new("app_commons" , "other"))?;
+        assert_eq!(Decoder.decode(&keys , &token).err().unwrap().kind() , &ErrorKind::InvalidAudience);
+        // 許容誤差を超えて期限切れのトークン
+        let mut claims = Claims::new("app_commons" , "sample");
+        claims.exp -= 180;
+        let token = Encoder::encode(&keys , &claims)?;
+        assert_eq!(Decoder.decode(&keys , &token).err().unwrap().kind() , &ErrorKind::ExpiredSignature);
+        // 鍵ファイルが存在しない
+        let mut config = config("" , "RS256");
+        config.key.private_key_file = Some(String::from("not_found.pem"));
+        assert!(JwtKeys::from_config(&config).is_err());
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
 pub mod forms;
 pub mod jwt;
-pub mod validate;
\ No newline at end of file
+pub mod decoder;
+pub mod claims;
+pub mod validate;
+#[cfg(feature = "actix-web")]
+pub mod actix;
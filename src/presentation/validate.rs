@@ -1,23 +1,154 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use thiserror::Error;
+use validator::{ValidationErrors, ValidationErrorsKind};
+use crate::i18n::{Locale, Message};
+
+///
+/// 項目の検証エラー
+/// codeは検証規則(required,length,range,email,must_match,invalidなど)、paramsは規則の引数(min,maxなど)
+/// messageは既定のロケールで表示した文字列、localize()で他のロケールに変換する
+///
+#[derive(Debug , Clone , PartialEq , Serialize , Deserialize)]
+pub struct FieldError {
+    pub code:    String ,       // 検証規則
+    pub message: String ,       // エラーメッセージ
+    #[serde(default , skip_serializing_if = "BTreeMap::is_empty")]
+    pub params:  BTreeMap<String , Value> , // 検証規則の引数
+    #[serde(skip)]
+    pub source:  Option<Message>            // メッセージのキーと引数
+}
+impl FieldError {
+    pub fn new(code: &str , message: impl Into<Message>) -> Self {
+        let message = message.into();
+        Self{ code: code.to_string() , message: message.to_string() , params: BTreeMap::new() , source: Some(message) }
+    }
+    /// 指定されたロケールのメッセージに変換する
+    pub fn localize(&self , locale: Locale) -> Self {
+        let mut error = self.clone();
+        if let Some(source) = self.source.as_ref() {
+            error.message = source.render(locale);
+        }
+        error
+    }
+    /// 検証規則の引数を追加する
+    pub fn with_param<T: Serialize>(mut self , name: &str , value: T) -> Self {
+        self.params.insert(name.to_string() , serde_json::to_value(value).unwrap_or(Value::Null));
+        self
+    }
+}
 
 ///
 /// 入力値検証エラー
+/// 項目名ごとに複数のエラーを保持する、入れ子の項目は"items[0].name"のように表す
 ///
-#[derive(Debug , Error)]
+#[derive(Debug , Clone , Default , PartialEq , Error , Serialize , Deserialize)]
+#[serde(transparent)]
 pub struct ValidationError {
-    pub errors: HashMap<String , String>
+    pub errors: BTreeMap<String , Vec<FieldError>>
+}
+impl ValidationError {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// 項目のエラーを追加する
+    pub fn add(&mut self , field: &str , error: FieldError) {
+        self.errors.entry(field.to_string()).or_default().push(error);
+    }
+    /// 入れ子の項目のエラーを"親.子"の項目名で追加する
+    pub fn nest(&mut self , parent: &str , errors: ValidationError) {
+        for (field , field_errors) in errors.errors {
+            self.errors.entry(format!("{}.{}" , parent , field)).or_default().extend(field_errors);
+        }
+    }
+    /// リストの要素のエラーを"親[添字].子"の項目名で追加する
+    pub fn nest_index(&mut self , parent: &str , index: usize , errors: ValidationError) {
+        self.nest(&format!("{}[{}]" , parent , index) , errors);
+    }
+    /// 他の検証エラーをまとめる
+    pub fn merge(&mut self , errors: ValidationError) {
+        for (field , field_errors) in errors.errors {
+            self.errors.entry(field).or_default().extend(field_errors);
+        }
+    }
+    /// エラーがないか判定する
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// 項目のエラーメッセージ
+    pub fn messages(&self , field: &str) -> Vec<&str> {
+        match self.errors.get(field) {
+            Some(errors) => errors.iter().map(|error| error.message.as_str()).collect() ,
+            None => Vec::new()
+        }
+    }
+    /// 指定されたロケールのメッセージに変換する
+    pub fn localize(&self , locale: Locale) -> Self {
+        let errors = self.errors.iter().map(|(field , errors)|
+            (field.clone() , errors.iter().map(|error| error.localize(locale)).collect())).collect();
+        Self{ errors }
+    }
+    /// エラーがなければOk、あればErrを返す
+    pub fn into_result(self) -> Result<() , ValidationError> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
 }
+// 項目ごとに"項目名: メッセージ, メッセージ"の形式で出力する
 impl Display for ValidationError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f , "{}" , self)
+        let fields: Vec<String> = self.errors.iter().map(|(field , errors)| {
+            let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+            format!("{}: {}" , field , messages.join(", "))
+        }).collect();
+        write!(f , "{}" , fields.join("; "))
     }
 }
+// 項目名とメッセージの組から生成する
 impl From<HashMap<String , String>> for ValidationError {
-    fn from(_errors: HashMap<String, String>) -> Self {
-        Self{errors:_errors}
+    fn from(messages: HashMap<String, String>) -> Self {
+        let mut errors = Self::new();
+        for (field , message) in messages {
+            errors.add(&field , FieldError::new("invalid" , message.as_str()));
+        }
+        errors
+    }
+}
+// validatorクレートの検証結果から生成する、入れ子の構造体とリストの項目名も展開する
+impl From<ValidationErrors> for ValidationError {
+    fn from(validation_errors: ValidationErrors) -> Self {
+        let mut errors = Self::new();
+        for (field , kind) in validation_errors.into_errors() {
+            match kind {
+                ValidationErrorsKind::Field(field_errors) => {
+                    for error in field_errors {
+                        // 属性のmessageはメッセージカタログのキーとして扱う
+                        let mut message = match error.message.as_ref() {
+                            Some(key) => Message::new(key) ,
+                            None => Message::new("validation.invalid").with_label("label" , field)
+                        };
+                        // 入力値は画面やログに出さない
+                        let params: BTreeMap<String , Value> = error.params.iter()
+                            .filter(|(name , _)| *name != "value")
+                            .map(|(name , value)| (name.to_string() , value.clone())).collect();
+                        message.params.extend(params.clone());
+                        let mut field_error = FieldError::new(&error.code , message);
+                        field_error.params = params;
+                        errors.add(field , field_error);
+                    }
+                },
+                ValidationErrorsKind::Struct(nested) => errors.nest(field , Self::from(*nested)) ,
+                ValidationErrorsKind::List(items) => {
+                    for (index , nested) in items {
+                        errors.nest_index(field , index , Self::from(*nested));
+                    }
+                }
+            }
+        }
+        errors
     }
 }
 ///
@@ -25,4 +156,60 @@ impl From<HashMap<String , String>> for ValidationError {
 ///
 pub trait AppValidator {
     fn validate_value(&self) -> Result<() , ValidationError>;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Item {
+        #[validate(length(min = 1 , max = 5 , message = "品名は5文字以内で入力して下さい。"))]
+        name: String
+    }
+    #[derive(Validate)]
+    struct Order {
+        #[validate(length(min = 6 , message = "6文字以上で入力して下さい。") , email)]
+        mail: String ,
+        #[validate]
+        items: Vec<Item>
+    }
+
+    #[test]
+    fn multiple_and_nested() -> anyhow::Result<()> {
+        let order = Order{ mail: String::from("abc") ,
+            items: vec![Item{ name: String::from("ペン") } , Item{ name: String::from("ボールペン赤") }] };
+        let errors = ValidationError::from(order.validate().err().unwrap());
+        // 1つの項目に複数のエラーを保持する
+        assert_eq!(errors.messages("mail").len() , 2);
+        let length = errors.errors["mail"].iter().find(|error| error.code == "length").unwrap();
+        assert_eq!(length.params["min"] , 6);
+        assert!(!length.params.contains_key("value"));
+        // リストの要素は添字付きの項目名になる
+        assert_eq!(errors.messages("items[1].name") , vec!["品名は5文字以内で入力して下さい。"]);
+        assert!(!errors.errors.contains_key("items[0].name"));
+        let json = serde_json::to_value(&errors)?;
+        assert_eq!(json["items[1].name"][0]["code"] , "length");
+        assert_eq!(json["items[1].name"][0]["params"]["max"] , 5);
+        // メッセージのないエラーは項目名を含む既定のメッセージになる
+        assert_eq!(errors.localize(Locale::En).messages("mail") ,
+                   vec!["6文字以上で入力して下さい。" , "mail has an invalid value."]);
+        Ok(())
+    }
+
+    #[test]
+    fn build_and_display() {
+        let mut errors = ValidationError::new();
+        assert!(errors.clone().into_result().is_ok());
+        errors.add("price" , FieldError::new("required" , "単価は入力必須です。"));
+        errors.add("name" , FieldError::new("length" , "4文字以上で入力して下さい。").with_param("min" , 4));
+        errors.add("name" , FieldError::new("invalid" , "使用できない文字が含まれています。"));
+        let mut address = ValidationError::new();
+        address.add("zip" , FieldError::new("invalid" , "郵便番号が不正です。"));
+        errors.nest("address" , address);
+        assert_eq!(errors.to_string() ,
+            "address.zip: 郵便番号が不正です。; name: 4文字以上で入力して下さい。, 使用できない文字が含まれています。; price: 単価は入力必須です。");
+        assert!(errors.into_result().is_err());
+    }
+}
@@ -1,46 +1,46 @@
 use std::sync::Arc;
 use async_trait::async_trait;
-use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use sea_orm::DatabaseTransaction;
 use crate::{AppError, Result};
+use crate::i18n::Message;
 use crate::domain::entities::Category;
 use crate::domain::repositories::CategoryRepository;
 use crate::domain::services::CategoryService;
 use crate::domain::values::categories::CategoryId;
 use crate::domain::values::ValueInto;
 use crate::infrastructure::sea_orm::repositories::category::CategoryRepositoryImpl;
+use crate::infrastructure::transaction::Transaction;
 
 ///
 /// カテゴリサービスの実装
 ///
-pub struct CategoryServiceImpl{
-    repository: Arc<dyn CategoryRepository<Transaction=DatabaseTransaction>>
+pub struct CategoryServiceImpl<T: Transaction>{
+    repository: Arc<dyn CategoryRepository<Transaction=T>>
 }
-impl CategoryServiceImpl{
-    pub fn new() -> Arc<dyn CategoryService<Database=DatabaseConnection>> {
+impl CategoryServiceImpl<DatabaseTransaction>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn CategoryService<Transaction=DatabaseTransaction>> {
         Arc::new(Self{ repository: CategoryRepositoryImpl::new() })
     }
 }
+impl<T: Transaction> CategoryServiceImpl<T>{
+    // 指定されたRepositoryを利用するインスタンスを返す
+    pub fn with_repository(repository: Arc<dyn CategoryRepository<Transaction=T>>)
+        -> Arc<dyn CategoryService<Transaction=T>> {
+        Arc::new(Self{ repository })
+    }
+}
 #[async_trait]
-impl CategoryService for CategoryServiceImpl{
-    type Database = sea_orm::DatabaseConnection;
-    async fn all(&self, db: &Self::Database) -> Result<Vec<Category>> {
-        match db.begin().await {
-            Ok(tran) => Ok(self.repository.select_all(&tran).await?) ,
-            Err(error) => Err(AppError::from(error))
-        }
+impl<T: Transaction> CategoryService for CategoryServiceImpl<T>{
+    type Transaction = T;
+    async fn all(&self, tran: &Self::Transaction) -> Result<Vec<Category>> {
+        self.repository.select_all(tran).await
     }
 
-    async fn by_id(&self, db: &Self::Database , id: &CategoryId) -> Result<Category> {
-        match db.begin().await {
-            Ok(tran) => {
-                let category = self.repository.select_by_id(&tran, id).await?;
-                if category.is_some() {
-                    Ok(category.unwrap())
-                } else {
-                    Err(AppError::SearchError(format!("カテゴリ番号{}に該当データがありません。", id.value())))
-                }
-            },
-            Err(error) => Err(AppError::from(error))
+    async fn by_id(&self, tran: &Self::Transaction , id: &CategoryId) -> Result<Category> {
+        match self.repository.select_by_id(tran, id).await? {
+            Some(category) => Ok(category) ,
+            None => Err(AppError::NotFound(Message::new("category.not_found").with_param("id" , id.value())))
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use sea_orm::DatabaseTransaction;
+use crate::{AppError, Result};
+use crate::i18n::Message;
+use crate::domain::entities::{AuthEvent, AuthOutcome, Characteristic, LoginAttempt, User};
+use crate::domain::lockout_policy::LockoutPolicy;
+use crate::domain::repositories::{AuthEventRepository, LoginAttemptRepository};
+use crate::domain::services::LoginAttemptService;
+use crate::domain::values::users::UserName;
+use crate::infrastructure::sea_orm::repositories::login_attempt::{AuthEventRepositoryImpl, LoginAttemptRepositoryImpl};
+use crate::infrastructure::transaction::Transaction;
+
+/// クライアントの識別子の最大文字数
+const CLIENT_MAX: usize = 64;
+/// クライアントの識別子がない場合の値
+const UNKNOWN_CLIENT: &str = "unknown";
+
+///
+/// 認証の失敗回数とロックアウトのサービスの実装
+/// ロックの対象はユーザー名とクライアントで、存在しないユーザー名も同じように扱う
+///
+pub struct LoginAttemptServiceImpl<T: Transaction>{
+    attempts: Arc<dyn LoginAttemptRepository<Transaction=T>> ,
+    events: Arc<dyn AuthEventRepository<Transaction=T>> ,
+    policy: LockoutPolicy
+}
+impl LoginAttemptServiceImpl<DatabaseTransaction>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(policy: LockoutPolicy) -> Arc<dyn LoginAttemptService<Transaction=DatabaseTransaction>>{
+        Arc::new(Self{ attempts: LoginAttemptRepositoryImpl::new() , events: AuthEventRepositoryImpl::new() , policy })
+    }
+}
+impl<T: Transaction> LoginAttemptServiceImpl<T>{
+    // 指定されたRepositoryを利用するインスタンスを返す
+    pub fn with_repositories(attempts: Arc<dyn LoginAttemptRepository<Transaction=T>> ,
+                             events: Arc<dyn AuthEventRepository<Transaction=T>> ,
+                             policy: LockoutPolicy) -> Arc<dyn LoginAttemptService<Transaction=T>> {
+        Arc::new(Self{ attempts , events , policy })
+    }
+    // ユーザー名とクライアントのキー、ロックまでの失敗回数
+    fn keys(&self , user_name: &UserName , client: &str) -> [(String , u32); 2] {
+        [(LoginAttempt::user_key(user_name) , self.policy.user_threshold) ,
+         (LoginAttempt::client_key(&client_id(client)) , self.policy.client_threshold)]
+    }
+    // 認証イベントを記録する
+    async fn record(&self , tran: &T , user_name: &UserName , user: Option<&User> , client: &str ,
+                    outcome: AuthOutcome , now: i64) -> Result<()> {
+        let event = AuthEvent::new(user_name , user.map(|user| user.get()) , &client_id(client) , outcome , now);
+        self.events.insert(tran , &event).await
+    }
+}
+#[async_trait]
+impl<T: Transaction> LoginAttemptService for LoginAttemptServiceImpl<T>{
+    type Transaction = T;
+
+    async fn check(&self, tran: &Self::Transaction, user_name: &UserName, client: &str) -> Result<()> {
+        let now = now();
+        let mut locked: Option<u64> = None;
+        for (key , _) in self.keys(user_name , client) {
+            if let Some(seconds) = self.attempts.select_by_key(tran , &key).await?.and_then(|attempt| attempt.locked(now)) {
+                locked = Some(locked.unwrap_or_default().max(seconds));
+            }
+        }
+        match locked {
+            Some(seconds) => {
+                self.record(tran , user_name , None , client , AuthOutcome::Locked , now).await?;
+                Err(AppError::Unauthorized(Message::new("user.locked").with_param("seconds" , seconds)))
+            },
+            None => Ok(())
+        }
+    }
+
+    async fn succeeded(&self, tran: &Self::Transaction, user: &User, client: &str) -> Result<()> {
+        // クライアントの失敗回数は他のユーザー名での失敗を含むため消去しない
+        self.attempts.delete(tran , &LoginAttempt::user_key(&user.user_name)).await?;
+        self.record(tran , &user.user_name , Some(user) , client , AuthOutcome::Success , now()).await
+    }
+
+    async fn failed(&self, tran: &Self::Transaction, user_name: &UserName, client: &str) -> Result<()> {
+        let now = now();
+        // 同時に失敗した試行が同じ回数を読まないよう、ロックして数える
+        for (key , threshold) in self.keys(user_name , client) {
+            let mut attempt = self.attempts.select_for_update(tran , &key).await?;
+            attempt.fail(now , threshold , &self.policy);
+            self.attempts.save(tran , &attempt).await?;
+        }
+        self.record(tran , user_name , None , client , AuthOutcome::Failure , now).await
+    }
+}
+// 保存するクライアントの識別子、長すぎる値は切り詰める
+fn client_id(client: &str) -> String {
+    match client.trim() {
+        "" => String::from(UNKNOWN_CLIENT) ,
+        client => client.chars().take(CLIENT_MAX).collect()
+    }
+}
+// 現在時刻(UNIX時間)
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs() as i64).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::infrastructure::memory::database::MemoryDatabase;
+    use crate::infrastructure::memory::repositories::login_attempt::{AuthEventRepositoryImpl, LoginAttemptRepositoryImpl};
+    use crate::infrastructure::transaction::TransactionManager;
+
+    #[actix::test]
+    async fn lockout() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let policy = LockoutPolicy{ user_threshold: 3 , client_threshold: 5 , ..Default::default() };
+        let service = LoginAttemptServiceImpl::with_repositories(
+            LoginAttemptRepositoryImpl::new() , AuthEventRepositoryImpl::new() , policy);
+        let tran = database.begin().await?;
+        let user_name = UserName::try_from(String::from("user001"))?;
+        for _ in 0..2 {
+            service.check(&tran , &user_name , "192.0.2.1").await?;
+            service.failed(&tran , &user_name , "192.0.2.1").await?;
+        }
+        // 失敗回数がしきい値に達するとロックされ、ロック中の試行も記録する
+        service.failed(&tran , &user_name , "192.0.2.1").await?;
+        let error = service.check(&tran , &user_name , "192.0.2.2").await.err().unwrap();
+        assert_eq!(error.to_string() , "認証の失敗が続いたためロックされています。60秒後に再度お試し下さい。");
+        let store = tran.store()?.clone();
+        assert_eq!(store.login_attempts["user:user001"].failures , 3);
+        assert_eq!(store.login_attempts["client:192.0.2.1"].failures , 3);
+        let outcomes: Vec<AuthOutcome> = store.auth_events.iter().map(|event| event.outcome).collect();
+        assert_eq!(outcomes , vec![AuthOutcome::Failure , AuthOutcome::Failure , AuthOutcome::Failure , AuthOutcome::Locked]);
+        // クライアントごとの失敗回数は異なるユーザー名でも数える
+        let other = UserName::try_from(String::from("user002"))?;
+        service.failed(&tran , &other , "192.0.2.1").await?;
+        service.failed(&tran , &other , "192.0.2.1").await?;
+        assert!(service.check(&tran , &UserName::try_from(String::from("user003"))? , "192.0.2.1").await.is_err());
+        assert!(service.check(&tran , &UserName::try_from(String::from("user003"))? , "192.0.2.3").await.is_ok());
+        Ok(())
+    }
+
+    #[actix::test]
+    async fn succeeded() -> Result<()> {
+        let database = MemoryDatabase::with_fixtures()?;
+        let service = LoginAttemptServiceImpl::with_repositories(
+            LoginAttemptRepositoryImpl::new() , AuthEventRepositoryImpl::new() , LockoutPolicy::default());
+        let tran = database.begin().await?;
+        let user = tran.store()?.users.values().next().unwrap().clone();
+        service.failed(&tran , &user.user_name , "").await?;
+        service.succeeded(&tran , &user , "").await?;
+        let store = tran.store()?.clone();
+        // ユーザー名の失敗回数は消去し、クライアントの失敗回数は残す
+        assert!(!store.login_attempts.contains_key(&LoginAttempt::user_key(&user.user_name)));
+        assert_eq!(store.login_attempts["client:unknown"].failures , 1);
+        assert_eq!(store.auth_events[1].user_id , Some(user.get()));
+        assert_eq!(client_id(&"x".repeat(100)).len() , CLIENT_MAX);
+        Ok(())
+    }
+}
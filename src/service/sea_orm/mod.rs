@@ -1,3 +1,5 @@
 pub mod category;
 pub mod product;
-pub mod user;
\ No newline at end of file
+pub mod token;
+pub mod user;
+pub mod login_attempt;
\ No newline at end of file
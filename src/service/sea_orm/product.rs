@@ -1,74 +1,99 @@
 use std::sync::Arc;
 use async_trait::async_trait;
-use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use sea_orm::DatabaseTransaction;
 use crate::{AppError, Result};
+use crate::i18n::Message;
 use crate::domain::entities::Product;
 use crate::domain::repositories::ProductRepository;
 use crate::domain::services::ProductService;
-use crate::domain::values::products::ProductName;
+use crate::domain::values::pages::{Page, PageRequest};
+use crate::domain::values::products::{ProductId, ProductName, ProductSearchCriteria, ProductSortKey};
 use crate::domain::values::ValueInto;
 use crate::infrastructure::sea_orm::repositories::product::ProductRepositoryImpl;
+use crate::infrastructure::transaction::Transaction;
 
 ///
 /// 商品サービスの実装
+/// トランザクションの開始と終了は呼び出し側(アプリケーションサービス)が行う
 ///
-pub struct ProductServiceImpl{
+pub struct ProductServiceImpl<T: Transaction>{
     // サービスで利用するリポジトリ
-    repository: Arc<dyn ProductRepository<Transaction=DatabaseTransaction>>
+    repository: Arc<dyn ProductRepository<Transaction=T>>
 }
-impl ProductServiceImpl{
+impl ProductServiceImpl<DatabaseTransaction>{
     // インスタンスをProductService型に変換して返す
-    pub fn new() -> Arc<dyn ProductService<Database=DatabaseConnection>> {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn ProductService<Transaction=DatabaseTransaction>> {
         // Repositoryを生成してフィールドにセットする
         Arc::new(Self{ repository: ProductRepositoryImpl::new() })
     }
 }
+impl<T: Transaction> ProductServiceImpl<T>{
+    // 指定されたRepositoryを利用するインスタンスを返す
+    pub fn with_repository(repository: Arc<dyn ProductRepository<Transaction=T>>)
+        -> Arc<dyn ProductService<Transaction=T>> {
+        Arc::new(Self{ repository })
+    }
+}
 #[async_trait]
-impl ProductService for ProductServiceImpl{
-    type Database = DatabaseConnection;
-    // 指定されたキーワードの商品を取得する
-    async fn by_keyword(&self, db: &Self::Database, keyword: &ProductName) -> Result<Vec<Product>> {
-        // トランザクションを開始する
-        let tran = match db.begin().await {
-            Ok(tran) => tran ,
-            Err(error) => return Err(AppError::from(error)) // 内部エラーを返す
-        };
-        // Repositoryのメソッドを利用してキーワード検索する
-        let products = self.repository.select_by_name_like(&tran, keyword).await?;
-        if products.is_empty() {
+impl<T: Transaction> ProductService for ProductServiceImpl<T>{
+    type Transaction = T;
+    // 検索条件に該当する商品を取得する
+    async fn by_criteria(&self, tran: &Self::Transaction, criteria: &ProductSearchCriteria ,
+                         page: &PageRequest , sort: &ProductSortKey) -> Result<Page<Product>> {
+        // Repositoryのメソッドを利用して検索する
+        let products = self.repository.select_by_criteria(tran, criteria , page , sort).await?;
+        if products.total_count == 0 {
             // 結果が空の場合、検索エラーメッセージを返す
-            Err(AppError::SearchError(format!("キーワード:{} を含んだ商品は見つかりません。", keyword.value())))
+            Err(AppError::NotFound(Message::new("product.search_not_found")))
         } else {
             Ok(products)  // 空でなければそのまま結果を返す
         }
     }
     // 商品を永続化する
-    async fn register(&self, db: &Self::Database , product: &Product) -> Result<Product> {
-        // トランザクションを開始する
-        let tran = match db.begin().await {
-            Ok(tran) => tran ,
-            Err(error) => return Err(AppError::from(error))
-        };
+    async fn register(&self, tran: &Self::Transaction , product: &Product) -> Result<Product> {
         // Repositoryを利用して商品を永続化する
-        let new_product= self.repository.insert(&tran , product).await?;
-        // トランザクションをコミットする
-        match tran.commit().await{
-            Ok(_) => Ok(new_product) ,
-            Err(error) => Err(AppError::from(error))
+        // 存在確認の後に他のトランザクションが登録した場合は一意制約違反になるため、存在確認と同じエラーにする
+        match self.repository.insert(tran , product).await {
+            Err(AppError::Conflict(_)) => Err(duplicate(&product.name)) ,
+            result => result
         }
     }
     // 商品の存在確認する
-    async fn exists(&self, db: &Self::Database , name: &ProductName) -> Result<()> {
-        // トランザクションを開始する
-        let tran = match db.begin().await {
-            Ok(tran) => tran ,
-            Err(error) => return Err(AppError::from(error))
-        };
+    async fn exists(&self, tran: &Self::Transaction , name: &ProductName) -> Result<()> {
         // 同一名称の商品が存在するか確認する
-        if self.repository.exists(&tran , name).await? {
-            Err(AppError::RegisterError(format!("{}は登録済です。",name.value())))
+        if self.repository.exists(tran , name).await? {
+            Err(duplicate(name))
         }else{
             Ok(())
         }
     }
-}
\ No newline at end of file
+    // 指定された商品番号の商品を取得する
+    async fn by_id(&self, tran: &Self::Transaction, id: &ProductId) -> Result<Product> {
+        match self.repository.select_by_id(tran , id).await? {
+            Some(product) => Ok(product) ,
+            None => Err(AppError::NotFound(Message::new("product.not_found").with_param("id" , id.value())))
+        }
+    }
+    // 商品を変更する
+    async fn update(&self, tran: &Self::Transaction, product: &Product) -> Result<Product> {
+        // Repositoryを利用して商品を変更する
+        // 存在確認の後に他のトランザクションが同じ名称に変更した場合も存在確認と同じエラーにする
+        match self.repository.update(tran , product).await {
+            Err(AppError::Conflict(_)) => Err(duplicate(&product.name)) ,
+            result => result
+        }
+    }
+    // 指定された商品番号の商品を削除する
+    async fn delete(&self, tran: &Self::Transaction, id: &ProductId) -> Result<()> {
+        // 削除対象が存在しない場合は検索エラーを返す
+        if !self.repository.delete(tran , id).await? {
+            return Err(AppError::NotFound(Message::new("product.not_found").with_param("id" , id.value())));
+        }
+        Ok(())
+    }
+}
+// 同一名称の商品が登録済であることを表すエラー
+fn duplicate(name: &ProductName) -> AppError {
+    AppError::Conflict(Message::new("product.duplicate").with_param("name" , name.value()))
+}
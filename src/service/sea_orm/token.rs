@@ -0,0 +1,160 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use async_trait::async_trait;
+use easy_hasher::easy_hasher::sha3_512;
+use sea_orm::DatabaseTransaction;
+use crate::{AppError, Result};
+use crate::i18n::Message;
+use crate::domain::entities::RefreshToken;
+use crate::domain::repositories::{RefreshTokenRepository, RevokedTokenRepository};
+use crate::domain::services::TokenService;
+use crate::domain::values::users::UserId;
+use crate::infrastructure::sea_orm::repositories::token::{RefreshTokenRepositoryImpl, RevokedTokenRepositoryImpl};
+use crate::infrastructure::transaction::Transaction;
+
+/// リフレッシュトークンのバイト数
+const TOKEN_BYTES: usize = 32;
+
+///
+/// トークンサービスの実装
+/// リフレッシュトークンは推測できない乱数で、保存するのはハッシュ値のみとする
+///
+pub struct TokenServiceImpl<T: Transaction>{
+    refresh_tokens: Arc<dyn RefreshTokenRepository<Transaction=T>> ,
+    revoked_tokens: Arc<dyn RevokedTokenRepository<Transaction=T>> ,
+    // リフレッシュトークンの有効期間(秒)
+    expires_in: u64
+}
+impl TokenServiceImpl<DatabaseTransaction>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(expires_in: u64) -> Arc<dyn TokenService<Transaction=DatabaseTransaction>>{
+        Arc::new(Self{ refresh_tokens: RefreshTokenRepositoryImpl::new() ,
+                       revoked_tokens: RevokedTokenRepositoryImpl::new() , expires_in })
+    }
+}
+impl<T: Transaction> TokenServiceImpl<T>{
+    // 指定されたRepositoryを利用するインスタンスを返す
+    pub fn with_repositories(refresh_tokens: Arc<dyn RefreshTokenRepository<Transaction=T>> ,
+                             revoked_tokens: Arc<dyn RevokedTokenRepository<Transaction=T>> ,
+                             expires_in: u64) -> Arc<dyn TokenService<Transaction=T>> {
+        Arc::new(Self{ refresh_tokens , revoked_tokens , expires_in })
+    }
+    // 新しいトークンとそのハッシュ値を生成する
+    fn generate() -> (String , String) {
+        let mut bytes = [0u8; TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|byte| format!("{:02x}" , byte)).collect();
+        let hash = Self::hash(&token);
+        (token , hash)
+    }
+    // トークンのハッシュ値
+    fn hash(token: &str) -> String {
+        sha3_512(&token.to_string()).to_hex_string()
+    }
+    // 有効期限(UNIX時間)
+    fn expires_at(&self) -> i64 {
+        now() + self.expires_in as i64
+    }
+}
+#[async_trait]
+impl<T: Transaction> TokenService for TokenServiceImpl<T>{
+    type Transaction = T;
+
+    async fn issue(&self, tran: &Self::Transaction, user_id: &UserId) -> Result<String> {
+        let (token , hash) = Self::generate();
+        self.refresh_tokens.insert(tran , &RefreshToken::new(hash , user_id.clone() , self.expires_at())).await?;
+        Ok(token)
+    }
+
+    async fn rotate(&self, tran: &Self::Transaction, token: &str) -> Result<(String , UserId)> {
+        let current = match self.refresh_tokens.select_by_hash(tran , &Self::hash(token)).await? {
+            Some(current) => current ,
+            None => return Err(AppError::Unauthorized(Message::new("token.refresh_invalid")))
+        };
+        if current.is_expired(now()) {
+            return Err(AppError::Unauthorized(Message::new("token.refresh_expired")));
+        }
+        let (next , hash) = Self::generate();
+        // 使用済のトークンは漏洩したものとみなし、同じファミリーのトークンをすべて失効させる
+        if current.is_used() || !self.refresh_tokens.replace(tran , &current.token_hash , &hash).await? {
+            self.refresh_tokens.revoke_family(tran , &current.family_id).await?;
+            return Err(AppError::Unauthorized(Message::new("token.refresh_used")));
+        }
+        self.refresh_tokens.insert(tran , &current.rotate(hash , self.expires_at())).await?;
+        Ok((next , current.user_id))
+    }
+
+    async fn revoke(&self, tran: &Self::Transaction, token: &str) -> Result<()> {
+        if let Some(current) = self.refresh_tokens.select_by_hash(tran , &Self::hash(token)).await? {
+            self.refresh_tokens.revoke_family(tran , &current.family_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn deny(&self, tran: &Self::Transaction, jti: &str, expires_at: i64) -> Result<()> {
+        // 有効期限を過ぎたトークンは検証で拒否されるため保持しない
+        self.revoked_tokens.delete_expired(tran , now()).await?;
+        self.revoked_tokens.insert(tran , jti , expires_at).await
+    }
+
+    async fn denied(&self, tran: &Self::Transaction) -> Result<Vec<(String , i64)>> {
+        self.revoked_tokens.select_active(tran , now()).await
+    }
+}
+// 現在時刻(UNIX時間)
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs() as i64).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use crate::infrastructure::memory::database::{MemoryDatabase, MemoryTransaction};
+    use crate::infrastructure::memory::repositories::token;
+    use crate::infrastructure::transaction::TransactionManager;
+
+    fn service() -> Arc<dyn TokenService<Transaction=MemoryTransaction>> {
+        TokenServiceImpl::with_repositories(token::RefreshTokenRepositoryImpl::new() ,
+                                            token::RevokedTokenRepositoryImpl::new() , 600)
+    }
+    fn user_id() -> UserId {
+        UserId::try_from(String::from("5772a800-fef1-40bf-888b-68fddd29d881")).unwrap()
+    }
+
+    #[actix::test]
+    async fn issue_and_rotate() -> Result<()> {
+        let tran = MemoryDatabase::new().begin().await?;
+        let service = service();
+        let first = service.issue(&tran , &user_id()).await?;
+        assert_eq!(first.len() , TOKEN_BYTES * 2);
+        // トークン本体は保存しない
+        assert!(!tran.store()?.refresh_tokens.contains_key(&first));
+        let (second , rotated_user) = service.rotate(&tran , &first).await?;
+        assert_eq!(rotated_user , user_id());
+        assert_ne!(first , second);
+        let (third , _) = service.rotate(&tran , &second).await?;
+        // ローテーション済のトークンの再利用はファミリー全体を失効させる
+        assert!(matches!(service.rotate(&tran , &first).await , Err(AppError::Unauthorized(_))));
+        assert!(tran.store()?.refresh_tokens.values().all(|token| token.revoked));
+        assert!(service.rotate(&tran , &third).await.is_err());
+        assert!(service.rotate(&tran , "unknown").await.is_err());
+        Ok(())
+    }
+
+    #[actix::test]
+    async fn revoke_and_deny() -> Result<()> {
+        let tran = MemoryDatabase::new().begin().await?;
+        let service = service();
+        let token = service.issue(&tran , &user_id()).await?;
+        service.revoke(&tran , &token).await?;
+        assert!(service.rotate(&tran , &token).await.is_err());
+        // 期限切れのjtiは保持しない
+        tran.store()?.revoked_tokens.insert(String::from("expired") , 1);
+        service.deny(&tran , "jti-001" , now() + 60).await?;
+        assert_eq!(service.denied(&tran).await?.len() , 1);
+        assert!(!tran.store()?.revoked_tokens.contains_key("expired"));
+        Ok(())
+    }
+}
@@ -1,56 +1,100 @@
-use std::borrow::Borrow;
 use std::sync::Arc;
 use async_trait::async_trait;
 use crate::{AppError, Result};
-use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use crate::i18n::Message;
+use sea_orm::DatabaseTransaction;
 use crate::domain::entities::User;
+use crate::domain::hasher::PasswordHasher;
 use crate::domain::repositories::UserRepository;
 use crate::domain::services::UserService;
+use crate::domain::values::users::{Credentials, Mail, PasswordChange, UserId, UserName};
 use crate::domain::values::ValueInto;
+use crate::infrastructure::hasher_impl::Argon2PasswordHasher;
 use crate::infrastructure::sea_orm::repositories::user::UserRepositoryImpl;
+use crate::infrastructure::transaction::Transaction;
 
 ///
 /// ユーザーサービスの実装
 ///
-pub struct UserServiceImpl{
-    repository: Arc<dyn UserRepository<Transaction=DatabaseTransaction>>
+pub struct UserServiceImpl<T: Transaction>{
+    repository: Arc<dyn UserRepository<Transaction=T>> ,
+    // パスワードのハッシュ変換と照合
+    hasher: Arc<dyn PasswordHasher>
 }
-impl UserServiceImpl{
-    pub fn new() -> Arc<dyn UserService<Database=DatabaseConnection>>{
-        Arc::new(Self{ repository: UserRepositoryImpl::new() })
+impl UserServiceImpl<DatabaseTransaction>{
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn UserService<Transaction=DatabaseTransaction>>{
+        Arc::new(Self{ repository: UserRepositoryImpl::new() , hasher: Argon2PasswordHasher::new() })
+    }
+}
+impl<T: Transaction> UserServiceImpl<T>{
+    // 指定されたRepositoryを利用するインスタンスを返す
+    pub fn with_repository(repository: Arc<dyn UserRepository<Transaction=T>>)
+        -> Arc<dyn UserService<Transaction=T>> {
+        Arc::new(Self{ repository , hasher: Argon2PasswordHasher::new() })
     }
 }
 #[async_trait]
-impl UserService for UserServiceImpl{
-    type Database = sea_orm::DatabaseConnection;
-    async fn register(&self, db: &Self::Database , user: &User) -> Result<User> {
-        let tran = match db.begin().await{
-            Ok(tran) => tran ,
-            Err(error) => return Err(AppError::from(error))
+impl<T: Transaction> UserService for UserServiceImpl<T>{
+    type Transaction = T;
+    async fn register(&self, tran: &Self::Transaction , user: &User) -> Result<User> {
+        // パスワードをハッシュ変換して永続化する
+        let mut new_user = user.clone();
+        new_user.password = self.hasher.hash(&user.password)?;
+        self.repository.insert(tran , &new_user).await
+    }
+
+    // ユーザー名の存在確認する
+    async fn exists_name(&self, tran: &Self::Transaction, user_name: &UserName) -> Result<()> {
+        if self.repository.exists_by_name(tran , user_name).await? {
+            Err(AppError::Conflict(Message::new("user.name_duplicate").with_param("name" , user_name.value())))
+        }else{
+            Ok(())
+        }
+    }
+    // メールアドレスの存在確認する
+    async fn exists_mail(&self, tran: &Self::Transaction, mail: &Mail) -> Result<()> {
+        if self.repository.exists_by_mail(tran , mail).await? {
+            Err(AppError::Conflict(Message::new("user.mail_duplicate").with_param("mail" , mail.value())))
+        }else{
+            Ok(())
+        }
+    }
+
+    // ユーザー名の存在が分からないよう、失敗の理由によらず同じエラーを返す
+    async fn authenticate(&self, tran: &Self::Transaction , credentials: &Credentials) -> Result<User> {
+        let mut get_user = match self.repository.select_by_name(tran , &credentials.user_name).await? {
+            Some(get_user) => get_user ,
+            None => {
+                // 照合と同程度の時間をかけ、応答時間からも区別できないようにする
+                self.hasher.hash(&credentials.password)?;
+                return Err(AppError::Unauthorized(Message::new("user.invalid_credentials")));
+            }
         };
-        let new_user = self.repository.insert(&tran , user).await?;
-        match tran.commit().await{
-            Ok(_) => Ok(new_user),
-            Err(error) => Err(AppError::from(error))
+        if !self.hasher.verify(&credentials.password , &get_user.password)? {
+            return Err(AppError::Unauthorized(Message::new("user.invalid_credentials")));
+        }
+        // 旧方式のハッシュは認証に成功した時点で現在の方式に変換する
+        if self.hasher.needs_rehash(&get_user.password) {
+            get_user.password = self.hasher.hash(&credentials.password)?;
+            self.repository.update_password(tran , &get_user).await?;
+        }
+        Ok(get_user)
+    }
+
+    async fn by_id(&self, tran: &Self::Transaction, user_id: &UserId) -> Result<User> {
+        match self.repository.select_by_id(tran , user_id).await? {
+            Some(user) => Ok(user) ,
+            None => Err(AppError::NotFound(Message::new("user.not_found").with_param("id" , user_id.value())))
         }
     }
 
-    async fn authenticate(&self, db: &Self::Database , user: &User) -> Result<User> {
-        match db.begin().await{
-            Ok(tran) =>{
-                let opt_user = self.repository.select_by_name(&tran , user.user_name.borrow()).await?;
-                match opt_user {
-                    Some(get_user) => {
-                        if user.password.value().eq(&get_user.password.value()){
-                            Ok(get_user.clone())
-                        }else{
-                            Err(AppError::AuthenticateError(String::from("パスワードが異なります。")))
-                        }
-                    },
-                    None => Err(AppError::AuthenticateError(String::from("存在しないユーザー名です。")))
-                }
-            } ,
-            Err(error) => Err(AppError::from(error))
+    async fn change_password(&self, tran: &Self::Transaction, user: &User, change: &PasswordChange) -> Result<User> {
+        if !self.hasher.verify(&change.current , &user.password)? {
+            return Err(AppError::Unauthorized(Message::new("user.password_mismatch")));
         }
+        let mut new_user = user.clone();
+        new_user.password = self.hasher.hash(&change.password)?;
+        self.repository.update_password(tran , &new_user).await
     }
 }
\ No newline at end of file